@@ -1,12 +1,59 @@
+#[cfg(any(feature = "backup", feature = "restore"))]
+use anyhow::bail;
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+#[cfg(any(feature = "backup", feature = "restore"))]
 use colored::Colorize;
 #[cfg(feature = "backup")]
-use snap_kube::backup::{backup_operator::BackupOperator, backup_payload::BackupPayload};
+use snap_kube::aws_ops::ebs::{parse_tag, EbsSnapshotTags, SnapshotCopyTarget};
 #[cfg(feature = "restore")]
-use snap_kube::k8s_ops::vsc::retain_policy::VSCRetainPolicy;
+use snap_kube::aws_ops::ebs::{EbsSnapshotSource, EbsVolumeSettings};
+#[cfg(feature = "backup")]
+use snap_kube::backup::{
+    backup_hooks::BackupHooks, backup_operator::BackupOperator, backup_payload::BackupPayload,
+};
+#[cfg(any(feature = "backup", feature = "restore"))]
+use snap_kube::catalog::backup_catalog::CatalogLocation;
+#[cfg(feature = "full")]
+use snap_kube::controller::{controller_operator::ControllerOperator, crds::crds_yaml};
+#[cfg(feature = "backup")]
+use snap_kube::daemon::{
+    daemon_config::DaemonConfig,
+    daemon_operator::{DaemonOperator, DaemonPayload},
+};
+#[cfg(feature = "backup")]
+use snap_kube::k8s_ops::ns::namespaces::NamespaceSelection;
 #[cfg(feature = "restore")]
-use snap_kube::restore::{restore_operator::RestoreOperator, restore_payload::RestorePayload};
+use snap_kube::k8s_ops::pvc::pvc_rename::PvcRename;
+#[cfg(any(feature = "backup", feature = "restore"))]
+use snap_kube::k8s_ops::pvc::pvc_selector::PvcSelector;
+#[cfg(feature = "restore")]
+use snap_kube::k8s_ops::pvc::pvc_template::{
+    parse_key_value, PvcTemplate, ACCESS_MODES, VOLUME_MODES,
+};
+#[cfg(feature = "backup")]
+use snap_kube::k8s_ops::vs::volume_snapshot_naming::{
+    VolumeSnapshotNameTemplate, DEFAULT_VS_NAME_TEMPLATE,
+};
+#[cfg(feature = "restore")]
+use snap_kube::k8s_ops::{
+    pv::reclaim_policy::PVReclaimPolicy, vsc::retain_policy::VSCRetainPolicy,
+};
+#[cfg(feature = "backup")]
+use snap_kube::prune::{
+    prune_operator::PruneOperator, prune_payload::PrunePayload, retention_policy::RetentionPolicy,
+};
+#[cfg(feature = "restore")]
+use snap_kube::restore::{
+    conflict_policy::ConflictPolicy, restore_operator::RestoreOperator,
+    restore_payload::RestorePayload, restore_strategy::RestoreStrategy,
+};
+#[cfg(feature = "backup")]
+use snap_kube::share::{share_operator::ShareOperator, share_payload::SharePayload};
+use std::path::PathBuf;
+#[cfg(any(feature = "backup", feature = "restore"))]
+use std::time::Duration;
+#[cfg(any(feature = "backup", feature = "restore"))]
 use tracing::info;
 
 #[derive(Parser)]
@@ -18,15 +65,15 @@ struct Cli {
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum Commands {
     #[cfg(feature = "backup")]
     Backup {
         /// Region where the EBS volumes are stored
         #[arg(long, required = false, default_value = "eu-west-1")]
         region: String,
-        /// Source namespace
-        #[arg(long, required = true)]
-        source_ns: String,
+        #[command(flatten)]
+        namespace_selection_args: NamespaceSelectionArgs,
         /// VolumeSnapshotClass name
         #[arg(long, required = true)]
         volume_snapshot_class: String,
@@ -41,15 +88,113 @@ enum Commands {
             conflicts_with = "pvc_name"
         )]
         include_all_pvcs: bool,
+        #[command(flatten)]
+        pvc_selector_args: PvcSelectorArgs,
         /// VolumeSnapshot name prefix
         #[arg(long, required = true)]
         volume_snapshot_name_prefix: String,
+        /// VolumeSnapshot name template, supporting the {prefix}, {namespace}, {pvc}, {timestamp} and {hash} placeholders
+        #[arg(long, required = false, default_value = DEFAULT_VS_NAME_TEMPLATE)]
+        volume_snapshot_name_template: String,
+        /// Maximum number of PVCs snapshotted concurrently
+        #[arg(long, required = false, default_value = "4")]
+        max_concurrency: usize,
+        /// Maximum time in seconds to wait for each VolumeSnapshot to be ready
+        #[arg(long, required = false, default_value = "3600")]
+        snapshot_timeout_secs: u64,
+        #[command(flatten)]
+        catalog_args: CatalogArgs,
+        /// YAML file of the hooks run in the pods mounting the PVCs around their VolumeSnapshots
+        #[arg(long, required = false)]
+        hooks_file: Option<PathBuf>,
+        /// Scale down the Deployments and StatefulSets mounting the PVCs until their VolumeSnapshots are bound
+        #[arg(long, required = false, default_value = "false")]
+        scale_down: bool,
+        /// Maximum time in seconds to wait for the pods of the scaled down workloads to terminate
+        #[arg(long, required = false, default_value = "300")]
+        scale_down_timeout_secs: u64,
+        /// VolumeGroupSnapshotClass used to snapshot the PVCs matching --selector of each namespace together
+        #[arg(long, required = false)]
+        volume_group_snapshot_class: Option<String>,
+        #[command(flatten)]
+        ebs_tag_args: EbsTagArgs,
+    },
+    #[cfg(feature = "backup")]
+    Daemon {
+        /// Region where the EBS volumes are stored
+        #[arg(long, required = false, default_value = "eu-west-1")]
+        region: String,
+        /// YAML file with the backup schedules
+        #[arg(long, required = true)]
+        config: PathBuf,
+        /// JSON file where the last run of each schedule is persisted, to catch up on missed runs after a restart
+        #[arg(long, required = false)]
+        state_file: Option<PathBuf>,
+        #[command(flatten)]
+        catalog_args: CatalogArgs,
+    },
+    #[cfg(feature = "backup")]
+    Prune {
+        /// Namespace of the VolumeSnapshots
+        #[arg(long, required = true)]
+        namespace: String,
+        /// Only prune the VolumeSnapshots with this name prefix
+        #[arg(long, required = false)]
+        volume_snapshot_name_prefix: Option<String>,
+        /// Keep the N most recent VolumeSnapshots of each PVC
+        #[arg(long, required = false)]
+        keep_last: Option<usize>,
+        /// Keep the VolumeSnapshots younger than this number of days
+        #[arg(long, required = false)]
+        max_age_days: Option<u32>,
+        /// Keep the most recent VolumeSnapshot of each of the last N days
+        #[arg(long, required = false)]
+        keep_daily: Option<usize>,
+        /// Keep the most recent VolumeSnapshot of each of the last N weeks
+        #[arg(long, required = false)]
+        keep_weekly: Option<usize>,
+        /// Keep the most recent VolumeSnapshot of each of the last N months
+        #[arg(long, required = false)]
+        keep_monthly: Option<usize>,
+        /// Only report the VolumeSnapshots that would be deleted
+        #[arg(long, required = false, default_value = "false")]
+        dry_run: bool,
+        #[command(flatten)]
+        catalog_args: CatalogArgs,
+    },
+    #[cfg(feature = "backup")]
+    Recover {
+        #[command(flatten)]
+        namespace_selection_args: NamespaceSelectionArgs,
+    },
+    #[cfg(feature = "backup")]
+    Share {
+        /// Region where the EBS snapshots are stored
+        #[arg(long, required = false, default_value = "eu-west-1")]
+        region: String,
+        /// ID of an EBS snapshot to share, can be repeated
+        #[arg(long = "snapshot-id", required_unless_present = "backup_id")]
+        snapshot_ids: Vec<String>,
+        /// Share the EBS snapshots of a backup ID recorded in the catalog, along with their copies
+        #[arg(long, required = false)]
+        backup_id: Option<String>,
+        #[command(flatten)]
+        catalog_args: CatalogArgs,
+        /// ID of an AWS account to share the EBS snapshots with, can be repeated
+        #[arg(long = "account-id", required = true)]
+        account_ids: Vec<String>,
+        /// Revoke the share of the EBS snapshots and of their KMS keys
+        #[arg(long, required = false, default_value = "false")]
+        revoke: bool,
     },
     #[cfg(feature = "restore")]
     Restore {
+        /// Region where the EBS volumes are stored
+        #[arg(long, required = false, default_value = "eu-west-1")]
+        region: String,
         /// Source namespace
-        #[arg(long, required = true)]
-        source_ns: String,
+        #[arg(long, required_unless_present = "source_snapshot_id")]
+        source_ns: Option<String>,
         /// Target namespace
         #[arg(long, required = true)]
         target_ns: String,
@@ -67,19 +212,62 @@ enum Commands {
             conflicts_with = "pvc_name"
         )]
         include_all_pvcs: bool,
+        #[command(flatten)]
+        pvc_selector_args: PvcSelectorArgs,
         /// VolumeSnapshot name prefix
-        #[arg(long, required = true)]
-        volume_snapshot_name_prefix: String,
-        /// Target VolumeSnapshotContent name prefix
+        #[arg(long, required_unless_present_any = ["backup_id", "volume_group_snapshot", "source_snapshot_id"])]
+        volume_snapshot_name_prefix: Option<String>,
+        /// Target VolumeSnapshotContent name prefix, followed by the target namespace and the VolumeSnapshot name
         #[arg(long, required = true)]
         target_snapshot_content_name_prefix: String,
-        /// StorageClass name
+        /// StorageClass name, used for the PVCs whose source StorageClass is not mapped
         #[arg(long, required = true)]
         storage_class_name: String,
+        /// Target StorageClass of the PVCs of a source StorageClass, as `<source>=<target>`, e.g. `gp2=gp3`, can be repeated
+        #[arg(long = "storage-class-mapping", required = false, value_parser = parse_key_value)]
+        storage_class_mappings: Vec<(String, String)>,
         /// VSC Retain Policy
         #[arg(long, required = false, default_value = "delete")]
         #[clap(value_enum)]
         vsc_retain_policy: VSCRetainPolicy,
+        /// Maximum number of PVCs restored concurrently
+        #[arg(long, required = false, default_value = "4")]
+        max_concurrency: usize,
+        /// Restore the PVCs of a backup ID recorded in the catalog
+        #[arg(
+            long,
+            required = false,
+            conflicts_with_all = ["volume_snapshot_name_prefix", "include_all_pvcs"]
+        )]
+        backup_id: Option<String>,
+        #[command(flatten)]
+        catalog_args: CatalogArgs,
+        /// Restore every member PVC of a VolumeGroupSnapshot of the source namespace, with its original name
+        #[arg(
+            long,
+            required = false,
+            conflicts_with_all = ["pvc_name", "include_all_pvcs", "backup_id", "volume_snapshot_name_prefix"]
+        )]
+        volume_group_snapshot: Option<String>,
+        /// Maximum time in seconds to wait for the members of a VolumeGroupSnapshot to be Bound, or for the replaced PVCs to be deleted
+        #[arg(long, required = false, default_value = "600")]
+        bind_timeout_secs: u64,
+        /// What to do with the PVCs that already exist in the target namespace
+        #[arg(long, required = false, default_value = "fail")]
+        #[clap(value_enum)]
+        on_conflict: ConflictPolicy,
+        #[command(flatten)]
+        ebs_snapshot_source_args: EbsSnapshotSourceArgs,
+        /// How the PVCs are restored, static-volume creating the EBS volumes with EC2 for the clusters without the CSI snapshot controller, from a --backup-id or --from-ebs-snapshot
+        #[arg(long, required = false, default_value = "snapshot")]
+        #[clap(value_enum)]
+        restore_strategy: RestoreStrategy,
+        #[command(flatten)]
+        static_volume_args: StaticVolumeArgs,
+        #[command(flatten)]
+        pvc_override_args: PvcOverrideArgs,
+        #[command(flatten)]
+        pvc_rename_args: PvcRenameArgs,
     },
     #[cfg(feature = "full")]
     Full {
@@ -106,22 +294,354 @@ enum Commands {
             conflicts_with = "pvc_name"
         )]
         include_all_pvcs: bool,
+        #[command(flatten)]
+        pvc_selector_args: PvcSelectorArgs,
         /// VolumeSnapshot name prefix
         #[arg(long, required = true)]
         volume_snapshot_name_prefix: String,
-        /// Target VolumeSnapshotContent name prefix
+        /// VolumeSnapshot name template, supporting the {prefix}, {namespace}, {pvc}, {timestamp} and {hash} placeholders
+        #[arg(long, required = false, default_value = DEFAULT_VS_NAME_TEMPLATE)]
+        volume_snapshot_name_template: String,
+        /// Target VolumeSnapshotContent name prefix, followed by the target namespace and the VolumeSnapshot name
         #[arg(long, required = true)]
         target_snapshot_content_name_prefix: String,
-        /// StorageClass name
+        /// StorageClass name, used for the PVCs whose source StorageClass is not mapped
         #[arg(long, required = true)]
         storage_class_name: String,
+        /// Target StorageClass of the PVCs of a source StorageClass, as `<source>=<target>`, e.g. `gp2=gp3`, can be repeated
+        #[arg(long = "storage-class-mapping", required = false, value_parser = parse_key_value)]
+        storage_class_mappings: Vec<(String, String)>,
         /// VSC Retain Policy
         #[arg(long, required = false, default_value = "delete")]
         #[clap(value_enum)]
         vsc_retain_policy: VSCRetainPolicy,
+        /// Maximum number of PVCs backed up or restored concurrently
+        #[arg(long, required = false, default_value = "4")]
+        max_concurrency: usize,
+        /// Maximum time in seconds to wait for each VolumeSnapshot to be ready
+        #[arg(long, required = false, default_value = "3600")]
+        snapshot_timeout_secs: u64,
+        #[command(flatten)]
+        catalog_args: CatalogArgs,
+        /// YAML file of the hooks run in the pods mounting the PVCs around their VolumeSnapshots
+        #[arg(long, required = false)]
+        hooks_file: Option<PathBuf>,
+        /// Scale down the Deployments and StatefulSets mounting the PVCs until their VolumeSnapshots are bound
+        #[arg(long, required = false, default_value = "false")]
+        scale_down: bool,
+        /// Maximum time in seconds to wait for the pods of the scaled down workloads to terminate
+        #[arg(long, required = false, default_value = "300")]
+        scale_down_timeout_secs: u64,
+        #[command(flatten)]
+        ebs_tag_args: EbsTagArgs,
+        #[command(flatten)]
+        pvc_override_args: PvcOverrideArgs,
+        /// What to do with the PVCs that already exist in the target namespace
+        #[arg(long, required = false, default_value = "fail")]
+        #[clap(value_enum)]
+        on_conflict: ConflictPolicy,
+        /// Maximum time in seconds to wait for the replaced PVCs to be deleted
+        #[arg(long, required = false, default_value = "600")]
+        bind_timeout_secs: u64,
+        #[command(flatten)]
+        pvc_rename_args: PvcRenameArgs,
     },
+    #[cfg(feature = "full")]
+    Controller,
+    #[cfg(feature = "full")]
+    Crds,
+}
+
+// Arguments for selecting the namespaces to backup
+#[cfg(feature = "backup")]
+#[derive(Args)]
+struct NamespaceSelectionArgs {
+    /// Source namespace(s), comma separated or repeated
+    #[arg(
+        long,
+        required_unless_present_any = ["namespace_selector", "all_namespaces"],
+        value_delimiter = ',',
+        conflicts_with_all = ["namespace_selector", "all_namespaces"]
+    )]
+    source_ns: Vec<String>,
+    /// Label selector for the source namespaces
+    #[arg(long, required = false, conflicts_with = "all_namespaces")]
+    namespace_selector: Option<String>,
+    /// Backup all the namespaces of the cluster
+    #[arg(long, required = false, default_value = "false")]
+    all_namespaces: bool,
+    /// Namespaces excluded when backing up all the namespaces, comma separated or repeated
+    #[arg(
+        long,
+        required = false,
+        requires = "all_namespaces",
+        value_delimiter = ','
+    )]
+    exclude_ns: Vec<String>,
+}
+
+#[cfg(feature = "backup")]
+impl NamespaceSelectionArgs {
+    fn to_namespace_selection(&self) -> NamespaceSelection {
+        if self.all_namespaces {
+            NamespaceSelection::All {
+                exclude: self.exclude_ns.clone(),
+            }
+        } else if let Some(namespace_selector) = &self.namespace_selector {
+            NamespaceSelection::LabelSelector(namespace_selector.clone())
+        } else {
+            NamespaceSelection::List(self.source_ns.clone())
+        }
+    }
+}
+
+// Arguments for the tags and the copies of the EBS snapshots
+#[cfg(feature = "backup")]
+#[derive(Args)]
+struct EbsTagArgs {
+    /// Name of the cluster, tagged on the EBS snapshots
+    #[arg(long, required = false)]
+    cluster_name: Option<String>,
+    /// Extra tag applied to the EBS snapshots, as `<key>=<value>`, can be repeated
+    #[arg(long = "ebs-tag", required = false, value_parser = parse_tag)]
+    ebs_tags: Vec<(String, String)>,
+    /// Description of the EBS snapshots, tagged as `Description`
+    #[arg(long, required = false)]
+    ebs_snapshot_description: Option<String>,
+    /// Region the EBS snapshots are copied to, as `<region>` or `<region>=<kms-key-id>`, can be repeated
+    #[arg(long = "copy-to-region", required = false, value_parser = SnapshotCopyTarget::parse)]
+    snapshot_copy_targets: Vec<SnapshotCopyTarget>,
+}
+
+#[cfg(feature = "backup")]
+impl EbsTagArgs {
+    fn to_ebs_snapshot_tags(&self) -> Result<EbsSnapshotTags> {
+        EbsSnapshotTags::new(
+            self.cluster_name.clone(),
+            self.ebs_tags.iter().cloned().collect(),
+            self.ebs_snapshot_description.clone(),
+        )
+    }
+}
+
+// Arguments for an EBS snapshot restored from outside of the cluster
+#[cfg(feature = "restore")]
+#[derive(Args)]
+struct EbsSnapshotSourceArgs {
+    /// Restore the PVC named --pvc-name from this EBS snapshot, e.g. `snap-0123456789abcdef0`, importing it as a VolumeSnapshot of the target namespace
+    #[arg(
+        long,
+        visible_alias = "from-ebs-snapshot",
+        required = false,
+        requires = "pvc_name",
+        conflicts_with_all = ["backup_id", "volume_group_snapshot"]
+    )]
+    source_snapshot_id: Option<String>,
+    /// Region of the EBS snapshot, the snapshot is copied into --region if they differ
+    #[arg(long, required = false, requires = "source_snapshot_id")]
+    source_snapshot_region: Option<String>,
+    /// KMS key the copy of the EBS snapshot into --region is encrypted with
+    #[arg(long, required = false, requires = "source_snapshot_id")]
+    copy_kms_key_id: Option<String>,
+    /// Maximum time in seconds to wait for the copy of the EBS snapshot into --region
+    #[arg(long, required = false, default_value = "3600")]
+    copy_timeout_secs: u64,
+}
+
+#[cfg(feature = "restore")]
+impl EbsSnapshotSourceArgs {
+    fn to_ebs_snapshot_source(&self) -> Option<EbsSnapshotSource> {
+        self.source_snapshot_id
+            .as_ref()
+            .map(|snapshot_id| EbsSnapshotSource {
+                snapshot_id: snapshot_id.clone(),
+                region: self.source_snapshot_region.clone(),
+                kms_key_id: self.copy_kms_key_id.clone(),
+                copy_timeout_secs: self.copy_timeout_secs,
+            })
+    }
+}
+
+// Arguments for the EBS volumes created by the static volume restore strategy
+#[cfg(feature = "restore")]
+#[derive(Args)]
+struct StaticVolumeArgs {
+    /// Availability zone of the EBS volumes, required unless the StorageClass allows a single zone
+    #[arg(long, required = false)]
+    availability_zone: Option<String>,
+    /// Type of the EBS volumes, the `type` parameter of the StorageClass if not set
+    #[arg(long, required = false)]
+    volume_type: Option<String>,
+    /// Provisioned IOPS of the EBS volumes, the `iops` parameter of the StorageClass if not set
+    #[arg(long, required = false)]
+    volume_iops: Option<i32>,
+    /// Provisioned throughput of the EBS volumes in MiB/s, the `throughput` parameter of the StorageClass if not set
+    #[arg(long, required = false)]
+    volume_throughput: Option<i32>,
+    /// KMS key the EBS volumes are encrypted with, the `kmsKeyId` parameter of the StorageClass or the key of the snapshot if not set
+    #[arg(long, required = false)]
+    volume_kms_key_id: Option<String>,
+    /// Reclaim policy of the PersistentVolumes, the `reclaimPolicy` of the StorageClass if not set
+    #[arg(long, required = false)]
+    #[clap(value_enum)]
+    pv_reclaim_policy: Option<PVReclaimPolicy>,
+}
+
+#[cfg(feature = "restore")]
+impl StaticVolumeArgs {
+    fn to_ebs_volume_settings(&self) -> EbsVolumeSettings {
+        EbsVolumeSettings {
+            availability_zone: self.availability_zone.clone(),
+            volume_type: self.volume_type.clone(),
+            iops: self.volume_iops,
+            throughput: self.volume_throughput,
+            kms_key_id: self.volume_kms_key_id.clone(),
+        }
+    }
+}
+
+// Arguments overriding the spec, labels and annotations the restored PVCs take from the source PVCs
+#[cfg(feature = "restore")]
+#[derive(Args)]
+struct PvcOverrideArgs {
+    /// Access mode of the restored PVCs, can be repeated, the ones of the source PVC if not set
+    #[arg(long = "access-mode", required = false, value_parser = ACCESS_MODES)]
+    access_modes: Vec<String>,
+    /// Volume mode of the restored PVCs, the one of the source PVC if not set
+    #[arg(long, required = false, value_parser = VOLUME_MODES)]
+    volume_mode: Option<String>,
+    /// Label of the restored PVCs, as `<key>=<value>`, added to the ones of the source PVC, can be repeated
+    #[arg(long = "pvc-label", required = false, value_parser = parse_key_value)]
+    pvc_labels: Vec<(String, String)>,
+    /// Annotation of the restored PVCs, as `<key>=<value>`, added to the ones of the source PVC, can be repeated
+    #[arg(long = "pvc-annotation", required = false, value_parser = parse_key_value)]
+    pvc_annotations: Vec<(String, String)>,
+}
+
+#[cfg(feature = "restore")]
+impl PvcOverrideArgs {
+    fn to_pvc_template(&self) -> PvcTemplate {
+        PvcTemplate {
+            access_modes: self.access_modes.clone(),
+            volume_mode: self.volume_mode.clone(),
+            labels: self.pvc_labels.iter().cloned().collect(),
+            annotations: self.pvc_annotations.iter().cloned().collect(),
+        }
+    }
 }
 
+// Arguments renaming the restored PVCs
+#[cfg(feature = "restore")]
+#[derive(Args)]
+struct PvcRenameArgs {
+    /// New name of a restored PVC, as `<old>=<new>`, taking precedence over the other rename arguments, can be repeated
+    #[arg(long = "rename-pvc", required = false, value_parser = parse_key_value)]
+    rename_pvcs: Vec<(String, String)>,
+    /// Prefix added to the names of the restored PVCs
+    #[arg(long, required = false)]
+    pvc_name_prefix: Option<String>,
+    /// Suffix added to the names of the restored PVCs
+    #[arg(long, required = false)]
+    pvc_name_suffix: Option<String>,
+    /// Regex rewriting the names of the restored PVCs, e.g. `^data-(.*)$`
+    #[arg(long, required = false, requires = "pvc_name_replacement")]
+    pvc_name_pattern: Option<String>,
+    /// Replacement of the PVC name regex, with `$1` referring to its first capture group, e.g. `db-$1`
+    #[arg(long, required = false, requires = "pvc_name_pattern")]
+    pvc_name_replacement: Option<String>,
+}
+
+#[cfg(feature = "restore")]
+impl PvcRenameArgs {
+    fn to_pvc_rename(&self) -> PvcRename {
+        PvcRename {
+            mappings: self.rename_pvcs.iter().cloned().collect(),
+            prefix: self.pvc_name_prefix.clone(),
+            suffix: self.pvc_name_suffix.clone(),
+            pattern: self.pvc_name_pattern.clone(),
+            replacement: self.pvc_name_replacement.clone(),
+        }
+    }
+}
+
+// Arguments for the location of the backup catalog
+#[derive(Args)]
+struct CatalogArgs {
+    /// Local JSON file where the backup catalog is stored
+    #[arg(long, required = false, conflicts_with = "catalog_config_map")]
+    catalog_file: Option<PathBuf>,
+    /// ConfigMap where the backup catalog is stored, as `<namespace>/<name>`
+    #[arg(long, required = false)]
+    catalog_config_map: Option<String>,
+}
+
+#[cfg(any(feature = "backup", feature = "restore"))]
+impl CatalogArgs {
+    fn to_catalog_location(&self) -> Result<Option<CatalogLocation>> {
+        if let Some(path) = &self.catalog_file {
+            return Ok(Some(CatalogLocation::LocalFile(path.clone())));
+        }
+        match &self.catalog_config_map {
+            Some(config_map) => match config_map.split_once('/') {
+                Some((namespace, name)) if !namespace.is_empty() && !name.is_empty() => {
+                    Ok(Some(CatalogLocation::ConfigMap {
+                        namespace: namespace.to_string(),
+                        name: name.to_string(),
+                    }))
+                }
+                _ => bail!(
+                    "Invalid catalog ConfigMap, expected <namespace>/<name>: {}",
+                    config_map
+                ),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+// Arguments for selecting the PVCs of a namespace
+#[derive(Args)]
+struct PvcSelectorArgs {
+    /// Label selector for the PVCs, e.g. `app=postgres,tier!=cache`
+    #[arg(long, required = false, conflicts_with = "pvc_name")]
+    selector: Option<String>,
+    /// Field selector for the PVCs, e.g. `metadata.name!=scratch`
+    #[arg(long, required = false, conflicts_with = "pvc_name")]
+    field_selector: Option<String>,
+    /// Only include the PVCs whose name matches this regex
+    #[arg(long, required = false, conflicts_with = "pvc_name")]
+    include_pvc_regex: Option<String>,
+    /// Exclude the PVCs whose name matches this regex
+    #[arg(long, required = false, conflicts_with = "pvc_name")]
+    exclude_pvc_regex: Option<String>,
+}
+
+#[cfg(any(feature = "backup", feature = "restore"))]
+impl PvcSelectorArgs {
+    fn to_pvc_selector(&self) -> Result<PvcSelector> {
+        PvcSelector::new(
+            self.selector.clone(),
+            self.field_selector.clone(),
+            self.include_pvc_regex.as_deref(),
+            self.exclude_pvc_regex.as_deref(),
+        )
+    }
+}
+
+/// Wait for a SIGTERM or SIGINT signal, or forever if they cannot be listened for
+#[cfg(feature = "backup")]
+async fn shutdown_signal() {
+    if let Err(e) = DaemonOperator::shutdown_signal().await {
+        tracing::error!("Failed to listen for the shutdown signals: {:#}", e);
+        std::future::pending::<()>().await;
+    }
+}
+
+// Without the backup and restore features there is no command to run
+#[cfg_attr(
+    not(any(feature = "backup", feature = "restore")),
+    allow(unreachable_code, unused_variables)
+)]
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
@@ -131,53 +651,240 @@ async fn main() -> Result<()> {
         #[cfg(feature = "backup")]
         Commands::Backup {
             region,
-            source_ns,
+            namespace_selection_args,
             volume_snapshot_class,
             pvc_name,
             include_all_pvcs,
+            pvc_selector_args,
             volume_snapshot_name_prefix,
+            volume_snapshot_name_template,
+            max_concurrency,
+            snapshot_timeout_secs,
+            catalog_args,
+            hooks_file,
+            scale_down,
+            scale_down_timeout_secs,
+            volume_group_snapshot_class,
+            ebs_tag_args,
         } => {
             let backup_payload = BackupPayload::new(
                 region,
-                source_ns,
+                namespace_selection_args.to_namespace_selection(),
                 volume_snapshot_class,
                 pvc_name,
                 include_all_pvcs,
+                pvc_selector_args.to_pvc_selector()?,
                 volume_snapshot_name_prefix,
+                VolumeSnapshotNameTemplate::new(volume_snapshot_name_template)?,
+                max_concurrency,
+                Duration::from_secs(snapshot_timeout_secs),
+                catalog_args.to_catalog_location()?,
+                match hooks_file {
+                    Some(hooks_file) => BackupHooks::from_file(&hooks_file).await?,
+                    None => BackupHooks::default(),
+                },
+                scale_down.then(|| Duration::from_secs(scale_down_timeout_secs)),
+                volume_group_snapshot_class,
+                ebs_tag_args.to_ebs_snapshot_tags()?,
+                ebs_tag_args.snapshot_copy_targets,
             );
 
             info!("{}", "Starting Backup process...".bold().blue());
-            BackupOperator::backup(backup_payload).await?;
+            let backup_summary =
+                BackupOperator::backup(backup_payload, shutdown_signal(), |_| {}).await?;
+            info!("{}", backup_summary);
+            if let Some(catalog_error) = &backup_summary.catalog_error {
+                bail!(
+                    "Backup process failed to save the catalog: {}",
+                    catalog_error
+                );
+            }
+            if !backup_summary.is_success() {
+                bail!(
+                    "Backup process failed for {} PVC(s)",
+                    backup_summary.failed().count()
+                );
+            }
             info!(
                 "{}",
                 "Backup process completed successfully!".bold().green()
             );
         }
+        #[cfg(feature = "backup")]
+        Commands::Daemon {
+            region,
+            config,
+            state_file,
+            catalog_args,
+        } => {
+            let daemon_payload = DaemonPayload::new(
+                DaemonConfig::from_file(&config).await?,
+                region,
+                catalog_args.to_catalog_location()?,
+                state_file,
+            );
+
+            info!("{}", "Starting Daemon...".bold().blue());
+            DaemonOperator::run(daemon_payload, shutdown_signal()).await?;
+            info!("{}", "Daemon stopped gracefully!".bold().green());
+        }
+        #[cfg(feature = "backup")]
+        Commands::Prune {
+            namespace,
+            volume_snapshot_name_prefix,
+            keep_last,
+            max_age_days,
+            keep_daily,
+            keep_weekly,
+            keep_monthly,
+            dry_run,
+            catalog_args,
+        } => {
+            let prune_payload = PrunePayload::new(
+                namespace,
+                volume_snapshot_name_prefix,
+                RetentionPolicy::new(
+                    keep_last,
+                    max_age_days.map(|days| chrono::Duration::days(days.into())),
+                    keep_daily,
+                    keep_weekly,
+                    keep_monthly,
+                )?,
+                dry_run,
+                catalog_args.to_catalog_location()?,
+            );
+
+            info!("{}", "Starting Prune process...".bold().blue());
+            let prune_summary = PruneOperator::prune(prune_payload).await?;
+            info!("{}", prune_summary);
+            if let Some(catalog_error) = &prune_summary.catalog_error {
+                bail!(
+                    "Prune process failed to update the catalog: {}",
+                    catalog_error
+                );
+            }
+            if !prune_summary.is_success() {
+                bail!(
+                    "Prune process failed for {} VolumeSnapshot(s)",
+                    prune_summary.failed().count()
+                );
+            }
+            info!("{}", "Prune process completed successfully!".bold().green());
+        }
+        #[cfg(feature = "backup")]
+        Commands::Recover {
+            namespace_selection_args,
+        } => {
+            info!("{}", "Starting Recover process...".bold().blue());
+            let recovered =
+                BackupOperator::recover(&namespace_selection_args.to_namespace_selection()).await?;
+            for (namespace, workload) in &recovered {
+                info!(
+                    "Restored {} on namespace {} to {} replica(s)",
+                    workload,
+                    namespace,
+                    workload.replicas_to_restore()
+                );
+            }
+            info!(
+                "{}",
+                format!("Recovered {} workload(s)!", recovered.len())
+                    .bold()
+                    .green()
+            );
+        }
+        #[cfg(feature = "backup")]
+        Commands::Share {
+            region,
+            snapshot_ids,
+            backup_id,
+            catalog_args,
+            account_ids,
+            revoke,
+        } => {
+            let share_payload = SharePayload::new(
+                region,
+                snapshot_ids,
+                backup_id,
+                catalog_args.to_catalog_location()?,
+                account_ids,
+                revoke,
+            );
+
+            info!("{}", "Starting Share process...".bold().blue());
+            let share_summary = ShareOperator::share(share_payload).await?;
+            info!("{}", share_summary);
+            if !share_summary.is_success() {
+                bail!(
+                    "Share process failed for {} EBS snapshot(s)",
+                    share_summary.failed().count()
+                );
+            }
+            info!("{}", "Share process completed successfully!".bold().green());
+        }
         #[cfg(feature = "restore")]
         Commands::Restore {
+            region,
             source_ns,
             target_ns,
             volume_snapshot_class,
             pvc_name,
             include_all_pvcs,
+            pvc_selector_args,
             volume_snapshot_name_prefix,
             target_snapshot_content_name_prefix,
             storage_class_name,
+            storage_class_mappings,
             vsc_retain_policy,
+            max_concurrency,
+            backup_id,
+            catalog_args,
+            volume_group_snapshot,
+            bind_timeout_secs,
+            on_conflict,
+            ebs_snapshot_source_args,
+            restore_strategy,
+            static_volume_args,
+            pvc_override_args,
+            pvc_rename_args,
         } => {
             let restore_payload = RestorePayload::new(
-                source_ns.clone(),
+                source_ns.unwrap_or_default(),
                 target_ns.clone(),
                 volume_snapshot_class.clone(),
                 pvc_name.clone(),
                 include_all_pvcs,
-                volume_snapshot_name_prefix.clone(),
+                volume_snapshot_name_prefix.unwrap_or_default(),
                 target_snapshot_content_name_prefix.clone(),
                 storage_class_name.clone(),
                 vsc_retain_policy,
-            );
+                region,
+            )
+            .with_pvc_selector(pvc_selector_args.to_pvc_selector()?)
+            .with_max_concurrency(max_concurrency)
+            .with_backup_id(backup_id)
+            .with_catalog(catalog_args.to_catalog_location()?)
+            .with_volume_group_snapshot(volume_group_snapshot)
+            .with_bind_timeout(Duration::from_secs(bind_timeout_secs))
+            .with_ebs_snapshot_source(ebs_snapshot_source_args.to_ebs_snapshot_source())
+            .with_restore_strategy(restore_strategy)
+            .with_static_volume(
+                static_volume_args.to_ebs_volume_settings(),
+                static_volume_args.pv_reclaim_policy,
+            )
+            .with_pvc_overrides(pvc_override_args.to_pvc_template())
+            .with_on_conflict(on_conflict)
+            .with_pvc_rename(pvc_rename_args.to_pvc_rename())
+            .with_storage_class_mappings(storage_class_mappings.into_iter().collect());
             info!("{}", "Starting Restore process...".bold().blue());
-            RestoreOperator::restore(restore_payload).await?;
+            let restore_summary = RestoreOperator::restore(restore_payload, |_| {}).await?;
+            info!("{}", restore_summary);
+            if !restore_summary.is_success() {
+                bail!(
+                    "Restore process failed for {} PVC(s)",
+                    restore_summary.failed().count()
+                );
+            }
             info!(
                 "{}",
                 "Restore process completed successfully!".bold().green()
@@ -191,18 +898,45 @@ async fn main() -> Result<()> {
             volume_snapshot_class,
             pvc_name,
             include_all_pvcs,
+            pvc_selector_args,
             volume_snapshot_name_prefix,
+            volume_snapshot_name_template,
             target_snapshot_content_name_prefix,
             storage_class_name,
+            storage_class_mappings,
             vsc_retain_policy,
+            max_concurrency,
+            snapshot_timeout_secs,
+            catalog_args,
+            hooks_file,
+            scale_down,
+            scale_down_timeout_secs,
+            ebs_tag_args,
+            pvc_override_args,
+            on_conflict,
+            bind_timeout_secs,
+            pvc_rename_args,
         } => {
             let backup_payload = BackupPayload::new(
                 region.clone(),
-                source_ns.clone(),
+                NamespaceSelection::single(source_ns.clone()),
                 volume_snapshot_class.clone(),
                 pvc_name.clone(),
                 include_all_pvcs,
+                pvc_selector_args.to_pvc_selector()?,
                 volume_snapshot_name_prefix.clone(),
+                VolumeSnapshotNameTemplate::new(volume_snapshot_name_template)?,
+                max_concurrency,
+                Duration::from_secs(snapshot_timeout_secs),
+                catalog_args.to_catalog_location()?,
+                match hooks_file {
+                    Some(hooks_file) => BackupHooks::from_file(&hooks_file).await?,
+                    None => BackupHooks::default(),
+                },
+                scale_down.then(|| Duration::from_secs(scale_down_timeout_secs)),
+                None::<String>,
+                ebs_tag_args.to_ebs_snapshot_tags()?,
+                ebs_tag_args.snapshot_copy_targets,
             );
 
             let restore_payload = RestorePayload::new(
@@ -215,22 +949,61 @@ async fn main() -> Result<()> {
                 target_snapshot_content_name_prefix.clone(),
                 storage_class_name.clone(),
                 vsc_retain_policy,
-            );
+                region,
+            )
+            .with_pvc_selector(pvc_selector_args.to_pvc_selector()?)
+            .with_max_concurrency(max_concurrency)
+            .with_bind_timeout(Duration::from_secs(bind_timeout_secs))
+            .with_pvc_overrides(pvc_override_args.to_pvc_template())
+            .with_on_conflict(on_conflict)
+            .with_pvc_rename(pvc_rename_args.to_pvc_rename())
+            .with_storage_class_mappings(storage_class_mappings.into_iter().collect());
 
             info!("{}", "Starting Backup process...".bold().blue());
-            BackupOperator::backup(backup_payload).await?;
+            let backup_summary =
+                BackupOperator::backup(backup_payload, shutdown_signal(), |_| {}).await?;
+            info!("{}", backup_summary);
+            if let Some(catalog_error) = &backup_summary.catalog_error {
+                bail!(
+                    "Backup process failed to save the catalog: {}",
+                    catalog_error
+                );
+            }
+            if !backup_summary.is_success() {
+                bail!(
+                    "Backup process failed for {} PVC(s)",
+                    backup_summary.failed().count()
+                );
+            }
             info!(
                 "{}",
                 "Backup process completed successfully!".bold().green()
             );
 
             info!("{}", "Starting Restore process...".bold().blue());
-            RestoreOperator::restore(restore_payload).await?;
+            let restore_summary = RestoreOperator::restore(restore_payload, |_| {}).await?;
+            info!("{}", restore_summary);
+            if !restore_summary.is_success() {
+                bail!(
+                    "Restore process failed for {} PVC(s)",
+                    restore_summary.failed().count()
+                );
+            }
             info!(
                 "{}",
                 "Restore process completed successfully!".bold().green()
             );
         }
+        #[cfg(feature = "full")]
+        Commands::Controller => {
+            info!("{}", "Starting Controller...".bold().blue());
+            ControllerOperator::run(shutdown_signal()).await?;
+            info!("{}", "Controller stopped gracefully!".bold().green());
+        }
+        #[cfg(feature = "full")]
+        Commands::Crds => {
+            print!("{}", crds_yaml()?);
+        }
     };
     Ok(())
 }
@@ -1,6 +1,286 @@
+#[cfg(feature = "backup")]
+use super::kms::{create_kms_grant, ensure_kms_key_is_shareable, revoke_kms_grant};
 use super::region::get_region_config;
-use anyhow::Result;
-use aws_sdk_ec2::Client as EbsClient;
+use anyhow::{bail, Context, Result};
+#[cfg(feature = "backup")]
+use aws_sdk_ec2::types::{
+    CreateVolumePermission, CreateVolumePermissionModifications, SnapshotAttributeName,
+};
+use aws_sdk_ec2::{
+    types::{
+        ResourceType, Snapshot, SnapshotState, Tag, TagSpecification, VolumeState, VolumeType,
+    },
+    Client as EbsClient,
+};
+#[cfg(feature = "backup")]
+use aws_sdk_kms::Client as KmsClient;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+use tokio::time::{sleep, Instant};
+use tracing::info;
+
+/// Tag holding the name of the cluster an EBS snapshot was taken from
+pub const CLUSTER_TAG: &str = "snap-kube/cluster";
+/// Tag holding the namespace of the PVC an EBS snapshot was taken from
+pub const NAMESPACE_TAG: &str = "snap-kube/namespace";
+/// Tag holding the name of the PVC an EBS snapshot was taken from
+pub const PVC_TAG: &str = "snap-kube/pvc";
+/// Tag holding the name of the VolumeSnapshot of an EBS snapshot
+pub const VOLUME_SNAPSHOT_TAG: &str = "snap-kube/volume-snapshot";
+/// Tag holding the ID of the backup run an EBS snapshot belongs to
+pub const BACKUP_ID_TAG: &str = "snap-kube/backup-id";
+/// Tag holding the description of an EBS snapshot, as the description set by the CSI driver
+/// cannot be changed once the snapshot is created
+pub const DESCRIPTION_TAG: &str = "Description";
+
+/// Tag holding the ID of the EBS snapshot a copy was made from
+pub const SOURCE_SNAPSHOT_TAG: &str = "snap-kube/source-snapshot";
+/// Tag holding the region of the EBS snapshot a copy was made from
+pub const SOURCE_REGION_TAG: &str = "snap-kube/source-region";
+
+/// Tag of the EBS volumes managed by the EBS CSI driver, which its default IAM policy requires
+/// to delete a volume
+pub const CSI_CLUSTER_TAG: &str = "ebs.csi.aws.com/cluster";
+
+/// Volume type of the EBS volumes created from a snapshot, as the default of the EBS CSI driver
+const DEFAULT_VOLUME_TYPE: &str = "gp3";
+
+/// Interval between two checks of the state of an EBS volume
+const VOLUME_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Maximum number of tags of an EBS snapshot
+const MAX_TAGS: usize = 50;
+
+/// Interval between two progress reports while waiting for an EBS snapshot
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A region the EBS snapshots are copied to
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotCopyTarget {
+    /// Destination region, e.g. `eu-central-1`
+    pub region: String,
+    /// KMS key the copy is encrypted with, the default EBS key of the region if not set
+    pub kms_key_id: Option<String>,
+}
+
+impl SnapshotCopyTarget {
+    /// Parse a copy target given as `<region>` or `<region>=<kms-key-id>`
+    pub fn parse(target: &str) -> Result<Self> {
+        let (region, kms_key_id) = match target.split_once('=') {
+            Some((region, kms_key_id)) => (region.trim(), Some(kms_key_id.trim().to_string())),
+            None => (target.trim(), None),
+        };
+        if region.is_empty() || kms_key_id.as_deref() == Some("") {
+            bail!(
+                "Invalid copy target, expected <region> or <region>=<kms-key-id>: {}",
+                target
+            );
+        }
+        Ok(Self {
+            region: region.to_string(),
+            kms_key_id,
+        })
+    }
+}
+
+/// An EBS snapshot taken outside of the cluster, possibly in another region or account
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EbsSnapshotSource {
+    /// ID of the EBS snapshot, e.g. `snap-0123456789abcdef0`
+    pub snapshot_id: String,
+    /// Region of the EBS snapshot, the region of the cluster if not set
+    pub region: Option<String>,
+    /// KMS key the copy into the region of the cluster is encrypted with, if a copy is needed
+    pub kms_key_id: Option<String>,
+    /// Maximum time in seconds to wait for the copy into the region of the cluster
+    #[serde(default = "default_copy_timeout_secs")]
+    pub copy_timeout_secs: u64,
+}
+
+fn default_copy_timeout_secs() -> u64 {
+    3600
+}
+
+impl EbsSnapshotSource {
+    pub fn copy_timeout(&self) -> Duration {
+        Duration::from_secs(self.copy_timeout_secs)
+    }
+}
+
+/// The settings of an EBS volume created from a snapshot, each one falling back to the
+/// parameters of the StorageClass if not set
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EbsVolumeSettings {
+    /// Availability zone of the volume, e.g. `eu-west-1a`
+    pub availability_zone: Option<String>,
+    /// Volume type, e.g. `gp3` or `io2`
+    pub volume_type: Option<String>,
+    /// Provisioned IOPS of the volume
+    pub iops: Option<i32>,
+    /// Provisioned throughput of the volume in MiB/s
+    pub throughput: Option<i32>,
+    /// KMS key the volume is encrypted with, the key of the snapshot if not set
+    pub kms_key_id: Option<String>,
+}
+
+impl EbsVolumeSettings {
+    /// Read the settings from the parameters of an EBS CSI StorageClass
+    ///
+    /// The `type`, `iops`, `throughput` and `kmsKeyId` parameters are read, case insensitively
+    /// as the EBS CSI driver does.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameters` - Parameters of the StorageClass
+    ///
+    /// # Returns
+    ///
+    /// The settings, or an error if a numeric parameter is not valid
+    pub fn from_storage_class_parameters(parameters: &BTreeMap<String, String>) -> Result<Self> {
+        let parameter = |name: &str| {
+            parameters
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+        };
+        let number = |name: &str| -> Result<Option<i32>> {
+            parameter(name)
+                .map(|value| {
+                    value.parse().with_context(|| {
+                        format!("Invalid StorageClass parameter {}: {}", name, value)
+                    })
+                })
+                .transpose()
+        };
+        Ok(Self {
+            availability_zone: None,
+            volume_type: parameter("type"),
+            iops: number("iops")?,
+            throughput: number("throughput")?,
+            kms_key_id: parameter("kmsKeyId"),
+        })
+    }
+
+    /// Fill the settings that are not set from other settings
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            availability_zone: self.availability_zone.or(other.availability_zone),
+            volume_type: self.volume_type.or(other.volume_type),
+            iops: self.iops.or(other.iops),
+            throughput: self.throughput.or(other.throughput),
+            kms_key_id: self.kms_key_id.or(other.kms_key_id),
+        }
+    }
+}
+
+/// The tags applied to the EBS snapshots of a backup, along with the ones derived from the
+/// VolumeSnapshot of each snapshot
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EbsSnapshotTags {
+    cluster_name: Option<String>,
+    extra_tags: BTreeMap<String, String>,
+    description: Option<String>,
+}
+
+impl EbsSnapshotTags {
+    /// Creates a new EbsSnapshotTags
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster_name` - Name of the cluster, tagged if set
+    /// * `extra_tags` - User-supplied tags, e.g. for cost allocation
+    /// * `description` - Description of the snapshots, generated from the PVC if not set
+    ///
+    /// # Returns
+    ///
+    /// A new EbsSnapshotTags instance, or an error if a tag is not valid on EC2
+    pub fn new(
+        cluster_name: Option<impl Into<String>>,
+        extra_tags: BTreeMap<String, String>,
+        description: Option<impl Into<String>>,
+    ) -> Result<Self> {
+        for (key, value) in &extra_tags {
+            if key.is_empty() || key.chars().count() > 128 {
+                bail!(
+                    "Invalid EBS tag key, expected 1 to 128 characters: {:?}",
+                    key
+                );
+            }
+            if key.to_lowercase().starts_with("aws:") {
+                bail!("EBS tag keys cannot start with aws: {}", key);
+            }
+            if value.chars().count() > 256 {
+                bail!(
+                    "Invalid value of EBS tag {}, expected at most 256 characters",
+                    key
+                );
+            }
+        }
+        Ok(Self {
+            cluster_name: cluster_name.map(|cluster_name| cluster_name.into()),
+            extra_tags,
+            description: description.map(|description| description.into()),
+        })
+    }
+
+    /// The tags of the EBS snapshot of a VolumeSnapshot
+    ///
+    /// The tags set by snap-kube take precedence over the user-supplied ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the PVC
+    /// * `pvc` - Name of the PVC
+    /// * `volume_snapshot_name` - Name of the VolumeSnapshot
+    /// * `backup_id` - ID of the backup run
+    ///
+    /// # Returns
+    ///
+    /// The tags, keyed by tag key
+    pub fn for_snapshot(
+        &self,
+        namespace: &str,
+        pvc: &str,
+        volume_snapshot_name: &str,
+        backup_id: &str,
+    ) -> BTreeMap<String, String> {
+        let mut tags = self.extra_tags.clone();
+        if let Some(cluster_name) = &self.cluster_name {
+            tags.insert(CLUSTER_TAG.to_string(), cluster_name.clone());
+        }
+        tags.insert(NAMESPACE_TAG.to_string(), namespace.to_string());
+        tags.insert(PVC_TAG.to_string(), pvc.to_string());
+        tags.insert(
+            VOLUME_SNAPSHOT_TAG.to_string(),
+            volume_snapshot_name.to_string(),
+        );
+        tags.insert(BACKUP_ID_TAG.to_string(), backup_id.to_string());
+        tags.insert(
+            DESCRIPTION_TAG.to_string(),
+            self.description.clone().unwrap_or_else(|| {
+                format!(
+                    "snap-kube backup {} of PVC {}/{}",
+                    backup_id, namespace, pvc
+                )
+            }),
+        );
+        tags
+    }
+}
+
+/// Parse a tag given as `key=value`
+pub fn parse_tag(tag: &str) -> Result<(String, String)> {
+    match tag.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => bail!("Invalid tag, expected <key>=<value>: {}", tag),
+    }
+}
 
 /// Create an EBS client
 ///
@@ -17,6 +297,121 @@ pub async fn create_ebs_client(region: Option<String>) -> Result<EbsClient> {
     Ok(ebs_client)
 }
 
+/// Describe an EBS snapshot
+///
+/// # Arguments
+///
+/// * `ebs_client` - EBS client
+/// * `snapshot_id` - Snapshot ID
+///
+/// # Returns
+///
+/// The EBS snapshot
+pub async fn get_ebs_snapshot(ebs_client: EbsClient, snapshot_id: String) -> Result<Snapshot> {
+    let resp = ebs_client
+        .describe_snapshots()
+        .snapshot_ids(snapshot_id.clone())
+        .send()
+        .await?;
+
+    resp.snapshots
+        .unwrap_or_default()
+        .pop()
+        .with_context(|| format!("EBS snapshot {} not found", snapshot_id))
+}
+
+/// Check if an EBS snapshot is owned by the account of the client, as opposed to shared with it
+///
+/// # Arguments
+///
+/// * `ebs_client` - EBS client
+/// * `snapshot_id` - Snapshot ID
+///
+/// # Returns
+///
+/// Whether the snapshot is owned by the account
+pub async fn is_own_ebs_snapshot(ebs_client: &EbsClient, snapshot_id: &str) -> Result<bool> {
+    let resp = ebs_client
+        .describe_snapshots()
+        .owner_ids("self")
+        .snapshot_ids(snapshot_id)
+        .send()
+        .await;
+    match resp {
+        Ok(resp) => Ok(!resp.snapshots.unwrap_or_default().is_empty()),
+        // Filtering a snapshot ID by an owner it does not have fails as not found
+        Err(e)
+            if e.as_service_error()
+                .and_then(|e| e.meta().code())
+                .is_some_and(|code| code == "InvalidSnapshot.NotFound") =>
+        {
+            Ok(false)
+        }
+        Err(e) => {
+            Err(e).with_context(|| format!("Failed to describe EBS snapshot {}", snapshot_id))
+        }
+    }
+}
+
+/// Get the size of the volume restored from an EBS snapshot
+///
+/// # Arguments
+///
+/// * `snapshot` - The EBS snapshot
+///
+/// # Returns
+///
+/// The size as a Kubernetes quantity, e.g. `100Gi`
+pub fn ebs_snapshot_restore_size(snapshot: &Snapshot) -> Result<String> {
+    match snapshot.volume_size {
+        Some(volume_size) if volume_size > 0 => Ok(format!("{}Gi", volume_size)),
+        _ => bail!(
+            "EBS snapshot {} has no volume size",
+            snapshot.snapshot_id.clone().unwrap_or_default()
+        ),
+    }
+}
+
+/// The progress of an EBS snapshot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EbsSnapshotProgress {
+    /// The snapshot is still being taken or copied, along with its progress, e.g. `42%`
+    Pending(String),
+    Completed,
+    /// The snapshot failed, along with the reason reported by EC2
+    Failed(String),
+}
+
+impl EbsSnapshotProgress {
+    /// Read the progress of a described EBS snapshot
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - The EBS snapshot
+    ///
+    /// # Returns
+    ///
+    /// The progress of the snapshot, or an error if a pending snapshot has no progress
+    pub fn of(snapshot: &Snapshot) -> Result<Self> {
+        match &snapshot.state {
+            Some(SnapshotState::Completed) => Ok(Self::Completed),
+            Some(SnapshotState::Error) => Ok(Self::Failed(
+                snapshot.state_message.clone().unwrap_or_default(),
+            )),
+            _ => snapshot
+                .progress
+                .clone()
+                .map(Self::Pending)
+                .with_context(|| {
+                    format!(
+                        "EBS snapshot {} has no progress",
+                        snapshot.snapshot_id.clone().unwrap_or_default()
+                    )
+                }),
+        }
+    }
+}
+
 /// Get the progress of an EBS snapshot
 ///
 /// # Arguments
@@ -30,14 +425,408 @@ pub async fn create_ebs_client(region: Option<String>) -> Result<EbsClient> {
 pub async fn get_ebs_snapshot_progress(
     ebs_client: EbsClient,
     snapshot_id: String,
+) -> Result<EbsSnapshotProgress> {
+    let snapshot = get_ebs_snapshot(ebs_client, snapshot_id).await?;
+    EbsSnapshotProgress::of(&snapshot)
+}
+
+/// Apply tags to an EBS snapshot
+///
+/// # Arguments
+///
+/// * `ebs_client` - EBS client
+/// * `snapshot_id` - Snapshot ID
+/// * `tags` - The tags, keyed by tag key
+pub async fn tag_ebs_snapshot(
+    ebs_client: &EbsClient,
+    snapshot_id: &str,
+    tags: &BTreeMap<String, String>,
+) -> Result<()> {
+    if tags.len() > MAX_TAGS {
+        bail!(
+            "An EBS snapshot can have at most {} tags, got {}",
+            MAX_TAGS,
+            tags.len()
+        );
+    }
+    ebs_client
+        .create_tags()
+        .resources(snapshot_id)
+        .set_tags(Some(
+            tags.iter()
+                .map(|(key, value)| Tag::builder().key(key).value(value).build())
+                .collect(),
+        ))
+        .send()
+        .await
+        .with_context(|| format!("Failed to tag EBS snapshot {}", snapshot_id))?;
+    Ok(())
+}
+
+/// Copy an EBS snapshot to another region
+///
+/// The copy gets the tags of the source snapshot, along with the ID and region of the source.
+///
+/// # Arguments
+///
+/// * `source_region` - Region of the source snapshot
+/// * `snapshot_id` - ID of the source snapshot
+/// * `target` - Destination region and KMS key of the copy
+/// * `tags` - Tags of the source snapshot
+///
+/// # Returns
+///
+/// The EBS client of the destination region, and the ID of the copy
+pub async fn copy_ebs_snapshot(
+    source_region: &str,
+    snapshot_id: &str,
+    target: &SnapshotCopyTarget,
+    tags: &BTreeMap<String, String>,
+) -> Result<(EbsClient, String)> {
+    let ebs_client = create_ebs_client(Some(target.region.clone())).await?;
+
+    let mut tags = tags.clone();
+    tags.insert(SOURCE_SNAPSHOT_TAG.to_string(), snapshot_id.to_string());
+    tags.insert(SOURCE_REGION_TAG.to_string(), source_region.to_string());
+    if tags.len() > MAX_TAGS {
+        bail!(
+            "An EBS snapshot can have at most {} tags, got {}",
+            MAX_TAGS,
+            tags.len()
+        );
+    }
+
+    let resp = ebs_client
+        .copy_snapshot()
+        .source_region(source_region)
+        .source_snapshot_id(snapshot_id)
+        .set_description(tags.get(DESCRIPTION_TAG).cloned())
+        .set_encrypted(target.kms_key_id.as_ref().map(|_| true))
+        .set_kms_key_id(target.kms_key_id.clone())
+        .tag_specifications(
+            TagSpecification::builder()
+                .resource_type(ResourceType::Snapshot)
+                .set_tags(Some(
+                    tags.iter()
+                        .map(|(key, value)| Tag::builder().key(key).value(value).build())
+                        .collect(),
+                ))
+                .build(),
+        )
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to copy EBS snapshot {} to region {}",
+                snapshot_id, target.region
+            )
+        })?;
+    let copy_id = resp
+        .snapshot_id
+        .with_context(|| format!("The copy of EBS snapshot {} has no ID", snapshot_id))?;
+    info!(
+        "Copying EBS snapshot {} to region {}: {}",
+        snapshot_id, target.region, copy_id
+    );
+    Ok((ebs_client, copy_id))
+}
+
+/// Wait untill an EBS snapshot is completed, reporting its progress
+///
+/// # Arguments
+///
+/// * `ebs_client` - EBS client of the region of the snapshot
+/// * `snapshot_id` - Snapshot ID
+/// * `timeout` - Maximum time to wait for the snapshot to complete
+pub async fn wait_untill_ebs_snapshot_is_completed(
+    ebs_client: &EbsClient,
+    snapshot_id: &str,
+    timeout: Duration,
+) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match get_ebs_snapshot_progress(ebs_client.clone(), snapshot_id.to_string()).await? {
+            EbsSnapshotProgress::Completed => {
+                info!("EBS snapshot {} is completed", snapshot_id);
+                return Ok(());
+            }
+            EbsSnapshotProgress::Failed(message) => {
+                bail!("EBS snapshot {} failed: {}", snapshot_id, message)
+            }
+            EbsSnapshotProgress::Pending(progress) => {
+                info!("Progress for EBS snapshot {} is: {}", snapshot_id, progress)
+            }
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "EBS snapshot {} is not completed after {}s",
+                snapshot_id,
+                timeout.as_secs()
+            );
+        }
+        sleep(PROGRESS_INTERVAL).await;
+    }
+}
+
+/// Check that an AWS account ID has 12 digits
+pub fn validate_account_id(account_id: &str) -> Result<()> {
+    if account_id.len() != 12 || !account_id.chars().all(|c| c.is_ascii_digit()) {
+        bail!("Invalid AWS account ID, expected 12 digits: {}", account_id);
+    }
+    Ok(())
+}
+
+/// The modifications of the createVolumePermission of an EBS snapshot for a list of accounts
+///
+/// # Arguments
+///
+/// * `account_ids` - IDs of the accounts
+/// * `revoke` - Remove the permission of the accounts instead of adding it
+#[cfg(feature = "backup")]
+pub fn create_volume_permission_modifications(
+    account_ids: &[String],
+    revoke: bool,
+) -> CreateVolumePermissionModifications {
+    let permissions = Some(
+        account_ids
+            .iter()
+            .map(|account_id| {
+                CreateVolumePermission::builder()
+                    .user_id(account_id)
+                    .build()
+            })
+            .collect(),
+    );
+    if revoke {
+        CreateVolumePermissionModifications::builder()
+            .set_remove(permissions)
+            .build()
+    } else {
+        CreateVolumePermissionModifications::builder()
+            .set_add(permissions)
+            .build()
+    }
+}
+
+/// Share an EBS snapshot with other accounts
+///
+/// The accounts are granted the createVolumePermission of the snapshot, and the use of its
+/// KMS key if it is encrypted, so that they can copy it or create a volume from it.
+///
+/// # Arguments
+///
+/// * `ebs_client` - EBS client of the region of the snapshot
+/// * `kms_client` - KMS client of the region of the snapshot
+/// * `snapshot_id` - Snapshot ID
+/// * `account_ids` - IDs of the accounts
+///
+/// # Returns
+///
+/// The KMS key granted to the accounts, if the snapshot is encrypted
+#[cfg(feature = "backup")]
+pub async fn share_ebs_snapshot(
+    ebs_client: &EbsClient,
+    kms_client: &KmsClient,
+    snapshot_id: &str,
+    account_ids: &[String],
+) -> Result<Option<String>> {
+    let snapshot = get_ebs_snapshot(ebs_client.clone(), snapshot_id.to_string()).await?;
+
+    // Grant the KMS key first, the snapshot is of no use to the accounts without it
+    let kms_key_id = match (snapshot.encrypted, snapshot.kms_key_id) {
+        (Some(true), Some(kms_key_id)) => {
+            ensure_kms_key_is_shareable(kms_client, &kms_key_id).await?;
+            for account_id in account_ids {
+                create_kms_grant(kms_client, &kms_key_id, snapshot_id, account_id).await?;
+            }
+            Some(kms_key_id)
+        }
+        _ => None,
+    };
+
+    ebs_client
+        .modify_snapshot_attribute()
+        .snapshot_id(snapshot_id)
+        .attribute(SnapshotAttributeName::CreateVolumePermission)
+        .create_volume_permission(create_volume_permission_modifications(account_ids, false))
+        .send()
+        .await
+        .with_context(|| format!("Failed to share EBS snapshot {}", snapshot_id))?;
+    info!(
+        "Shared EBS snapshot {} with account(s): {}",
+        snapshot_id,
+        account_ids.join(", ")
+    );
+    Ok(kms_key_id)
+}
+
+/// Revoke the share of an EBS snapshot with other accounts
+///
+/// The createVolumePermission of the accounts is removed, along with the grants of the KMS
+/// key of the snapshot. The copies and volumes the accounts already made are not affected.
+///
+/// # Arguments
+///
+/// * `ebs_client` - EBS client of the region of the snapshot
+/// * `kms_client` - KMS client of the region of the snapshot
+/// * `snapshot_id` - Snapshot ID
+/// * `account_ids` - IDs of the accounts
+#[cfg(feature = "backup")]
+pub async fn revoke_ebs_snapshot_share(
+    ebs_client: &EbsClient,
+    kms_client: &KmsClient,
+    snapshot_id: &str,
+    account_ids: &[String],
+) -> Result<()> {
+    let snapshot = get_ebs_snapshot(ebs_client.clone(), snapshot_id.to_string()).await?;
+
+    ebs_client
+        .modify_snapshot_attribute()
+        .snapshot_id(snapshot_id)
+        .attribute(SnapshotAttributeName::CreateVolumePermission)
+        .create_volume_permission(create_volume_permission_modifications(account_ids, true))
+        .send()
+        .await
+        .with_context(|| format!("Failed to revoke the share of EBS snapshot {}", snapshot_id))?;
+
+    if let (Some(true), Some(kms_key_id)) = (snapshot.encrypted, snapshot.kms_key_id) {
+        for account_id in account_ids {
+            revoke_kms_grant(kms_client, &kms_key_id, snapshot_id, account_id).await?;
+        }
+    }
+    info!(
+        "Revoked the share of EBS snapshot {} with account(s): {}",
+        snapshot_id,
+        account_ids.join(", ")
+    );
+    Ok(())
+}
+
+/// Create an EBS volume from a snapshot
+///
+/// The volume gets the size of the snapshot, and is tagged as managed by the EBS CSI driver,
+/// so that the driver can delete it along with its PersistentVolume.
+///
+/// # Arguments
+///
+/// * `ebs_client` - EBS client of the region of the snapshot
+/// * `snapshot_id` - Snapshot ID
+/// * `availability_zone` - Availability zone of the volume
+/// * `settings` - Type, performance and encryption of the volume
+/// * `tags` - Tags of the volume
+///
+/// # Returns
+///
+/// The ID of the volume
+pub async fn create_ebs_volume_from_snapshot(
+    ebs_client: &EbsClient,
+    snapshot_id: &str,
+    availability_zone: &str,
+    settings: &EbsVolumeSettings,
+    tags: &BTreeMap<String, String>,
 ) -> Result<String> {
+    let mut tags = tags.clone();
+    tags.insert(CSI_CLUSTER_TAG.to_string(), "true".to_string());
+
     let resp = ebs_client
-        .describe_snapshots()
-        .snapshot_ids(snapshot_id)
+        .create_volume()
+        .snapshot_id(snapshot_id)
+        .availability_zone(availability_zone)
+        .volume_type(VolumeType::from(
+            settings
+                .volume_type
+                .as_deref()
+                .unwrap_or(DEFAULT_VOLUME_TYPE),
+        ))
+        .set_iops(settings.iops)
+        .set_throughput(settings.throughput)
+        .set_encrypted(settings.kms_key_id.as_ref().map(|_| true))
+        .set_kms_key_id(settings.kms_key_id.clone())
+        .tag_specifications(
+            TagSpecification::builder()
+                .resource_type(ResourceType::Volume)
+                .set_tags(Some(
+                    tags.iter()
+                        .map(|(key, value)| Tag::builder().key(key).value(value).build())
+                        .collect(),
+                ))
+                .build(),
+        )
         .send()
-        .await?;
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to create an EBS volume from snapshot {} in {}",
+                snapshot_id, availability_zone
+            )
+        })?;
+    let volume_id = resp
+        .volume_id
+        .with_context(|| format!("The volume of EBS snapshot {} has no ID", snapshot_id))?;
+    info!(
+        "Creating EBS volume {} from snapshot {} in {}",
+        volume_id, snapshot_id, availability_zone
+    );
+    Ok(volume_id)
+}
 
-    let snapshot = resp.snapshots.unwrap().pop().unwrap();
-    let progress = snapshot.progress.unwrap();
-    Ok(progress)
+/// Wait untill an EBS volume is available
+///
+/// # Arguments
+///
+/// * `ebs_client` - EBS client of the region of the volume
+/// * `volume_id` - Volume ID
+/// * `timeout` - Maximum time to wait for the volume to be available
+pub async fn wait_untill_ebs_volume_is_available(
+    ebs_client: &EbsClient,
+    volume_id: &str,
+    timeout: Duration,
+) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let resp = ebs_client
+            .describe_volumes()
+            .volume_ids(volume_id)
+            .send()
+            .await
+            .with_context(|| format!("Failed to describe EBS volume {}", volume_id))?;
+        let state = resp
+            .volumes
+            .unwrap_or_default()
+            .pop()
+            .and_then(|volume| volume.state);
+        match state {
+            Some(VolumeState::Available) => {
+                info!("EBS volume {} is available", volume_id);
+                return Ok(());
+            }
+            Some(VolumeState::Error) => bail!("EBS volume {} failed to be created", volume_id),
+            _ => {}
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "EBS volume {} is not available after {}s",
+                volume_id,
+                timeout.as_secs()
+            );
+        }
+        sleep(VOLUME_POLL_INTERVAL).await;
+    }
+}
+
+/// Delete an EBS volume
+///
+/// # Arguments
+///
+/// * `ebs_client` - EBS client of the region of the volume
+/// * `volume_id` - Volume ID
+pub async fn delete_ebs_volume(ebs_client: &EbsClient, volume_id: &str) -> Result<()> {
+    ebs_client
+        .delete_volume()
+        .volume_id(volume_id)
+        .send()
+        .await
+        .with_context(|| format!("Failed to delete EBS volume {}", volume_id))?;
+    info!("Deleted EBS volume {}", volume_id);
+    Ok(())
 }
@@ -0,0 +1,235 @@
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "backup")]
+    use crate::aws_ops::ebs::create_volume_permission_modifications;
+    use crate::aws_ops::ebs::{
+        ebs_snapshot_restore_size, parse_tag, validate_account_id, EbsSnapshotProgress,
+        EbsSnapshotSource, EbsSnapshotTags, EbsVolumeSettings, SnapshotCopyTarget,
+    };
+    use aws_sdk_ec2::types::{Snapshot, SnapshotState};
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_ebs_snapshot_tags() {
+        let ebs_snapshot_tags = EbsSnapshotTags::new(
+            Some("prod-eu"),
+            BTreeMap::from([
+                ("team".to_string(), "payments".to_string()),
+                // Overridden by the tag set by snap-kube
+                ("snap-kube/pvc".to_string(), "other".to_string()),
+            ]),
+            None::<String>,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ebs_snapshot_tags.for_snapshot(
+                "payments",
+                "data-postgres-0",
+                "nightly-data-postgres-0-20241018-120000",
+                "20241018-120000-00ab",
+            ),
+            BTreeMap::from([
+                (
+                    "Description".to_string(),
+                    "snap-kube backup 20241018-120000-00ab of PVC payments/data-postgres-0"
+                        .to_string()
+                ),
+                (
+                    "snap-kube/backup-id".to_string(),
+                    "20241018-120000-00ab".to_string()
+                ),
+                ("snap-kube/cluster".to_string(), "prod-eu".to_string()),
+                ("snap-kube/namespace".to_string(), "payments".to_string()),
+                ("snap-kube/pvc".to_string(), "data-postgres-0".to_string()),
+                (
+                    "snap-kube/volume-snapshot".to_string(),
+                    "nightly-data-postgres-0-20241018-120000".to_string()
+                ),
+                ("team".to_string(), "payments".to_string()),
+            ])
+        );
+
+        let ebs_snapshot_tags =
+            EbsSnapshotTags::new(None::<String>, BTreeMap::new(), Some("Nightly backup")).unwrap();
+        let tags = ebs_snapshot_tags.for_snapshot("payments", "data", "vs", "id");
+        assert_eq!(tags.get("Description"), Some(&"Nightly backup".to_string()));
+        assert!(!tags.contains_key("snap-kube/cluster"));
+    }
+
+    #[test]
+    fn test_ebs_snapshot_tags_validation() {
+        let tags = |key: &str, value: &str| BTreeMap::from([(key.to_string(), value.to_string())]);
+
+        assert!(
+            EbsSnapshotTags::new(None::<String>, tags("aws:owner", "x"), None::<String>).is_err()
+        );
+        assert!(EbsSnapshotTags::new(None::<String>, tags("", "x"), None::<String>).is_err());
+        assert!(EbsSnapshotTags::new(
+            None::<String>,
+            tags("team", &"x".repeat(257)),
+            None::<String>
+        )
+        .is_err());
+        assert!(EbsSnapshotTags::new(None::<String>, tags("team", ""), None::<String>).is_ok());
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        assert_eq!(
+            parse_tag("cost-center = 1234").unwrap(),
+            ("cost-center".to_string(), "1234".to_string())
+        );
+        assert_eq!(
+            parse_tag("env=").unwrap(),
+            ("env".to_string(), String::new())
+        );
+        assert!(parse_tag("env").is_err());
+        assert!(parse_tag("=prod").is_err());
+    }
+
+    #[test]
+    fn test_parse_snapshot_copy_target() {
+        assert_eq!(
+            SnapshotCopyTarget::parse("eu-central-1").unwrap(),
+            SnapshotCopyTarget {
+                region: "eu-central-1".to_string(),
+                kms_key_id: None,
+            }
+        );
+        assert_eq!(
+            SnapshotCopyTarget::parse("us-east-1=arn:aws:kms:us-east-1:123456789012:key/dr")
+                .unwrap(),
+            SnapshotCopyTarget {
+                region: "us-east-1".to_string(),
+                kms_key_id: Some("arn:aws:kms:us-east-1:123456789012:key/dr".to_string()),
+            }
+        );
+        assert!(SnapshotCopyTarget::parse("").is_err());
+        assert!(SnapshotCopyTarget::parse("us-east-1=").is_err());
+    }
+
+    #[test]
+    fn test_ebs_snapshot_restore_size() {
+        let snapshot = Snapshot::builder()
+            .snapshot_id("snap-0123456789abcdef0")
+            .volume_size(100)
+            .build();
+        assert_eq!(ebs_snapshot_restore_size(&snapshot).unwrap(), "100Gi");
+
+        let snapshot = Snapshot::builder()
+            .snapshot_id("snap-0123456789abcdef0")
+            .build();
+        assert!(ebs_snapshot_restore_size(&snapshot).is_err());
+    }
+
+    #[test]
+    fn test_ebs_snapshot_progress() {
+        let snapshot = |state: SnapshotState, progress: Option<&str>| {
+            Snapshot::builder()
+                .snapshot_id("snap-0123456789abcdef0")
+                .state(state)
+                .set_progress(progress.map(String::from))
+                .state_message("KMS key is disabled")
+                .build()
+        };
+
+        assert_eq!(
+            EbsSnapshotProgress::of(&snapshot(SnapshotState::Pending, Some("42%"))).unwrap(),
+            EbsSnapshotProgress::Pending("42%".to_string())
+        );
+        assert_eq!(
+            EbsSnapshotProgress::of(&snapshot(SnapshotState::Completed, None)).unwrap(),
+            EbsSnapshotProgress::Completed
+        );
+        assert_eq!(
+            EbsSnapshotProgress::of(&snapshot(SnapshotState::Error, None)).unwrap(),
+            EbsSnapshotProgress::Failed("KMS key is disabled".to_string())
+        );
+        assert!(EbsSnapshotProgress::of(&snapshot(SnapshotState::Pending, None)).is_err());
+    }
+
+    #[test]
+    fn test_ebs_snapshot_source_defaults() {
+        let ebs_snapshot_source: EbsSnapshotSource = serde_json::from_value(serde_json::json!({
+            "snapshotId": "snap-0123456789abcdef0",
+            "region": "us-east-1",
+        }))
+        .unwrap();
+        assert_eq!(ebs_snapshot_source.kms_key_id, None);
+        assert_eq!(ebs_snapshot_source.copy_timeout().as_secs(), 3600);
+    }
+
+    #[test]
+    fn test_validate_account_id() {
+        assert!(validate_account_id("123456789012").is_ok());
+        assert!(validate_account_id("12345678901").is_err());
+        assert!(validate_account_id("12345678901a").is_err());
+        assert!(validate_account_id("").is_err());
+    }
+
+    #[cfg(feature = "backup")]
+    #[test]
+    fn test_create_volume_permission_modifications() {
+        let account_ids = vec!["111111111111".to_string(), "222222222222".to_string()];
+
+        let modifications = create_volume_permission_modifications(&account_ids, false);
+        assert_eq!(
+            modifications
+                .add()
+                .iter()
+                .filter_map(|permission| permission.user_id())
+                .collect::<Vec<_>>(),
+            vec!["111111111111", "222222222222"]
+        );
+        assert!(modifications.remove().is_empty());
+
+        let modifications = create_volume_permission_modifications(&account_ids, true);
+        assert!(modifications.add().is_empty());
+        assert_eq!(modifications.remove().len(), 2);
+    }
+
+    #[test]
+    fn test_ebs_volume_settings() {
+        let parameters = BTreeMap::from([
+            ("type".to_string(), "io2".to_string()),
+            ("IOPS".to_string(), "4000".to_string()),
+            (
+                "kmsKeyId".to_string(),
+                "arn:aws:kms:eu-west-1:123456789012:key/volumes".to_string(),
+            ),
+            ("encrypted".to_string(), "true".to_string()),
+        ]);
+        let from_storage_class =
+            EbsVolumeSettings::from_storage_class_parameters(&parameters).unwrap();
+        assert_eq!(
+            from_storage_class,
+            EbsVolumeSettings {
+                availability_zone: None,
+                volume_type: Some("io2".to_string()),
+                iops: Some(4000),
+                throughput: None,
+                kms_key_id: Some("arn:aws:kms:eu-west-1:123456789012:key/volumes".to_string()),
+            }
+        );
+
+        let settings = EbsVolumeSettings {
+            availability_zone: Some("eu-west-1a".to_string()),
+            iops: Some(8000),
+            ..Default::default()
+        }
+        .or(from_storage_class);
+        assert_eq!(settings.availability_zone.as_deref(), Some("eu-west-1a"));
+        assert_eq!(settings.volume_type.as_deref(), Some("io2"));
+        assert_eq!(settings.iops, Some(8000));
+
+        assert!(
+            EbsVolumeSettings::from_storage_class_parameters(&BTreeMap::from([(
+                "throughput".to_string(),
+                "fast".to_string()
+            )]))
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use super::region::get_region_config;
+use anyhow::{bail, Context, Result};
+use aws_sdk_kms::{
+    types::{GrantOperation, KeyManagerType},
+    Client as KmsClient,
+};
+use tracing::info;
+
+/// Operations granted on the KMS key of a shared EBS snapshot, so that the other account can
+/// copy the snapshot or create a volume from it
+const SHARED_SNAPSHOT_GRANT_OPERATIONS: [GrantOperation; 6] = [
+    GrantOperation::Decrypt,
+    GrantOperation::DescribeKey,
+    GrantOperation::CreateGrant,
+    GrantOperation::ReEncryptFrom,
+    GrantOperation::ReEncryptTo,
+    GrantOperation::GenerateDataKeyWithoutPlaintext,
+];
+
+/// Create a KMS client
+///
+/// # Arguments
+///
+/// * `region` - AWS region
+///
+/// # Returns
+///
+/// KMS client
+pub async fn create_kms_client(region: Option<String>) -> Result<KmsClient> {
+    let region_config = get_region_config(region).await;
+    let kms_client = KmsClient::new(&region_config);
+    Ok(kms_client)
+}
+
+/// The name of the KMS grant given to an account for an EBS snapshot
+pub fn kms_grant_name(snapshot_id: &str, account_id: &str) -> String {
+    format!("snap-kube-{}-{}", snapshot_id, account_id)
+}
+
+/// The principal of the root of an account, i.e. any principal of the account allowed by its
+/// own IAM policies
+fn account_principal(account_id: &str) -> String {
+    format!("arn:aws:iam::{}:root", account_id)
+}
+
+/// Check that a KMS key can be granted to other accounts
+///
+/// The snapshots encrypted with an AWS managed key, such as the default `aws/ebs` key,
+/// cannot be shared, since the policy of such a key cannot be extended to other accounts.
+///
+/// # Arguments
+///
+/// * `kms_client` - KMS client
+/// * `key_id` - ID or ARN of the KMS key
+pub async fn ensure_kms_key_is_shareable(kms_client: &KmsClient, key_id: &str) -> Result<()> {
+    let resp = kms_client
+        .describe_key()
+        .key_id(key_id)
+        .send()
+        .await
+        .with_context(|| format!("Failed to describe KMS key {}", key_id))?;
+    if resp
+        .key_metadata
+        .and_then(|key_metadata| key_metadata.key_manager)
+        == Some(KeyManagerType::Aws)
+    {
+        bail!(
+            "KMS key {} is AWS managed and cannot be shared, copy the snapshot with a customer managed key first",
+            key_id
+        );
+    }
+    Ok(())
+}
+
+/// List the IDs of the grants of a KMS key with a given name
+///
+/// # Arguments
+///
+/// * `kms_client` - KMS client
+/// * `key_id` - ID or ARN of the KMS key
+/// * `grant_name` - Name of the grants
+///
+/// # Returns
+///
+/// The IDs of the grants
+async fn list_kms_grant_ids(
+    kms_client: &KmsClient,
+    key_id: &str,
+    grant_name: &str,
+) -> Result<Vec<String>> {
+    let mut grant_ids = Vec::new();
+    let mut marker = None;
+    loop {
+        let resp = kms_client
+            .list_grants()
+            .key_id(key_id)
+            .set_marker(marker)
+            .send()
+            .await
+            .with_context(|| format!("Failed to list the grants of KMS key {}", key_id))?;
+        grant_ids.extend(
+            resp.grants()
+                .iter()
+                .filter(|grant| grant.name() == Some(grant_name))
+                .filter_map(|grant| grant.grant_id().map(str::to_string)),
+        );
+        if !resp.truncated() {
+            return Ok(grant_ids);
+        }
+        marker = resp.next_marker().map(str::to_string);
+    }
+}
+
+/// Grant an account the use of the KMS key of an EBS snapshot
+///
+/// The grant is named after the snapshot and the account, so granting twice is a no-op.
+///
+/// # Arguments
+///
+/// * `kms_client` - KMS client of the region of the key
+/// * `key_id` - ID or ARN of the KMS key
+/// * `snapshot_id` - ID of the EBS snapshot encrypted with the key
+/// * `account_id` - ID of the account
+pub async fn create_kms_grant(
+    kms_client: &KmsClient,
+    key_id: &str,
+    snapshot_id: &str,
+    account_id: &str,
+) -> Result<()> {
+    let grant_name = kms_grant_name(snapshot_id, account_id);
+    if !list_kms_grant_ids(kms_client, key_id, &grant_name)
+        .await?
+        .is_empty()
+    {
+        info!(
+            "KMS key {} is already granted to account {}",
+            key_id, account_id
+        );
+        return Ok(());
+    }
+    kms_client
+        .create_grant()
+        .key_id(key_id)
+        .name(&grant_name)
+        .grantee_principal(account_principal(account_id))
+        .set_operations(Some(SHARED_SNAPSHOT_GRANT_OPERATIONS.to_vec()))
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to grant KMS key {} to account {}",
+                key_id, account_id
+            )
+        })?;
+    info!("Granted KMS key {} to account {}", key_id, account_id);
+    Ok(())
+}
+
+/// Revoke the grants given to an account for the KMS key of an EBS snapshot
+///
+/// # Arguments
+///
+/// * `kms_client` - KMS client of the region of the key
+/// * `key_id` - ID or ARN of the KMS key
+/// * `snapshot_id` - ID of the EBS snapshot encrypted with the key
+/// * `account_id` - ID of the account
+pub async fn revoke_kms_grant(
+    kms_client: &KmsClient,
+    key_id: &str,
+    snapshot_id: &str,
+    account_id: &str,
+) -> Result<()> {
+    let grant_name = kms_grant_name(snapshot_id, account_id);
+    for grant_id in list_kms_grant_ids(kms_client, key_id, &grant_name).await? {
+        kms_client
+            .revoke_grant()
+            .key_id(key_id)
+            .grant_id(&grant_id)
+            .send()
+            .await
+            .with_context(|| {
+                format!("Failed to revoke grant {} of KMS key {}", grant_id, key_id)
+            })?;
+        info!(
+            "Revoked the grant of KMS key {} to account {}",
+            key_id, account_id
+        );
+    }
+    Ok(())
+}
@@ -1,4 +1,9 @@
-#[cfg(feature = "backup")]
+#[cfg(any(feature = "backup", feature = "restore"))]
 pub mod ebs;
 #[cfg(feature = "backup")]
+pub mod kms;
+#[cfg(any(feature = "backup", feature = "restore"))]
 mod region;
+
+#[cfg(all(test, any(feature = "backup", feature = "restore")))]
+mod ebs_tests;
@@ -0,0 +1,334 @@
+use crate::k8s_ops::{
+    pod::pods::{default_container, PodApiTrait},
+    pvc::pvc_selector::label_selector_matches,
+};
+use anyhow::{bail, Context, Result};
+use k8s_openapi::api::core::v1::{PersistentVolumeClaim, Pod};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display, Formatter},
+    path::Path,
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// Represents what happens when a hook command fails or times out
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+pub enum HookOnError {
+    /// A failed pre hook fails the backup of the PVC, a failed post hook fails it after the fact
+    #[default]
+    Fail,
+    /// The failure is logged and the backup of the PVC goes on
+    Continue,
+}
+
+impl Display for HookOnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HookOnError::Fail => write!(f, "Fail"),
+            HookOnError::Continue => write!(f, "Continue"),
+        }
+    }
+}
+
+/// Represents when a hook command runs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookPhase {
+    /// Before the VolumeSnapshot is created
+    Pre,
+    /// After the VolumeSnapshot is bound, or its creation failed
+    Post,
+}
+
+impl Display for HookPhase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HookPhase::Pre => write!(f, "pre"),
+            HookPhase::Post => write!(f, "post"),
+        }
+    }
+}
+
+/// A command executed in each pod mounting a PVC
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecHook {
+    /// Container to run the command in, the default container of the pod if not set
+    pub container: Option<String>,
+    /// Command and its arguments, e.g. `["fsfreeze", "--freeze", "/data"]`
+    pub command: Vec<String>,
+    /// Maximum time in seconds to wait for the command to complete
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// What happens when the command fails or times out
+    #[serde(default)]
+    pub on_error: HookOnError,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+/// The hook commands run around the VolumeSnapshots of the PVCs matching a PVC name or selector
+///
+/// ```yaml
+/// - name: freeze-postgres
+///   selector: app=postgres
+///   pre:
+///     - command: ["psql", "-c", "CHECKPOINT"]
+///     - command: ["fsfreeze", "--freeze", "/var/lib/postgresql/data"]
+///   post:
+///     - command: ["fsfreeze", "--unfreeze", "/var/lib/postgresql/data"]
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupHook {
+    /// Unique name of the hook
+    pub name: String,
+    /// Name of the PVC the hook applies to
+    pub pvc_name: Option<String>,
+    /// Label selector for the PVCs the hook applies to, e.g. `app=postgres`
+    pub selector: Option<String>,
+    /// Commands run before the VolumeSnapshot is created
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre: Vec<ExecHook>,
+    /// Commands run after the VolumeSnapshot is bound, even if its creation failed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<ExecHook>,
+}
+
+impl BackupHook {
+    /// Check that the hook can be run
+    pub fn validate(&self) -> Result<()> {
+        if self.pvc_name.is_none() && self.selector.is_none() {
+            bail!("One of pvcName or selector is required");
+        }
+        if let Some(selector) = &self.selector {
+            label_selector_matches(selector, &Default::default())?;
+        }
+        if self.pre.is_empty() && self.post.is_empty() {
+            bail!("At least one pre or post command is required");
+        }
+        if self
+            .pre
+            .iter()
+            .chain(&self.post)
+            .any(|exec_hook| exec_hook.command.is_empty())
+        {
+            bail!("A hook command cannot be empty");
+        }
+        Ok(())
+    }
+
+    /// Whether the hook applies to a PVC
+    pub fn matches(&self, pvc: &PersistentVolumeClaim) -> bool {
+        let name_matches = match &self.pvc_name {
+            Some(pvc_name) => pvc.metadata.name.as_ref() == Some(pvc_name),
+            None => true,
+        };
+        let selector_matches = match &self.selector {
+            Some(selector) => label_selector_matches(
+                selector,
+                pvc.metadata.labels.as_ref().unwrap_or(&Default::default()),
+            )
+            .unwrap_or(false),
+            None => true,
+        };
+        name_matches && selector_matches
+    }
+}
+
+/// The hooks of a backup, making the VolumeSnapshots application-consistent
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BackupHooks {
+    hooks: Vec<BackupHook>,
+}
+
+impl BackupHooks {
+    /// Creates a new BackupHooks
+    ///
+    /// # Arguments
+    ///
+    /// * `hooks` - The hooks, run in this order before the VolumeSnapshot is created, and in
+    ///   the reverse order after it is bound
+    ///
+    /// # Returns
+    ///
+    /// A new BackupHooks instance, or an error if a hook is invalid
+    pub fn new(hooks: Vec<BackupHook>) -> Result<Self> {
+        let mut names = BTreeSet::new();
+        for hook in &hooks {
+            if !names.insert(hook.name.as_str()) {
+                bail!("Duplicate hook name: {}", hook.name);
+            }
+            hook.validate()
+                .with_context(|| format!("Invalid hook: {}", hook.name))?;
+        }
+        Ok(Self { hooks })
+    }
+
+    /// Read the hooks from a YAML file holding a list of hooks
+    pub async fn from_file(path: &Path) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read the hooks file: {}", path.display()))?;
+        let hooks: Vec<BackupHook> = serde_yaml::from_str(&content)
+            .with_context(|| format!("Invalid hooks file: {}", path.display()))?;
+        Self::new(hooks)
+    }
+
+    pub fn hooks(&self) -> &[BackupHook] {
+        &self.hooks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// The hooks that apply to a PVC, in order
+    pub fn for_pvc(&self, pvc: &PersistentVolumeClaim) -> Vec<&BackupHook> {
+        self.hooks.iter().filter(|hook| hook.matches(pvc)).collect()
+    }
+}
+
+/// Run the pre hooks of a PVC in each pod mounting it
+///
+/// The commands run in the order of the hooks. The first command that fails with the `Fail`
+/// policy stops the pre hooks, and the VolumeSnapshot should not be created. The post hooks
+/// should still run, to undo the commands that already ran.
+///
+/// # Arguments
+///
+/// * `pod_api` - Api object for Pod
+/// * `pods` - The pods mounting the PVC
+/// * `hooks` - The hooks of the PVC
+///
+/// # Returns
+///
+/// An error if a command with the `Fail` policy failed
+pub async fn run_pre_hooks(
+    pod_api: &impl PodApiTrait,
+    pods: &[Pod],
+    hooks: &[&BackupHook],
+) -> Result<()> {
+    for hook in hooks {
+        for exec_hook in &hook.pre {
+            for pod in pods {
+                run_exec_hook(pod_api, pod, &hook.name, HookPhase::Pre, exec_hook).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run the post hooks of a PVC in each pod mounting it
+///
+/// The commands run in the reverse order of the hooks, and all of them run even if some fail.
+///
+/// # Arguments
+///
+/// * `pod_api` - Api object for Pod
+/// * `pods` - The pods mounting the PVC
+/// * `hooks` - The hooks of the PVC
+///
+/// # Returns
+///
+/// An error if a command with the `Fail` policy failed
+pub async fn run_post_hooks(
+    pod_api: &impl PodApiTrait,
+    pods: &[Pod],
+    hooks: &[&BackupHook],
+) -> Result<()> {
+    let mut errors = Vec::new();
+    for hook in hooks.iter().rev() {
+        for exec_hook in &hook.post {
+            for pod in pods {
+                if let Err(e) =
+                    run_exec_hook(pod_api, pod, &hook.name, HookPhase::Post, exec_hook).await
+                {
+                    errors.push(format!("{:#}", e));
+                }
+            }
+        }
+    }
+    if !errors.is_empty() {
+        bail!(errors.join("; "));
+    }
+    Ok(())
+}
+
+/// Run a single hook command in a pod, within its timeout
+///
+/// # Arguments
+///
+/// * `pod_api` - Api object for Pod
+/// * `pod` - The pod to run the command in
+/// * `hook_name` - Name of the hook of the command
+/// * `phase` - Whether the command is a pre or a post hook
+/// * `exec_hook` - The command to run
+///
+/// # Returns
+///
+/// An error if the command failed and its policy is `Fail`
+async fn run_exec_hook(
+    pod_api: &impl PodApiTrait,
+    pod: &Pod,
+    hook_name: &str,
+    phase: HookPhase,
+    exec_hook: &ExecHook,
+) -> Result<()> {
+    let pod_name = pod.metadata.name.clone().unwrap_or_default();
+    let container = match exec_hook
+        .container
+        .clone()
+        .or_else(|| default_container(pod))
+    {
+        Some(container) => container,
+        None => bail!("Pod {} has no container", pod_name),
+    };
+    info!(
+        "Running {} hook {} in pod {}, container {}: {:?}",
+        phase, hook_name, pod_name, container, exec_hook.command
+    );
+
+    let timeout = Duration::from_secs(exec_hook.timeout_secs);
+    let result = match tokio::time::timeout(
+        timeout,
+        pod_api.exec(&pod_name, &container, &exec_hook.command),
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(_) => Err(anyhow::anyhow!(
+            "Command {:?} timed out after {}s in pod {}",
+            exec_hook.command,
+            timeout.as_secs(),
+            pod_name
+        )),
+    };
+
+    match (result, exec_hook.on_error) {
+        (Ok(output), _) => {
+            info!(
+                "The {} hook {} completed in pod {}: {}",
+                phase,
+                hook_name,
+                pod_name,
+                output.stdout.trim()
+            );
+            Ok(())
+        }
+        (Err(e), HookOnError::Continue) => {
+            warn!(
+                "The {} hook {} failed in pod {}, continuing: {:#}",
+                phase, hook_name, pod_name, e
+            );
+            Ok(())
+        }
+        (Err(e), HookOnError::Fail) => {
+            Err(e.context(format!("The {} hook {} failed", phase, hook_name)))
+        }
+    }
+}
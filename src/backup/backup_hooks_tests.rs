@@ -0,0 +1,235 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backup::backup_hooks::{
+            run_post_hooks, run_pre_hooks, BackupHook, BackupHooks, ExecHook, HookOnError,
+        },
+        k8s_ops::pod::pods::{ExecOutput, MockPodApiTrait},
+    };
+    use anyhow::anyhow;
+    use k8s_openapi::api::core::v1::{Container, PersistentVolumeClaim, Pod, PodSpec};
+    use kube::api::ObjectMeta;
+    use mockall::{predicate, Sequence};
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    const HOOKS: &str = r#"
+- name: freeze-data
+  pvcName: data
+  pre:
+    - command: ["fsfreeze", "--freeze", "/data"]
+      timeoutSecs: 10
+  post:
+    - command: ["fsfreeze", "--unfreeze", "/data"]
+- name: postgres-checkpoint
+  selector: app=postgres,tier!=cache
+  pre:
+    - container: postgres
+      command: ["psql", "-c", "CHECKPOINT"]
+  post:
+    - container: postgres
+      command: ["psql", "-c", "SELECT pg_switch_wal()"]
+      onError: Continue
+"#;
+
+    fn hooks() -> Vec<BackupHook> {
+        serde_yaml::from_str(HOOKS).unwrap()
+    }
+
+    fn pvc(name: &str, labels: &[(&str, &str)]) -> PersistentVolumeClaim {
+        PersistentVolumeClaim {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                labels: Some(
+                    labels
+                        .iter()
+                        .map(|(key, value)| (key.to_string(), value.to_string()))
+                        .collect::<BTreeMap<_, _>>(),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn pod(name: &str) -> Pod {
+        Pod {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                ..Default::default()
+            },
+            spec: Some(PodSpec {
+                containers: vec![Container {
+                    name: "app".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn command(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_backup_hooks_from_yaml() {
+        let hooks = hooks();
+
+        assert_eq!(hooks[0].pre[0].timeout_secs, 10);
+        assert_eq!(hooks[0].post[0].timeout_secs, 30);
+        assert_eq!(hooks[0].post[0].on_error, HookOnError::Fail);
+        assert_eq!(hooks[1].post[0].on_error, HookOnError::Continue);
+        assert!(BackupHooks::new(hooks).is_ok());
+    }
+
+    #[test]
+    fn test_backup_hooks_validation() {
+        let mut duplicate = hooks();
+        duplicate[1].name = "freeze-data".to_string();
+        assert!(BackupHooks::new(duplicate).is_err());
+
+        let mut no_target = hooks();
+        no_target[0].pvc_name = None;
+        assert!(BackupHooks::new(no_target).is_err());
+
+        let mut set_based = hooks();
+        set_based[1].selector = Some("app in (postgres, mysql)".to_string());
+        assert!(BackupHooks::new(set_based).is_err());
+
+        let mut empty_command = hooks();
+        empty_command[0].post[0].command.clear();
+        assert!(BackupHooks::new(empty_command).is_err());
+    }
+
+    #[test]
+    fn test_backup_hooks_for_pvc() {
+        let hooks = BackupHooks::new(hooks()).unwrap();
+        let names = |pvc: &PersistentVolumeClaim| {
+            hooks
+                .for_pvc(pvc)
+                .iter()
+                .map(|hook| hook.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(&pvc("data", &[("app", "postgres")])),
+            vec!["freeze-data", "postgres-checkpoint"]
+        );
+        assert_eq!(
+            names(&pvc("wal", &[("app", "postgres"), ("tier", "cache")])),
+            Vec::<String>::new()
+        );
+        assert_eq!(names(&pvc("data", &[])), vec!["freeze-data"]);
+    }
+
+    #[tokio::test]
+    async fn test_run_pre_hooks_stops_on_failure() {
+        let hooks = hooks();
+        let hooks: Vec<&BackupHook> = hooks.iter().collect();
+        let mut mock_pod_api = MockPodApiTrait::new();
+        mock_pod_api
+            .expect_exec()
+            .with(
+                predicate::eq("postgres-0"),
+                predicate::eq("app"),
+                predicate::eq(command(&["fsfreeze", "--freeze", "/data"])),
+            )
+            .times(1)
+            .returning(|_, _, _| Err(anyhow!("fsfreeze: /data: Operation not supported")));
+
+        let result = run_pre_hooks(&mock_pod_api, &[pod("postgres-0")], &hooks).await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("The pre hook freeze-data failed"));
+    }
+
+    #[tokio::test]
+    async fn test_run_post_hooks_runs_all_in_reverse_order() {
+        let hooks = hooks();
+        let hooks: Vec<&BackupHook> = hooks.iter().collect();
+        let mut sequence = Sequence::new();
+        let mut mock_pod_api = MockPodApiTrait::new();
+        mock_pod_api
+            .expect_exec()
+            .with(
+                predicate::eq("postgres-0"),
+                predicate::eq("postgres"),
+                predicate::eq(command(&["psql", "-c", "SELECT pg_switch_wal()"])),
+            )
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_, _, _| Err(anyhow!("recovery is in progress")));
+        mock_pod_api
+            .expect_exec()
+            .with(
+                predicate::eq("postgres-0"),
+                predicate::eq("app"),
+                predicate::eq(command(&["fsfreeze", "--unfreeze", "/data"])),
+            )
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_, _, _| Ok(ExecOutput::default()));
+
+        // The failure of pg_switch_wal is ignored, as its policy is Continue
+        run_post_hooks(&mock_pod_api, &[pod("postgres-0")], &hooks)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_pre_hooks_continue_on_error() {
+        let mut hooks = hooks();
+        hooks[0].pre[0].on_error = HookOnError::Continue;
+        let hooks: Vec<&BackupHook> = hooks.iter().collect();
+        let mut mock_pod_api = MockPodApiTrait::new();
+        mock_pod_api
+            .expect_exec()
+            .times(2)
+            .returning(|_, _, command| match command[0].as_str() {
+                "fsfreeze" => Err(anyhow!("fsfreeze: /data: Operation not supported")),
+                _ => Ok(ExecOutput {
+                    stdout: "0/1A2B3C4D".to_string(),
+                    stderr: String::new(),
+                }),
+            });
+
+        run_pre_hooks(&mock_pod_api, &[pod("postgres-0")], &hooks)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_hooks_without_pods() {
+        let hooks = hooks();
+        let hooks: Vec<&BackupHook> = hooks.iter().collect();
+        let mock_pod_api = MockPodApiTrait::new();
+
+        run_pre_hooks(&mock_pod_api, &[], &hooks).await.unwrap();
+        run_post_hooks(&mock_pod_api, &[], &hooks).await.unwrap();
+    }
+
+    #[test]
+    fn test_exec_hook_serialization() {
+        let exec_hook = ExecHook {
+            container: None,
+            command: command(&["sync"]),
+            timeout_secs: 30,
+            on_error: HookOnError::Continue,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&exec_hook).unwrap(),
+            serde_json::json!({
+                "container": null,
+                "command": ["sync"],
+                "timeoutSecs": 30,
+                "onError": "Continue"
+            })
+        );
+    }
+}
@@ -1,115 +1,1067 @@
-use super::backup_payload::BackupPayload;
+use super::{
+    backup_hooks::{run_post_hooks, run_pre_hooks},
+    backup_payload::BackupPayload,
+    backup_summary::{BackupSummary, PvcBackupResult, PvcBackupStatus},
+};
 use crate::{
-    aws_ops::ebs::create_ebs_client,
+    aws_ops::ebs::{
+        copy_ebs_snapshot, create_ebs_client, tag_ebs_snapshot,
+        wait_untill_ebs_snapshot_is_completed, SnapshotCopyTarget,
+    },
+    catalog::{
+        backup_catalog::open_catalog_store,
+        catalog_entry::{generate_backup_id, BackupCatalogEntry, EbsSnapshotCopy},
+    },
     k8s_ops::{
-        pvc::persistent_volume_claims::{check_if_pvc_exists, get_pvcs_available, KubePvcApi},
+        ns::namespaces::{resolve_namespaces, KubeNamespaceApi, NamespaceSelection},
+        pod::pods::{get_pods_mounting_pvc, KubePodApi},
+        pvc::{
+            persistent_volume_claims::{check_if_pvc_exists, get_pvcs_available, KubePvcApi},
+            pvc_template::PvcTemplate,
+        },
+        vgs::volume_group_snapshots::{
+            construct_volume_group_snapshot_resource, group_snapshot_members,
+            group_snapshot_selector, is_volume_group_snapshot_bound_or_failed,
+            is_volume_group_snapshot_ready_or_failed, wait_untill_group_snapshot,
+        },
         vs::{
-            volume_snapshots::wait_untill_snapshot_is_ready,
+            volume_snapshot_naming::{volume_snapshot_labels, SOURCE_PVC_LABEL},
+            volume_snapshots::{wait_untill_snapshot_is_bound, wait_untill_snapshot_is_ready},
+            volume_snapshots_error::VolumeSnapshotError,
             volume_snapshots_operator::VolumeSnapshotOperator,
         },
-        vsc::retain_policy::VSCRetainPolicy,
+        vsc::{retain_policy::VSCRetainPolicy, volume_snapshot_contents::get_snapshot_handle},
+        workload::workloads::{
+            get_workloads_mounting_pvcs, recover_workloads, restore_workloads,
+            scale_down_workloads, wait_for_pods_to_terminate, KubeWorkloadApi, Workload,
+        },
     },
 };
-use anyhow::{bail, Result};
-use kube::{api::PostParams, Api, Client};
+use anyhow::{anyhow, bail, Context, Result};
+use aws_sdk_ec2::Client as EbsClient;
+use chrono::{DateTime, Utc};
+use futures::{future::join_all, stream, StreamExt};
+use k8s_openapi::api::core::v1::PersistentVolumeClaim;
+use kube::{
+    api::{Patch, PatchParams, PostParams},
+    Api, Client,
+};
+use kube_custom_resources_rs::groupsnapshot_storage_k8s_io::v1alpha1::volumegroupsnapshots::VolumeGroupSnapshot;
 use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::{
     volumesnapshotcontents::VolumeSnapshotContent,
     volumesnapshots::{VolumeSnapshot, VolumeSnapshotStatus},
 };
-use tracing::info;
+use serde_json::json;
+use std::{collections::BTreeMap, future::Future, time::Duration};
+use tracing::{error, info, warn};
+
+/// A VolumeSnapshot that was created, along with its source PVC
+struct CreatedVolumeSnapshot {
+    volume_snapshot_name: String,
+    source_pvc: PersistentVolumeClaim,
+    /// The VolumeGroupSnapshot the VolumeSnapshot is a member of, if any
+    volume_group_snapshot_name: Option<String>,
+}
+
+impl CreatedVolumeSnapshot {
+    fn new(volume_snapshot_name: String, source_pvc: PersistentVolumeClaim) -> Self {
+        Self {
+            volume_snapshot_name,
+            source_pvc,
+            volume_group_snapshot_name: None,
+        }
+    }
+}
+
+/// A VolumeGroupSnapshot that was created, whose member VolumeSnapshots may not exist yet
+struct CreatedVolumeGroupSnapshot {
+    volume_group_snapshot_name: String,
+    /// The source PVC of each expected member
+    source_pvcs: BTreeMap<String, PersistentVolumeClaim>,
+}
+
+/// The snapshots created for the PVCs, before waiting for them to be ready
+enum CreatedSnapshots {
+    /// The namespace, name and created VolumeSnapshot of each PVC
+    Single(Vec<(String, String, Result<CreatedVolumeSnapshot>)>),
+    /// The namespace, names and created VolumeGroupSnapshot of the PVCs of each namespace
+    Group(Vec<(String, Vec<String>, Result<CreatedVolumeGroupSnapshot>)>),
+}
 
 /// A struct for backing up a PVC to a VolumeSnapshot
 pub struct BackupOperator;
 
 impl BackupOperator {
-    /// Takes a backup of one or more PVCs from a specific namespace to a VolumeSnapshot/VolumeSnapshotContent
-    pub async fn backup(backup_payload: BackupPayload) -> Result<()> {
+    /// Takes a backup of one or more PVCs from one or more namespaces to a VolumeSnapshot/VolumeSnapshotContent
+    ///
+    /// All the VolumeSnapshots are created up front, so that the PVCs are snapshotted at nearly
+    /// the same moment, and then they are waited on concurrently. Both steps are bounded by the
+    /// `max_concurrency` of the payload. A failure of one PVC does not abort the others, and the
+    /// outcome of each PVC is reported in the returned summary, grouped per namespace.
+    ///
+    /// If a catalog location is set, an entry for every PVC backed up is saved in the catalog
+    /// under the backup ID of the run. A failure to save them fails the summary, but keeps
+    /// the outcome of each PVC.
+    ///
+    /// If a VolumeGroupSnapshotClass is set, the PVCs of each namespace are snapshotted at
+    /// the same moment with a VolumeGroupSnapshot, and its member VolumeSnapshots are
+    /// recorded like the ones of single PVCs.
+    ///
+    /// If a scale down timeout is set, the Deployments and StatefulSets mounting the PVCs are
+    /// scaled to zero until the VolumeSnapshots or VolumeGroupSnapshots are bound, and then
+    /// back to their original replicas, even if the backup fails or the shutdown future
+    /// completes meanwhile. The original replicas are recorded in an annotation, so that
+    /// `recover` can restore them after a crash.
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_payload` - Payload of the backup operation
+    /// * `shutdown` - Future that completes when the backup should be interrupted, e.g. on
+    ///   a SIGTERM. It is only awaited while the workloads are scaled down
+    /// * `on_result` - Called with the outcome of each PVC as soon as it is known, e.g. to
+    ///   report the progress of the backup
+    pub async fn backup(
+        backup_payload: BackupPayload,
+        shutdown: impl Future<Output = ()>,
+        on_result: impl Fn(&PvcBackupResult),
+    ) -> Result<BackupSummary> {
+        backup_payload.validate_group_snapshot()?;
+        let created_at = Utc::now();
+        let backup_run = BackupRun {
+            backup_id: generate_backup_id(created_at),
+            created_at,
+        };
+        info!("Backup ID: {}", backup_run.backup_id);
+
         // Create a Kubernetes client
         let k8s_client = Client::try_default().await?;
 
         // Create an AWS EBS client
-        let ebs_client = create_ebs_client(Some(backup_payload.region().to_string()))
-            .await
-            .unwrap();
+        let ebs_client = create_ebs_client(Some(backup_payload.region().to_string())).await?;
 
-        // Define the VolumeSnapshot and VolumeSnapshotContent APIs
-        let restore_k8s_apis_struct = BackupKubernetesApisStruct {
-            source_vs_api: Api::namespaced(k8s_client.clone(), backup_payload.source_ns()),
-            source_pvcs_api: KubePvcApi {
-                api: Api::namespaced(k8s_client.clone(), backup_payload.source_ns()),
+        // Resolve the namespaces to backup
+        let namespaces = resolve_namespaces(
+            &KubeNamespaceApi {
+                api: Api::all(k8s_client.clone()),
             },
-            vsc_api: Api::all(k8s_client.clone()),
+            backup_payload.namespace_selection(),
+        )
+        .await?;
+
+        // Define the VolumeSnapshot and VolumeSnapshotContent APIs of each namespace,
+        // and collect the PVCs to backup from each one of them
+        let mut backup_k8s_apis: BTreeMap<String, BackupKubernetesApisStruct> = BTreeMap::new();
+        let mut pvcs: Vec<(String, String)> = Vec::new();
+        for namespace in namespaces {
+            let backup_k8s_apis_struct = BackupKubernetesApisStruct {
+                source_vs_api: Api::namespaced(k8s_client.clone(), &namespace),
+                source_pvcs_api: KubePvcApi {
+                    api: Api::namespaced(k8s_client.clone(), &namespace),
+                },
+                vsc_api: Api::all(k8s_client.clone()),
+                source_vgs_api: Api::namespaced(k8s_client.clone(), &namespace),
+                pods_api: KubePodApi {
+                    api: Api::namespaced(k8s_client.clone(), &namespace),
+                },
+                workloads_api: KubeWorkloadApi {
+                    deployments_api: Api::namespaced(k8s_client.clone(), &namespace),
+                    stateful_sets_api: Api::namespaced(k8s_client.clone(), &namespace),
+                },
+            };
+
+            // Check if we will backup all PVCs in the namespace, or the ones matching the selector
+            let namespace_pvcs = if backup_payload.include_all_pvcs()
+                || !backup_payload.pvc_selector().is_empty()
+            {
+                get_pvcs_available(
+                    &backup_k8s_apis_struct.source_pvcs_api,
+                    backup_payload.pvc_selector(),
+                )
+                .await?
+            } else {
+                vec![backup_payload
+                    .pvc_name()
+                    .context("PVC name is required when include_all_pvcs is false and no PVC selector is set")?
+                    .to_string()]
+            };
+            if namespace_pvcs.is_empty() {
+                info!("No PVCs to backup in namespace: {}", namespace);
+            }
+
+            pvcs.extend(
+                namespace_pvcs
+                    .into_iter()
+                    .map(|pvc| (namespace.clone(), pvc)),
+            );
+            backup_k8s_apis.insert(namespace, backup_k8s_apis_struct);
+        }
+
+        // Create a VolumeSnapshot for each PVC before waiting on any of them, with the
+        // workloads mounting the PVCs scaled down if requested
+        let created = match backup_payload.scale_down_timeout() {
+            Some(scale_down_timeout) => {
+                let mut scaled_down: BTreeMap<String, Vec<Workload>> = BTreeMap::new();
+                let created = tokio::select! {
+                    created = Self::scale_down_and_create_volume_snapshots(
+                        &backup_payload,
+                        &backup_k8s_apis,
+                        &backup_run,
+                        pvcs,
+                        scale_down_timeout,
+                        &mut scaled_down,
+                    ) => created,
+                    _ = shutdown => Err(anyhow!("The backup was interrupted")),
+                };
+                Self::restore_scaled_down_workloads(&backup_k8s_apis, &scaled_down).await;
+                created?
+            }
+            None => {
+                Self::create_volume_snapshots(&backup_payload, &backup_k8s_apis, &backup_run, pvcs)
+                    .await
+            }
         };
 
-        // Check if we will backup all PVCs in the namespace
-        let pvcs = if backup_payload.include_all_pvcs() {
-            get_pvcs_available(&restore_k8s_apis_struct.source_pvcs_api).await?
-        } else {
-            vec![backup_payload
-                .pvc_name()
-                .unwrap_or_else(|| panic!("PVC name is required when include_all_pvcs is false"))
-                .to_string()]
+        // Wait for the VolumeGroupSnapshots to be ready, once the workloads are back
+        let created =
+            Self::member_volume_snapshots(&backup_payload, &backup_k8s_apis, &backup_run, created)
+                .await;
+
+        // Wait for all the VolumeSnapshots to be ready, and keep the outcome of each PVC
+        let results: Vec<PvcBackupResult> = stream::iter(created)
+            .map(|(namespace, pvc, created)| async {
+                let status = match created {
+                    Ok(created) => {
+                        Self::wait_for_volume_snapshot(
+                            &backup_payload,
+                            &backup_k8s_apis[&namespace],
+                            &ebs_client,
+                            &backup_run,
+                            created,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                let status = status.unwrap_or_else(|e| {
+                    error!(
+                        "Failed to backup PVC {} on namespace {}: {:#}",
+                        pvc, namespace, e
+                    );
+                    PvcBackupStatus::Failed(format!("{:#}", e))
+                });
+                let result = PvcBackupResult::new(namespace, pvc, status);
+                on_result(&result);
+                result
+            })
+            .buffer_unordered(backup_payload.max_concurrency())
+            .collect()
+            .await;
+
+        let mut backup_summary = BackupSummary::new(backup_run.backup_id, results);
+
+        // Save the PVCs backed up to the catalog. The VolumeSnapshots are taken by now, so
+        // a failure is reported in the summary rather than discarding it
+        if let Some(catalog_location) = backup_payload.catalog() {
+            let catalog_entries = backup_summary.catalog_entries();
+            if !catalog_entries.is_empty() {
+                if let Err(e) = open_catalog_store(k8s_client.clone(), catalog_location)
+                    .save(&backup_summary.backup_id, &catalog_entries)
+                    .await
+                {
+                    error!(
+                        "Failed to save backup {} to the catalog: {:#}",
+                        backup_summary.backup_id, e
+                    );
+                    backup_summary.catalog_error = Some(format!("{:#}", e));
+                }
+            }
+        }
+
+        Ok(backup_summary)
+    }
+
+    /// Restore the workloads left scaled down by an interrupted backup
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace_selection` - The namespaces of the workloads
+    ///
+    /// # Returns
+    ///
+    /// The namespace of each workload restored, along with the workload
+    pub async fn recover(
+        namespace_selection: &NamespaceSelection,
+    ) -> Result<Vec<(String, Workload)>> {
+        let k8s_client = Client::try_default().await?;
+        let namespaces = resolve_namespaces(
+            &KubeNamespaceApi {
+                api: Api::all(k8s_client.clone()),
+            },
+            namespace_selection,
+        )
+        .await?;
+
+        let mut recovered = Vec::new();
+        for namespace in namespaces {
+            let workloads = recover_workloads(&KubeWorkloadApi {
+                deployments_api: Api::namespaced(k8s_client.clone(), &namespace),
+                stateful_sets_api: Api::namespaced(k8s_client.clone(), &namespace),
+            })
+            .await?;
+            recovered.extend(
+                workloads
+                    .into_iter()
+                    .map(|workload| (namespace.clone(), workload)),
+            );
+        }
+        Ok(recovered)
+    }
+
+    /// Create a VolumeSnapshot for each PVC, or a VolumeGroupSnapshot for the PVCs of each
+    /// namespace, without waiting for them to be ready
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_payload` - Payload of the backup operation
+    /// * `backup_k8s_apis` - Kubernetes APIs of each namespace
+    /// * `backup_run` - The backup run the VolumeSnapshots belong to
+    /// * `pvcs` - The namespace and name of each PVC to snapshot
+    ///
+    /// # Returns
+    ///
+    /// The created VolumeSnapshot of each PVC, or VolumeGroupSnapshot of each namespace
+    async fn create_volume_snapshots(
+        backup_payload: &BackupPayload,
+        backup_k8s_apis: &BTreeMap<String, BackupKubernetesApisStruct>,
+        backup_run: &BackupRun,
+        pvcs: Vec<(String, String)>,
+    ) -> CreatedSnapshots {
+        if let Some(volume_group_snapshot_class) = backup_payload.volume_group_snapshot_class() {
+            let mut namespace_pvcs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for (namespace, pvc) in pvcs {
+                namespace_pvcs.entry(namespace).or_default().push(pvc);
+            }
+            return CreatedSnapshots::Group(
+                stream::iter(namespace_pvcs)
+                    .map(|(namespace, pvc_names)| async move {
+                        let created = Self::create_volume_group_snapshot(
+                            backup_payload,
+                            &backup_k8s_apis[&namespace],
+                            backup_run,
+                            volume_group_snapshot_class,
+                            &namespace,
+                            &pvc_names,
+                        )
+                        .await;
+                        (namespace, pvc_names, created)
+                    })
+                    .buffer_unordered(backup_payload.max_concurrency())
+                    .collect()
+                    .await,
+            );
+        }
+
+        let created = stream::iter(pvcs)
+            .map(|(namespace, pvc)| async {
+                let created = Self::create_volume_snapshot(
+                    backup_payload,
+                    &backup_k8s_apis[&namespace],
+                    backup_run,
+                    &namespace,
+                    &pvc,
+                )
+                .await;
+                (namespace, pvc, created)
+            })
+            .buffer_unordered(backup_payload.max_concurrency())
+            .collect()
+            .await;
+        CreatedSnapshots::Single(created)
+    }
+
+    /// Wait for the VolumeGroupSnapshots created to be ready, and collect their members
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_payload` - Payload of the backup operation
+    /// * `backup_k8s_apis` - Kubernetes APIs of each namespace
+    /// * `backup_run` - The backup run the VolumeSnapshots belong to
+    /// * `created` - The snapshots created for the PVCs
+    ///
+    /// # Returns
+    ///
+    /// The namespace, name and created VolumeSnapshot of each PVC
+    async fn member_volume_snapshots(
+        backup_payload: &BackupPayload,
+        backup_k8s_apis: &BTreeMap<String, BackupKubernetesApisStruct>,
+        backup_run: &BackupRun,
+        created: CreatedSnapshots,
+    ) -> Vec<(String, String, Result<CreatedVolumeSnapshot>)> {
+        let groups = match created {
+            CreatedSnapshots::Single(created) => return created,
+            CreatedSnapshots::Group(groups) => groups,
         };
+        stream::iter(groups)
+            .map(|(namespace, pvc_names, created)| async move {
+                let members = match created {
+                    Ok(created) => {
+                        Self::wait_for_volume_group_snapshot(
+                            backup_payload,
+                            &backup_k8s_apis[&namespace],
+                            backup_run,
+                            created,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                Self::group_members_outcome(namespace, pvc_names, members)
+            })
+            .buffer_unordered(backup_payload.max_concurrency())
+            .flat_map(stream::iter)
+            .collect()
+            .await
+    }
 
-        // We will iterate over the PVCs vector and backup each PVC
-        for pvc in pvcs {
-            info!("Backing up PVC: {}", pvc);
-            let volume_snapshot_name = format!("{}-{}", backup_payload.vs_name_prefix(), pvc);
+    /// Split the outcome of a VolumeGroupSnapshot into the outcome of each of its PVCs
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the PVCs
+    /// * `pvc_names` - Names of the PVCs expected in the group
+    /// * `created` - The member VolumeSnapshot of each PVC, or the error of the group
+    ///
+    /// # Returns
+    ///
+    /// The namespace, name and created VolumeSnapshot of each PVC
+    fn group_members_outcome(
+        namespace: String,
+        pvc_names: Vec<String>,
+        created: Result<BTreeMap<String, CreatedVolumeSnapshot>>,
+    ) -> Vec<(String, String, Result<CreatedVolumeSnapshot>)> {
+        match created {
+            Ok(mut members) => pvc_names
+                .into_iter()
+                .map(|pvc| {
+                    let created = members.remove(&pvc).with_context(|| {
+                        format!("PVC {} is not a member of the VolumeGroupSnapshot", pvc)
+                    });
+                    (namespace.clone(), pvc, created)
+                })
+                .collect(),
+            Err(e) => {
+                let e = format!("{:#}", e);
+                pvc_names
+                    .into_iter()
+                    .map(|pvc| (namespace.clone(), pvc, Err(anyhow!(e.clone()))))
+                    .collect()
+            }
+        }
+    }
+
+    /// Create a VolumeGroupSnapshot of the PVCs of a namespace, without waiting for it to be
+    /// ready
+    ///
+    /// The hooks of the PVCs run around the VolumeGroupSnapshot, like around the VolumeSnapshot
+    /// of a single PVC. With hooks, or with the workloads scaled down, it is waited on until it
+    /// is bound, i.e. until the snapshot is cut.
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_payload` - Payload of the backup operation
+    /// * `backup_k8s_apis_struct` - Kubernetes APIs of the namespace of the PVCs
+    /// * `backup_run` - The backup run the VolumeGroupSnapshot belongs to
+    /// * `volume_group_snapshot_class` - VolumeGroupSnapshotClass name
+    /// * `namespace` - Namespace of the PVCs
+    /// * `pvc_names` - Names of the PVCs expected in the group
+    ///
+    /// # Returns
+    ///
+    /// The created VolumeGroupSnapshot, along with the source PVC of each expected member
+    async fn create_volume_group_snapshot(
+        backup_payload: &BackupPayload,
+        backup_k8s_apis_struct: &BackupKubernetesApisStruct,
+        backup_run: &BackupRun,
+        volume_group_snapshot_class: &str,
+        namespace: &str,
+        pvc_names: &[String],
+    ) -> Result<CreatedVolumeGroupSnapshot> {
+        info!(
+            "Backing up PVCs: {:?} on namespace: {} as a group",
+            pvc_names, namespace
+        );
+        let volume_group_snapshot_name = backup_payload.vs_name_template().render(
+            backup_payload.vs_name_prefix(),
+            namespace,
+            "group",
+            backup_run.created_at,
+        );
+
+        let mut source_pvcs: BTreeMap<String, PersistentVolumeClaim> = BTreeMap::new();
+        let mut pvc_hooks = Vec::new();
+        for pvc in pvc_names {
+            let source_pvc =
+                check_if_pvc_exists(&backup_k8s_apis_struct.source_pvcs_api, pvc, true)
+                    .await?
+                    .with_context(|| format!("PVC {} does not exist", pvc))?;
+            let hooks = backup_payload.hooks().for_pvc(&source_pvc);
+            if !hooks.is_empty() {
+                let pods = get_pods_mounting_pvc(&backup_k8s_apis_struct.pods_api, pvc).await?;
+                pvc_hooks.push((pods, hooks));
+            }
+            source_pvcs.insert(pvc.clone(), source_pvc);
+        }
 
-            // Check if the PVC exists, it should exist
-            check_if_pvc_exists(&restore_k8s_apis_struct.source_pvcs_api, &pvc, true).await?;
+        let volume_group_snapshot = construct_volume_group_snapshot_resource(
+            &volume_group_snapshot_name,
+            namespace,
+            volume_group_snapshot_class,
+            group_snapshot_selector(
+                backup_payload
+                    .pvc_selector()
+                    .label_selector
+                    .as_deref()
+                    .unwrap_or_default(),
+            )?,
+            volume_snapshot_labels(backup_payload.vs_name_prefix(), "", &backup_run.backup_id)
+                .into_iter()
+                .filter(|(key, _)| key != SOURCE_PVC_LABEL)
+                .collect(),
+        );
 
-            let vs_operator = VolumeSnapshotOperator::new(
-                volume_snapshot_name.to_string(),
-                backup_payload.source_ns.to_string(),
-                backup_payload.volume_snapshot_class().to_string(),
-                Some(pvc),
-                None,
+        // The post hooks run even if the pre hooks or the VolumeGroupSnapshot failed
+        let mut snapshot: Result<()> = Ok(());
+        for (pods, hooks) in &pvc_hooks {
+            snapshot = run_pre_hooks(&backup_k8s_apis_struct.pods_api, pods, hooks).await;
+            if snapshot.is_err() {
+                break;
+            }
+        }
+        if snapshot.is_ok() {
+            snapshot = Self::submit_volume_group_snapshot(
+                backup_k8s_apis_struct,
+                &volume_group_snapshot,
+                namespace,
+            )
+            .await;
+        }
+        if snapshot.is_ok()
+            && (!pvc_hooks.is_empty() || backup_payload.scale_down_timeout().is_some())
+        {
+            snapshot = wait_untill_group_snapshot(
+                &backup_k8s_apis_struct.source_vgs_api,
+                &volume_group_snapshot_name,
+                is_volume_group_snapshot_bound_or_failed(),
+                backup_payload.snapshot_timeout(),
+            )
+            .await
+            .map(|_| ());
+        }
+        let mut post_hooks_errors = Vec::new();
+        for (pods, hooks) in pvc_hooks.iter().rev() {
+            if let Err(e) = run_post_hooks(&backup_k8s_apis_struct.pods_api, pods, hooks).await {
+                post_hooks_errors.push(format!("{:#}", e));
+            }
+        }
+        snapshot?;
+        if !post_hooks_errors.is_empty() {
+            bail!(
+                "VolumeGroupSnapshot {} was taken, but its post hooks failed: {}",
+                volume_group_snapshot_name,
+                post_hooks_errors.join("; ")
             );
+        }
+        Ok(CreatedVolumeGroupSnapshot {
+            volume_group_snapshot_name,
+            source_pvcs,
+        })
+    }
 
-            let volume_snapshot =
-                vs_operator.construct_volume_snapshot_resource(None, None, VSCRetainPolicy::Delete);
+    /// Wait for a VolumeGroupSnapshot to be ready, and label its member VolumeSnapshots
+    ///
+    /// The member VolumeSnapshots are labelled like the ones of single PVCs, so that they can
+    /// be found on restore and pruned.
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_payload` - Payload of the backup operation
+    /// * `backup_k8s_apis_struct` - Kubernetes APIs of the namespace of the PVCs
+    /// * `backup_run` - The backup run the VolumeGroupSnapshot belongs to
+    /// * `created` - The created VolumeGroupSnapshot
+    ///
+    /// # Returns
+    ///
+    /// The member VolumeSnapshot of each PVC of the group
+    async fn wait_for_volume_group_snapshot(
+        backup_payload: &BackupPayload,
+        backup_k8s_apis_struct: &BackupKubernetesApisStruct,
+        backup_run: &BackupRun,
+        created: CreatedVolumeGroupSnapshot,
+    ) -> Result<BTreeMap<String, CreatedVolumeSnapshot>> {
+        let CreatedVolumeGroupSnapshot {
+            volume_group_snapshot_name,
+            mut source_pvcs,
+        } = created;
+        let status = wait_untill_group_snapshot(
+            &backup_k8s_apis_struct.source_vgs_api,
+            &volume_group_snapshot_name,
+            is_volume_group_snapshot_ready_or_failed(),
+            backup_payload.snapshot_timeout(),
+        )
+        .await?;
 
-            let pp = PostParams::default();
-            let status: VolumeSnapshotStatus = match restore_k8s_apis_struct
+        let mut members = BTreeMap::new();
+        for (pvc, volume_snapshot_name) in group_snapshot_members(&status) {
+            let Some(source_pvc) = source_pvcs.remove(&pvc) else {
+                warn!(
+                    "PVC {} is a member of VolumeGroupSnapshot {}, but was not selected",
+                    pvc, volume_group_snapshot_name
+                );
+                continue;
+            };
+            // Label the member VolumeSnapshot like the VolumeSnapshot of a single PVC
+            backup_k8s_apis_struct
                 .source_vs_api
-                .create(&pp, &volume_snapshot)
+                .patch(
+                    &volume_snapshot_name,
+                    &PatchParams::default(),
+                    &Patch::Merge(json!({
+                        "metadata": {
+                            "labels": volume_snapshot_labels(
+                                backup_payload.vs_name_prefix(),
+                                &pvc,
+                                &backup_run.backup_id,
+                            )
+                        }
+                    })),
+                )
+                .await
+                .with_context(|| {
+                    format!("Failed to label VolumeSnapshot {}", volume_snapshot_name)
+                })?;
+            members.insert(
+                pvc,
+                CreatedVolumeSnapshot {
+                    volume_snapshot_name,
+                    source_pvc,
+                    volume_group_snapshot_name: Some(volume_group_snapshot_name.clone()),
+                },
+            );
+        }
+        Ok(members)
+    }
+
+    /// Submit a VolumeGroupSnapshot to the Kubernetes API
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_k8s_apis_struct` - Kubernetes APIs of the namespace of the PVCs
+    /// * `volume_group_snapshot` - The VolumeGroupSnapshot resource
+    /// * `namespace` - Namespace of the PVCs
+    async fn submit_volume_group_snapshot(
+        backup_k8s_apis_struct: &BackupKubernetesApisStruct,
+        volume_group_snapshot: &VolumeGroupSnapshot,
+        namespace: &str,
+    ) -> Result<()> {
+        match backup_k8s_apis_struct
+            .source_vgs_api
+            .create(&PostParams::default(), volume_group_snapshot)
+            .await
+        {
+            Ok(group_snapshot) => {
+                info!(
+                    "Created VolumeGroupSnapshot: {} on namespace: {}",
+                    group_snapshot.metadata.name.unwrap_or_default(),
+                    namespace
+                );
+                Ok(())
+            }
+            Err(e) => bail!("Failed to create VolumeGroupSnapshot: {}", e),
+        }
+    }
+
+    /// Scale down the workloads mounting the PVCs, and create a VolumeSnapshot for each PVC
+    /// once their pods are terminated
+    ///
+    /// The workloads are recorded in `scaled_down` before they are scaled down, so that the
+    /// caller can restore them whatever the outcome, even if this future is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_payload` - Payload of the backup operation
+    /// * `backup_k8s_apis` - Kubernetes APIs of each namespace
+    /// * `backup_run` - The backup run the VolumeSnapshots belong to
+    /// * `pvcs` - The namespace and name of each PVC to snapshot
+    /// * `scale_down_timeout` - Maximum time to wait for the pods to terminate
+    /// * `scaled_down` - The workloads scaled down, per namespace
+    ///
+    /// # Returns
+    ///
+    /// The snapshots created for the PVCs, or an error if the workloads could not be scaled
+    /// down
+    async fn scale_down_and_create_volume_snapshots(
+        backup_payload: &BackupPayload,
+        backup_k8s_apis: &BTreeMap<String, BackupKubernetesApisStruct>,
+        backup_run: &BackupRun,
+        pvcs: Vec<(String, String)>,
+        scale_down_timeout: Duration,
+        scaled_down: &mut BTreeMap<String, Vec<Workload>>,
+    ) -> Result<CreatedSnapshots> {
+        let mut namespace_pvcs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (namespace, pvc) in &pvcs {
+            namespace_pvcs
+                .entry(namespace.clone())
+                .or_default()
+                .push(pvc.clone());
+        }
+
+        for (namespace, pvc_names) in &namespace_pvcs {
+            let workloads_api = &backup_k8s_apis[namespace].workloads_api;
+            let workloads = get_workloads_mounting_pvcs(workloads_api, pvc_names).await?;
+            if workloads.is_empty() {
+                info!("No workload mounts the PVCs on namespace: {}", namespace);
+                continue;
+            }
+            scaled_down.insert(namespace.clone(), workloads.clone());
+            scale_down_workloads(workloads_api, &workloads)
                 .await
+                .with_context(|| format!("Failed to scale down on namespace {}", namespace))?;
+        }
+        for (namespace, pvc_names) in &namespace_pvcs {
+            wait_for_pods_to_terminate(
+                &backup_k8s_apis[namespace].pods_api,
+                pvc_names,
+                scale_down_timeout,
+            )
+            .await?;
+        }
+
+        Ok(Self::create_volume_snapshots(backup_payload, backup_k8s_apis, backup_run, pvcs).await)
+    }
+
+    /// Scale the workloads back to their original replicas
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_k8s_apis` - Kubernetes APIs of each namespace
+    /// * `scaled_down` - The workloads scaled down, per namespace
+    async fn restore_scaled_down_workloads(
+        backup_k8s_apis: &BTreeMap<String, BackupKubernetesApisStruct>,
+        scaled_down: &BTreeMap<String, Vec<Workload>>,
+    ) {
+        for (namespace, workloads) in scaled_down {
+            if let Err(e) =
+                restore_workloads(&backup_k8s_apis[namespace].workloads_api, workloads).await
             {
-                Ok(snapshot) => {
-                    info!(
-                        "{}",
-                        format!(
-                            "Created VolumeSnapshot: {} on namespace: {}",
-                            snapshot.metadata.name.clone().unwrap(),
-                            backup_payload.source_ns()
-                        )
-                    );
-                    wait_untill_snapshot_is_ready(
-                        &restore_k8s_apis_struct.source_vs_api,
-                        &restore_k8s_apis_struct.vsc_api,
-                        &ebs_client,
+                error!(
+                    "{:#}. Run `snap-kube-client recover --source-ns {}` to restore them",
+                    e, namespace
+                );
+            }
+        }
+    }
+
+    /// Create the VolumeSnapshot of a single PVC
+    ///
+    /// The VolumeSnapshot is named after the name template of the payload, so that every
+    /// backup run gets its own VolumeSnapshots, and is labelled with its source PVC, name
+    /// prefix and backup ID so that the latest one of each PVC can be found on restore.
+    ///
+    /// If hooks apply to the PVC, their pre commands run in the pods mounting it before the
+    /// VolumeSnapshot is created, and their post commands once it is bound, so that the
+    /// application is quiesced only while the snapshot is cut.
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_payload` - Payload of the backup operation
+    /// * `backup_k8s_apis_struct` - Kubernetes APIs of the namespace of the PVC
+    /// * `backup_run` - The backup run the VolumeSnapshot belongs to
+    /// * `namespace` - Namespace of the PVC
+    /// * `pvc` - Name of the PVC to snapshot
+    ///
+    /// # Returns
+    ///
+    /// Name of the created VolumeSnapshot and the source PVC
+    async fn create_volume_snapshot(
+        backup_payload: &BackupPayload,
+        backup_k8s_apis_struct: &BackupKubernetesApisStruct,
+        backup_run: &BackupRun,
+        namespace: &str,
+        pvc: &str,
+    ) -> Result<CreatedVolumeSnapshot> {
+        info!("Backing up PVC: {} on namespace: {}", pvc, namespace);
+        let volume_snapshot_name = backup_payload.vs_name_template().render(
+            backup_payload.vs_name_prefix(),
+            namespace,
+            pvc,
+            backup_run.created_at,
+        );
+
+        // Check if the PVC exists, it should exist
+        let source_pvc = check_if_pvc_exists(&backup_k8s_apis_struct.source_pvcs_api, pvc, true)
+            .await?
+            .context("PVC does not exist")?;
+
+        let vs_operator = VolumeSnapshotOperator::new(
+            volume_snapshot_name.to_string(),
+            namespace.to_string(),
+            backup_payload.volume_snapshot_class().to_string(),
+            Some(pvc.to_string()),
+            None,
+            volume_snapshot_labels(backup_payload.vs_name_prefix(), pvc, &backup_run.backup_id),
+        );
+
+        let volume_snapshot =
+            vs_operator.construct_volume_snapshot_resource(None, None, VSCRetainPolicy::Delete);
+
+        // Without hooks, the VolumeSnapshot is crash-consistent, unless the workloads
+        // mounting the PVC are scaled down until it is bound
+        let hooks = backup_payload.hooks().for_pvc(&source_pvc);
+        if hooks.is_empty() {
+            Self::submit_volume_snapshot(backup_k8s_apis_struct, &volume_snapshot, namespace)
+                .await?;
+            if backup_payload.scale_down_timeout().is_some() {
+                wait_untill_snapshot_is_bound(
+                    &backup_k8s_apis_struct.source_vs_api,
+                    &volume_snapshot_name,
+                    backup_payload.snapshot_timeout(),
+                )
+                .await?;
+            }
+            return Ok(CreatedVolumeSnapshot::new(volume_snapshot_name, source_pvc));
+        }
+
+        let pods = get_pods_mounting_pvc(&backup_k8s_apis_struct.pods_api, pvc).await?;
+        if pods.is_empty() {
+            warn!(
+                "No running pod mounts PVC {} on namespace {}, skipping its hooks",
+                pvc, namespace
+            );
+        }
+
+        // The post hooks run even if the pre hooks or the VolumeSnapshot failed, to undo the
+        // pre hooks that did run, e.g. to unfreeze a filesystem
+        let snapshot = match run_pre_hooks(&backup_k8s_apis_struct.pods_api, &pods, &hooks).await {
+            Ok(()) => {
+                match Self::submit_volume_snapshot(
+                    backup_k8s_apis_struct,
+                    &volume_snapshot,
+                    namespace,
+                )
+                .await
+                {
+                    Ok(()) => wait_untill_snapshot_is_bound(
+                        &backup_k8s_apis_struct.source_vs_api,
                         &volume_snapshot_name,
+                        backup_payload.snapshot_timeout(),
                     )
-                    .await?
-                }
-                Err(e) => {
-                    bail!("Failed to create VolumeSnapshot: {}", e);
+                    .await
+                    .map(|_| ()),
+                    Err(e) => Err(e),
                 }
-            };
+            }
+            Err(e) => Err(e),
+        };
+        let post_hooks = run_post_hooks(&backup_k8s_apis_struct.pods_api, &pods, &hooks).await;
 
-            let bound_vsc_name = status.bound_volume_snapshot_content_name.unwrap();
-            let restore_size = status.restore_size.unwrap();
-            info!(
-                "{}",
-                format!(
-                    "VolumeSnapshot is ready! VS name: {}, Bound VSC name: {}, Restore size: {}",
-                    volume_snapshot_name, bound_vsc_name, restore_size
-                )
-            );
+        match (snapshot, post_hooks) {
+            (Ok(()), Ok(())) => Ok(CreatedVolumeSnapshot::new(volume_snapshot_name, source_pvc)),
+            (Err(e), Ok(())) => Err(e),
+            (Ok(()), Err(e)) => Err(e.context(format!(
+                "VolumeSnapshot {} was taken, but its post hooks failed",
+                volume_snapshot_name
+            ))),
+            (Err(e), Err(post_hooks_error)) => Err(e.context(format!(
+                "The post hooks failed as well: {:#}",
+                post_hooks_error
+            ))),
         }
+    }
+
+    /// Submit a VolumeSnapshot to the Kubernetes API
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_k8s_apis_struct` - Kubernetes APIs of the namespace of the PVC
+    /// * `volume_snapshot` - The VolumeSnapshot resource
+    /// * `namespace` - Namespace of the PVC
+    async fn submit_volume_snapshot(
+        backup_k8s_apis_struct: &BackupKubernetesApisStruct,
+        volume_snapshot: &VolumeSnapshot,
+        namespace: &str,
+    ) -> Result<()> {
+        let pp = PostParams::default();
+        match backup_k8s_apis_struct
+            .source_vs_api
+            .create(&pp, volume_snapshot)
+            .await
+        {
+            Ok(snapshot) => {
+                info!(
+                    "{}",
+                    format!(
+                        "Created VolumeSnapshot: {} on namespace: {}",
+                        snapshot.metadata.name.clone().unwrap(),
+                        namespace
+                    )
+                );
+            }
+            Err(e) => {
+                bail!("Failed to create VolumeSnapshot: {}", e);
+            }
+        };
         Ok(())
     }
+
+    /// Wait for a VolumeSnapshot to be ready and log its details
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_payload` - Payload of the backup operation
+    /// * `backup_k8s_apis_struct` - Kubernetes APIs of the namespace of the VolumeSnapshot
+    /// * `ebs_client` - EBS client
+    /// * `backup_run` - The backup run the VolumeSnapshot belongs to
+    /// * `created` - The VolumeSnapshot and the PVC it was taken from
+    ///
+    /// # Returns
+    ///
+    /// The completed status of the PVC backup, holding its catalog entry
+    async fn wait_for_volume_snapshot(
+        backup_payload: &BackupPayload,
+        backup_k8s_apis_struct: &BackupKubernetesApisStruct,
+        ebs_client: &EbsClient,
+        backup_run: &BackupRun,
+        created: CreatedVolumeSnapshot,
+    ) -> Result<PvcBackupStatus> {
+        let CreatedVolumeSnapshot {
+            volume_snapshot_name,
+            source_pvc,
+            volume_group_snapshot_name,
+        } = created;
+        let volume_snapshot_name = volume_snapshot_name.as_str();
+        let status: VolumeSnapshotStatus = wait_untill_snapshot_is_ready(
+            &backup_k8s_apis_struct.source_vs_api,
+            &backup_k8s_apis_struct.vsc_api,
+            ebs_client,
+            volume_snapshot_name,
+            backup_payload.snapshot_timeout(),
+        )
+        .await?;
+
+        let incomplete = |field: &str| VolumeSnapshotError::Incomplete {
+            name: volume_snapshot_name.to_string(),
+            field: field.to_string(),
+        };
+        let bound_vsc_name = status
+            .bound_volume_snapshot_content_name
+            .ok_or_else(|| incomplete("boundVolumeSnapshotContentName"))?;
+        let restore_size = status
+            .restore_size
+            .ok_or_else(|| incomplete("restoreSize"))?;
+        let snapshot_handle =
+            get_snapshot_handle(backup_k8s_apis_struct.vsc_api.clone(), &bound_vsc_name).await?;
+        info!(
+            "{}",
+            format!(
+                "VolumeSnapshot is ready! VS name: {}, Bound VSC name: {}, EBS snapshot: {}, Restore size: {}",
+                volume_snapshot_name, bound_vsc_name, snapshot_handle, restore_size
+            )
+        );
+
+        // Tag the EBS snapshot, a failure leaves the snapshot usable so it only warns
+        let pvc_template = PvcTemplate::from_pvc(&source_pvc);
+        let source_ns = source_pvc.metadata.namespace.unwrap_or_default();
+        let pvc_name = source_pvc.metadata.name.unwrap_or_default();
+        let tags = backup_payload.ebs_snapshot_tags().for_snapshot(
+            &source_ns,
+            &pvc_name,
+            volume_snapshot_name,
+            &backup_run.backup_id,
+        );
+        match tag_ebs_snapshot(ebs_client, &snapshot_handle, &tags).await {
+            Ok(()) => info!("Tagged EBS snapshot: {}", snapshot_handle),
+            Err(e) => warn!("{:#}", e),
+        }
+
+        // Copy the EBS snapshot to the other regions, concurrently. A failed copy leaves the
+        // source snapshot usable, so it is still recorded
+        let mut snapshot_copies = Vec::new();
+        let mut failed_copies = BTreeMap::new();
+        let copies =
+            join_all(backup_payload.snapshot_copy_targets().iter().map(|target| {
+                Self::copy_snapshot(backup_payload, &snapshot_handle, target, &tags)
+            }))
+            .await;
+        for (target, copy) in backup_payload.snapshot_copy_targets().iter().zip(copies) {
+            match copy {
+                Ok(copy) => snapshot_copies.push(copy),
+                Err(e) => {
+                    error!("{:#}", e);
+                    failed_copies.insert(target.region.clone(), format!("{:#}", e));
+                }
+            }
+        }
+
+        let entry = Box::new(BackupCatalogEntry {
+            backup_id: backup_run.backup_id.clone(),
+            created_at: backup_run.created_at,
+            source_ns,
+            pvc_name,
+            pvc_spec: source_pvc.spec,
+            pvc_labels: pvc_template.labels,
+            pvc_annotations: pvc_template.annotations,
+            volume_snapshot_class: backup_payload.volume_snapshot_class().to_string(),
+            volume_snapshot_name: volume_snapshot_name.to_string(),
+            volume_snapshot_content_name: bound_vsc_name,
+            snapshot_handle,
+            restore_size,
+            volume_group_snapshot_name,
+            snapshot_copies,
+        });
+        Ok(if failed_copies.is_empty() {
+            PvcBackupStatus::Completed(entry)
+        } else {
+            PvcBackupStatus::CopiesFailed(entry, failed_copies)
+        })
+    }
+
+    /// Copy an EBS snapshot to another region, and wait for the copy to complete
+    ///
+    /// # Arguments
+    ///
+    /// * `backup_payload` - Payload of the backup operation
+    /// * `snapshot_handle` - ID of the EBS snapshot
+    /// * `target` - Destination region and KMS key of the copy
+    /// * `tags` - Tags of the EBS snapshot
+    ///
+    /// # Returns
+    ///
+    /// The region and ID of the completed copy
+    async fn copy_snapshot(
+        backup_payload: &BackupPayload,
+        snapshot_handle: &str,
+        target: &SnapshotCopyTarget,
+        tags: &BTreeMap<String, String>,
+    ) -> Result<EbsSnapshotCopy> {
+        let (ebs_client, snapshot_id) =
+            copy_ebs_snapshot(backup_payload.region(), snapshot_handle, target, tags).await?;
+        wait_untill_ebs_snapshot_is_completed(
+            &ebs_client,
+            &snapshot_id,
+            backup_payload.snapshot_timeout(),
+        )
+        .await?;
+        Ok(EbsSnapshotCopy {
+            region: target.region.clone(),
+            snapshot_id,
+        })
+    }
+}
+
+/// A struct for holding the identity of a backup run
+struct BackupRun {
+    backup_id: String,
+    created_at: DateTime<Utc>,
 }
 
 /// A struct for holding the Kubernetes APIs for the backup operation
@@ -117,4 +1069,7 @@ struct BackupKubernetesApisStruct {
     source_vs_api: Api<VolumeSnapshot>,
     source_pvcs_api: KubePvcApi,
     vsc_api: Api<VolumeSnapshotContent>,
+    source_vgs_api: Api<VolumeGroupSnapshot>,
+    pods_api: KubePodApi,
+    workloads_api: KubeWorkloadApi,
 }
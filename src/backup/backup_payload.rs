@@ -1,10 +1,33 @@
+use super::backup_hooks::BackupHooks;
+use crate::{
+    aws_ops::ebs::{EbsSnapshotTags, SnapshotCopyTarget},
+    catalog::backup_catalog::CatalogLocation,
+    k8s_ops::{
+        ns::namespaces::NamespaceSelection, pvc::pvc_selector::PvcSelector,
+        vgs::volume_group_snapshots::group_snapshot_selector,
+        vs::volume_snapshot_naming::VolumeSnapshotNameTemplate,
+    },
+};
+use anyhow::{bail, Result};
+use std::time::Duration;
+
 pub struct BackupPayload {
     pub region: String,
-    pub source_ns: String,
+    pub namespace_selection: NamespaceSelection,
     pub volume_snapshot_class: String,
     pub pvc_name: Option<String>,
     pub include_all_pvcs: bool,
+    pub pvc_selector: PvcSelector,
     pub vs_name_prefix: String,
+    pub vs_name_template: VolumeSnapshotNameTemplate,
+    pub max_concurrency: usize,
+    pub snapshot_timeout: Duration,
+    pub catalog: Option<CatalogLocation>,
+    pub hooks: BackupHooks,
+    pub scale_down_timeout: Option<Duration>,
+    pub volume_group_snapshot_class: Option<String>,
+    pub ebs_snapshot_tags: EbsSnapshotTags,
+    pub snapshot_copy_targets: Vec<SnapshotCopyTarget>,
 }
 
 impl BackupPayload {
@@ -13,30 +36,64 @@ impl BackupPayload {
     /// # Arguments
     ///
     /// * `region` - AWS region
-    /// * `source_ns` - Source namespace
+    /// * `namespace_selection` - Source namespaces
     /// * `volume_snapshot_class` - VolumeSnapshotClass name
     /// * `pvc_name` - PVC name
     /// * `include_all_pvcs` - Include all PVCs in the namespace
+    /// * `pvc_selector` - Selector for the PVCs in the namespace
     /// * `vs_name_prefix` - VolumeSnapshot name prefix
+    /// * `vs_name_template` - Template of the VolumeSnapshot names
+    /// * `max_concurrency` - Maximum number of VolumeSnapshots handled concurrently
+    /// * `snapshot_timeout` - Maximum time to wait for each VolumeSnapshot to be ready
+    /// * `catalog` - Where the backup catalog is stored, if any
+    /// * `hooks` - Commands run in the pods mounting the PVCs around their VolumeSnapshots
+    /// * `scale_down_timeout` - If set, the workloads mounting the PVCs are scaled down during
+    ///   the snapshots, waiting at most this long for their pods to terminate
+    /// * `volume_group_snapshot_class` - If set, the PVCs of each namespace are snapshotted
+    ///   together with a VolumeGroupSnapshot of this VolumeGroupSnapshotClass
+    /// * `ebs_snapshot_tags` - Tags applied to the EBS snapshots once they are ready
+    /// * `snapshot_copy_targets` - Regions the EBS snapshots are copied to once they are ready
     ///
     /// # Returns
     ///
     /// A new BackupPayload instance
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         region: impl Into<String>,
-        source_ns: impl Into<String>,
+        namespace_selection: NamespaceSelection,
         volume_snapshot_class: impl Into<String>,
         pvc_name: Option<impl Into<String>>,
         include_all_pvcs: bool,
+        pvc_selector: PvcSelector,
         vs_name_prefix: impl Into<String>,
+        vs_name_template: VolumeSnapshotNameTemplate,
+        max_concurrency: usize,
+        snapshot_timeout: Duration,
+        catalog: Option<CatalogLocation>,
+        hooks: BackupHooks,
+        scale_down_timeout: Option<Duration>,
+        volume_group_snapshot_class: Option<impl Into<String>>,
+        ebs_snapshot_tags: EbsSnapshotTags,
+        snapshot_copy_targets: Vec<SnapshotCopyTarget>,
     ) -> Self {
         Self {
             region: region.into(),
-            source_ns: source_ns.into(),
+            namespace_selection,
             volume_snapshot_class: volume_snapshot_class.into(),
             pvc_name: pvc_name.map(|pvc_name| pvc_name.into()),
             include_all_pvcs,
+            pvc_selector,
             vs_name_prefix: vs_name_prefix.into(),
+            vs_name_template,
+            max_concurrency,
+            snapshot_timeout,
+            catalog,
+            hooks,
+            scale_down_timeout,
+            volume_group_snapshot_class: volume_group_snapshot_class
+                .map(|volume_group_snapshot_class| volume_group_snapshot_class.into()),
+            ebs_snapshot_tags,
+            snapshot_copy_targets,
         }
     }
 
@@ -44,8 +101,8 @@ impl BackupPayload {
         &self.region
     }
 
-    pub fn source_ns(&self) -> &str {
-        &self.source_ns
+    pub fn namespace_selection(&self) -> &NamespaceSelection {
+        &self.namespace_selection
     }
 
     pub fn volume_snapshot_class(&self) -> &str {
@@ -60,7 +117,70 @@ impl BackupPayload {
         self.include_all_pvcs
     }
 
+    pub fn pvc_selector(&self) -> &PvcSelector {
+        &self.pvc_selector
+    }
+
     pub fn vs_name_prefix(&self) -> &str {
         &self.vs_name_prefix
     }
+
+    pub fn vs_name_template(&self) -> &VolumeSnapshotNameTemplate {
+        &self.vs_name_template
+    }
+
+    /// Maximum number of VolumeSnapshots handled concurrently, at least 1
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency.max(1)
+    }
+
+    pub fn snapshot_timeout(&self) -> Duration {
+        self.snapshot_timeout
+    }
+
+    pub fn catalog(&self) -> Option<&CatalogLocation> {
+        self.catalog.as_ref()
+    }
+
+    pub fn hooks(&self) -> &BackupHooks {
+        &self.hooks
+    }
+
+    pub fn scale_down_timeout(&self) -> Option<Duration> {
+        self.scale_down_timeout
+    }
+
+    pub fn volume_group_snapshot_class(&self) -> Option<&str> {
+        self.volume_group_snapshot_class.as_deref()
+    }
+
+    pub fn ebs_snapshot_tags(&self) -> &EbsSnapshotTags {
+        &self.ebs_snapshot_tags
+    }
+
+    pub fn snapshot_copy_targets(&self) -> &[SnapshotCopyTarget] {
+        &self.snapshot_copy_targets
+    }
+
+    /// Check that the PVCs are selected the way a VolumeGroupSnapshot selects them, i.e. by
+    /// a label selector only, if a VolumeGroupSnapshotClass is set
+    pub fn validate_group_snapshot(&self) -> Result<()> {
+        if self.volume_group_snapshot_class.is_none() {
+            return Ok(());
+        }
+        match &self.pvc_selector.label_selector {
+            Some(label_selector) => {
+                group_snapshot_selector(label_selector)?;
+            }
+            None => bail!("A group snapshot requires a PVC label selector"),
+        }
+        if self.pvc_name.is_some()
+            || self.pvc_selector.field_selector.is_some()
+            || self.pvc_selector.include_regex.is_some()
+            || self.pvc_selector.exclude_regex.is_some()
+        {
+            bail!("A group snapshot selects the PVCs by a label selector only");
+        }
+        Ok(())
+    }
 }
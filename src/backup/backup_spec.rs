@@ -0,0 +1,190 @@
+use super::{
+    backup_hooks::{BackupHook, BackupHooks},
+    backup_payload::BackupPayload,
+};
+use crate::{
+    aws_ops::ebs::{EbsSnapshotTags, SnapshotCopyTarget},
+    catalog::backup_catalog::CatalogLocation,
+    k8s_ops::{
+        ns::namespaces::NamespaceSelection,
+        pvc::pvc_selector::PvcSelector,
+        vs::volume_snapshot_naming::{VolumeSnapshotNameTemplate, DEFAULT_VS_NAME_TEMPLATE},
+    },
+};
+use anyhow::{bail, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+
+/// A declarative description of a backup, shared by the daemon schedules and the
+/// SnapKubeBackup custom resource
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSpec {
+    /// Source namespaces
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub namespaces: Vec<String>,
+    /// Label selector for the source namespaces
+    pub namespace_selector: Option<String>,
+    /// Backup all the namespaces of the cluster
+    #[serde(default)]
+    pub all_namespaces: bool,
+    /// Namespaces excluded when backing up all the namespaces
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_namespaces: Vec<String>,
+    /// VolumeSnapshotClass name
+    pub volume_snapshot_class: String,
+    /// PVC name
+    pub pvc_name: Option<String>,
+    /// Include all PVCs in the namespace
+    #[serde(default)]
+    pub include_all_pvcs: bool,
+    /// Label selector for the PVCs
+    pub selector: Option<String>,
+    /// Field selector for the PVCs
+    pub field_selector: Option<String>,
+    /// Only include the PVCs whose name matches this regex
+    pub include_pvc_regex: Option<String>,
+    /// Exclude the PVCs whose name matches this regex
+    pub exclude_pvc_regex: Option<String>,
+    /// VolumeSnapshot name prefix
+    pub volume_snapshot_name_prefix: String,
+    /// VolumeSnapshot name template
+    pub volume_snapshot_name_template: Option<String>,
+    /// Maximum number of PVCs snapshotted concurrently
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    /// Maximum time in seconds to wait for each VolumeSnapshot to be ready
+    #[serde(default = "default_snapshot_timeout_secs")]
+    pub snapshot_timeout_secs: u64,
+    /// Commands run in the pods mounting the PVCs around their VolumeSnapshots
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<BackupHook>,
+    /// Scale down the workloads mounting the PVCs until their VolumeSnapshots are bound
+    #[serde(default)]
+    pub scale_down: bool,
+    /// Maximum time in seconds to wait for the pods of the scaled down workloads to terminate
+    #[serde(default = "default_scale_down_timeout_secs")]
+    pub scale_down_timeout_secs: u64,
+    /// Snapshot the PVCs of each namespace together with a VolumeGroupSnapshot of this class
+    pub volume_group_snapshot_class: Option<String>,
+    /// Name of the cluster, tagged on the EBS snapshots
+    pub cluster_name: Option<String>,
+    /// Extra tags applied to the EBS snapshots
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub ebs_tags: BTreeMap<String, String>,
+    /// Description of the EBS snapshots, tagged as `Description`
+    pub ebs_snapshot_description: Option<String>,
+    /// Regions the EBS snapshots are copied to once they are ready
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snapshot_copies: Vec<SnapshotCopyTarget>,
+}
+
+fn default_max_concurrency() -> usize {
+    4
+}
+
+fn default_snapshot_timeout_secs() -> u64 {
+    3600
+}
+
+fn default_scale_down_timeout_secs() -> u64 {
+    300
+}
+
+impl BackupSpec {
+    /// Check that the spec can be turned into backup runs
+    pub fn validate(&self) -> Result<()> {
+        self.namespace_selection()?;
+        let backup_payload = self.to_backup_payload("", Vec::new(), None)?;
+        if backup_payload.pvc_name().is_none()
+            && !backup_payload.include_all_pvcs()
+            && backup_payload.pvc_selector().is_empty()
+        {
+            bail!("One of pvcName, includeAllPvcs or a PVC selector is required");
+        }
+        backup_payload.validate_group_snapshot()?;
+        Ok(())
+    }
+
+    /// Whether no source namespace is set
+    pub fn has_no_namespaces(&self) -> bool {
+        self.namespaces.is_empty() && self.namespace_selector.is_none() && !self.all_namespaces
+    }
+
+    /// The namespaces backed up by the spec
+    pub fn namespace_selection(&self) -> Result<NamespaceSelection> {
+        if self.all_namespaces {
+            if !self.namespaces.is_empty() || self.namespace_selector.is_some() {
+                bail!("allNamespaces cannot be combined with namespaces or namespaceSelector");
+            }
+            return Ok(NamespaceSelection::All {
+                exclude: self.exclude_namespaces.clone(),
+            });
+        }
+        if !self.exclude_namespaces.is_empty() {
+            bail!("excludeNamespaces requires allNamespaces");
+        }
+        match (&self.namespace_selector, self.namespaces.is_empty()) {
+            (Some(_), false) => bail!("namespaces cannot be combined with namespaceSelector"),
+            (Some(namespace_selector), true) => Ok(NamespaceSelection::LabelSelector(
+                namespace_selector.clone(),
+            )),
+            (None, false) => Ok(NamespaceSelection::List(self.namespaces.clone())),
+            (None, true) => {
+                bail!("One of namespaces, namespaceSelector or allNamespaces is required")
+            }
+        }
+    }
+
+    /// Build the payload of a backup run of the spec
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - AWS region
+    /// * `namespaces` - The namespaces to backup on this run
+    /// * `catalog` - Where the backup catalog is stored, if any
+    ///
+    /// # Returns
+    ///
+    /// The payload of the backup run
+    pub fn to_backup_payload(
+        &self,
+        region: &str,
+        namespaces: Vec<String>,
+        catalog: Option<CatalogLocation>,
+    ) -> Result<BackupPayload> {
+        Ok(BackupPayload::new(
+            region,
+            NamespaceSelection::List(namespaces),
+            self.volume_snapshot_class.clone(),
+            self.pvc_name.clone(),
+            self.include_all_pvcs,
+            PvcSelector::new(
+                self.selector.clone(),
+                self.field_selector.clone(),
+                self.include_pvc_regex.as_deref(),
+                self.exclude_pvc_regex.as_deref(),
+            )?,
+            self.volume_snapshot_name_prefix.clone(),
+            VolumeSnapshotNameTemplate::new(
+                self.volume_snapshot_name_template
+                    .as_deref()
+                    .unwrap_or(DEFAULT_VS_NAME_TEMPLATE),
+            )?,
+            self.max_concurrency,
+            Duration::from_secs(self.snapshot_timeout_secs),
+            catalog,
+            BackupHooks::new(self.hooks.clone())?,
+            self.scale_down
+                .then(|| Duration::from_secs(self.scale_down_timeout_secs)),
+            self.volume_group_snapshot_class.clone(),
+            EbsSnapshotTags::new(
+                self.cluster_name.clone(),
+                self.ebs_tags.clone(),
+                self.ebs_snapshot_description.clone(),
+            )?,
+            self.snapshot_copies.clone(),
+        ))
+    }
+}
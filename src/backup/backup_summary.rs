@@ -0,0 +1,161 @@
+use crate::catalog::catalog_entry::BackupCatalogEntry;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+/// Represents the outcome of backing up a single PVC
+#[derive(Clone, Debug, PartialEq)]
+pub enum PvcBackupStatus {
+    Completed(Box<BackupCatalogEntry>),
+    /// The EBS snapshot was taken and recorded in the catalog, but copying it to some of the
+    /// regions failed, with the error of each of these regions
+    CopiesFailed(Box<BackupCatalogEntry>, BTreeMap<String, String>),
+    Failed(String),
+}
+
+impl PvcBackupStatus {
+    /// The catalog entry of the PVC, if its EBS snapshot was taken
+    pub fn catalog_entry(&self) -> Option<&BackupCatalogEntry> {
+        match self {
+            PvcBackupStatus::Completed(entry) | PvcBackupStatus::CopiesFailed(entry, _) => {
+                Some(entry)
+            }
+            PvcBackupStatus::Failed(_) => None,
+        }
+    }
+}
+
+impl Display for PvcBackupStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let entry = match self {
+            PvcBackupStatus::Completed(entry) => {
+                write!(f, "Completed")?;
+                entry
+            }
+            PvcBackupStatus::CopiesFailed(entry, _) => {
+                write!(f, "Copies failed")?;
+                entry
+            }
+            PvcBackupStatus::Failed(e) => return write!(f, "Failed: {}", e),
+        };
+        write!(
+            f,
+            " (VS: {}, VSC: {}, EBS snapshot: {}, Restore size: {}",
+            entry.volume_snapshot_name,
+            entry.volume_snapshot_content_name,
+            entry.snapshot_handle,
+            entry.restore_size
+        )?;
+        for copy in &entry.snapshot_copies {
+            write!(f, ", Copy in {}: {}", copy.region, copy.snapshot_id)?;
+        }
+        if let PvcBackupStatus::CopiesFailed(_, failed_copies) = self {
+            for (region, e) in failed_copies {
+                write!(f, ", Copy in {} failed: {}", region, e)?;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+/// The result of backing up a single PVC
+#[derive(Clone, Debug, PartialEq)]
+pub struct PvcBackupResult {
+    pub namespace: String,
+    pub pvc_name: String,
+    pub status: PvcBackupStatus,
+}
+
+impl PvcBackupResult {
+    pub fn new(
+        namespace: impl Into<String>,
+        pvc_name: impl Into<String>,
+        status: PvcBackupStatus,
+    ) -> Self {
+        Self {
+            namespace: namespace.into(),
+            pvc_name: pvc_name.into(),
+            status,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self.status, PvcBackupStatus::Completed(_))
+    }
+}
+
+/// A per-PVC summary of a backup operation, grouped per namespace
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BackupSummary {
+    pub backup_id: String,
+    pub namespaces: BTreeMap<String, Vec<PvcBackupResult>>,
+    /// The error of saving the PVCs backed up to the catalog, if it failed
+    pub catalog_error: Option<String>,
+}
+
+impl BackupSummary {
+    /// Creates a new BackupSummary, grouping the results per namespace and sorting them by PVC name
+    pub fn new(backup_id: impl Into<String>, results: Vec<PvcBackupResult>) -> Self {
+        let mut namespaces: BTreeMap<String, Vec<PvcBackupResult>> = BTreeMap::new();
+        for result in results {
+            namespaces
+                .entry(result.namespace.clone())
+                .or_default()
+                .push(result);
+        }
+        for results in namespaces.values_mut() {
+            results.sort_by(|a, b| a.pvc_name.cmp(&b.pvc_name));
+        }
+        Self {
+            backup_id: backup_id.into(),
+            namespaces,
+            catalog_error: None,
+        }
+    }
+
+    pub fn results(&self) -> impl Iterator<Item = &PvcBackupResult> {
+        self.namespaces.values().flatten()
+    }
+
+    pub fn succeeded(&self) -> impl Iterator<Item = &PvcBackupResult> {
+        self.results().filter(|result| result.is_success())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &PvcBackupResult> {
+        self.results().filter(|result| !result.is_success())
+    }
+
+    /// The catalog entries of the PVCs whose EBS snapshot was taken, even if some of its
+    /// copies failed
+    pub fn catalog_entries(&self) -> Vec<BackupCatalogEntry> {
+        self.results()
+            .filter_map(|result| result.status.catalog_entry().cloned())
+            .collect()
+    }
+
+    /// Whether every PVC was backed up successfully, and saved to the catalog
+    pub fn is_success(&self) -> bool {
+        self.failed().next().is_none() && self.catalog_error.is_none()
+    }
+}
+
+impl Display for BackupSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Backup summary of {}: {} succeeded, {} failed",
+            self.backup_id,
+            self.succeeded().count(),
+            self.failed().count()
+        )?;
+        if let Some(catalog_error) = &self.catalog_error {
+            writeln!(f, "  Failed to save to the catalog: {}", catalog_error)?;
+        }
+        for (namespace, results) in &self.namespaces {
+            writeln!(f, "  Namespace: {}", namespace)?;
+            for result in results {
+                writeln!(f, "    - {}: {}", result.pvc_name, result.status)?;
+            }
+        }
+        Ok(())
+    }
+}
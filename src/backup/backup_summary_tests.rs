@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backup::backup_summary::{BackupSummary, PvcBackupResult, PvcBackupStatus},
+        catalog::catalog_entry::BackupCatalogEntry,
+    };
+    use chrono::Utc;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    fn completed(namespace: &str, pvc_name: &str) -> PvcBackupResult {
+        PvcBackupResult::new(
+            namespace,
+            pvc_name,
+            PvcBackupStatus::Completed(Box::new(BackupCatalogEntry {
+                backup_id: "backup-1".to_string(),
+                created_at: Utc::now(),
+                source_ns: namespace.to_string(),
+                pvc_name: pvc_name.to_string(),
+                pvc_spec: None,
+                volume_snapshot_class: "test-vsclass".to_string(),
+                volume_snapshot_name: format!("vs-{}", pvc_name),
+                volume_snapshot_content_name: format!("snapcontent-{}", pvc_name),
+                snapshot_handle: "snap-0123456789abcdef0".to_string(),
+                restore_size: "1Gi".to_string(),
+                volume_group_snapshot_name: None,
+                pvc_labels: BTreeMap::new(),
+                pvc_annotations: BTreeMap::new(),
+                snapshot_copies: Vec::new(),
+            })),
+        )
+    }
+
+    #[test]
+    fn test_backup_summary_groups_per_namespace() {
+        let summary = BackupSummary::new(
+            "backup-1",
+            vec![
+                PvcBackupResult::new(
+                    "tenant-b",
+                    "data",
+                    PvcBackupStatus::Failed("boom".to_string()),
+                ),
+                completed("tenant-a", "wal"),
+                completed("tenant-a", "data"),
+            ],
+        );
+
+        assert_eq!(
+            summary.namespaces.keys().collect::<Vec<_>>(),
+            vec!["tenant-a", "tenant-b"]
+        );
+        assert_eq!(summary.namespaces["tenant-a"][0].pvc_name, "data");
+        assert_eq!(summary.succeeded().count(), 2);
+        assert_eq!(summary.failed().count(), 1);
+        assert_eq!(summary.catalog_entries().len(), 2);
+        assert!(!summary.is_success());
+    }
+
+    #[test]
+    fn test_backup_summary_copies_failed() {
+        let PvcBackupStatus::Completed(entry) = completed("tenant-a", "data").status else {
+            unreachable!()
+        };
+        let summary = BackupSummary::new(
+            "backup-1",
+            vec![PvcBackupResult::new(
+                "tenant-a",
+                "data",
+                PvcBackupStatus::CopiesFailed(
+                    entry,
+                    BTreeMap::from([("us-east-1".to_string(), "quota exceeded".to_string())]),
+                ),
+            )],
+        );
+
+        // The EBS snapshot is recorded, but the PVC is reported as failed
+        assert_eq!(summary.catalog_entries().len(), 1);
+        assert_eq!(summary.failed().count(), 1);
+        assert!(summary
+            .to_string()
+            .contains("Copy in us-east-1 failed: quota exceeded"));
+    }
+
+    #[test]
+    fn test_backup_summary_catalog_error() {
+        let mut summary = BackupSummary::new("backup-1", vec![completed("tenant-a", "data")]);
+        assert!(summary.is_success());
+
+        summary.catalog_error = Some("ConfigMap is too large".to_string());
+
+        assert_eq!(summary.succeeded().count(), 1);
+        assert!(!summary.is_success());
+        assert!(summary
+            .to_string()
+            .contains("Failed to save to the catalog: ConfigMap is too large"));
+    }
+}
@@ -1,4 +1,15 @@
 #[cfg(feature = "backup")]
+pub mod backup_hooks;
+#[cfg(feature = "backup")]
 pub mod backup_operator;
 #[cfg(feature = "backup")]
 pub mod backup_payload;
+#[cfg(feature = "backup")]
+pub mod backup_spec;
+#[cfg(feature = "backup")]
+pub mod backup_summary;
+
+#[cfg(all(test, feature = "backup"))]
+mod backup_hooks_tests;
+#[cfg(all(test, feature = "backup"))]
+mod backup_summary_tests;
@@ -0,0 +1,55 @@
+use super::{
+    catalog_entry::BackupCatalogEntry, config_map_store::ConfigMapCatalogStore,
+    local_file_store::LocalFileCatalogStore,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use kube::{Api, Client};
+use std::path::PathBuf;
+
+#[cfg(test)]
+use mockall::automock;
+
+/// A store for the backup catalog
+///
+/// Each backup run is stored under its backup ID, with one entry per PVC.
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait BackupCatalogStore: Send + Sync {
+    async fn save(&self, backup_id: &str, entries: &[BackupCatalogEntry]) -> Result<()>;
+    async fn get(&self, backup_id: &str) -> Result<Vec<BackupCatalogEntry>>;
+    async fn list_backup_ids(&self) -> Result<Vec<String>>;
+    async fn delete(&self, backup_id: &str) -> Result<()>;
+}
+
+/// Represents where the backup catalog is stored
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CatalogLocation {
+    /// A ConfigMap in the cluster
+    ConfigMap { namespace: String, name: String },
+    /// A local JSON file
+    LocalFile(PathBuf),
+}
+
+/// Open the backup catalog store of a location
+///
+/// # Arguments
+///
+/// * `k8s_client` - Kubernetes client, used by the in-cluster stores
+/// * `catalog_location` - Where the backup catalog is stored
+///
+/// # Returns
+///
+/// The backup catalog store
+pub fn open_catalog_store(
+    k8s_client: Client,
+    catalog_location: &CatalogLocation,
+) -> Box<dyn BackupCatalogStore> {
+    match catalog_location {
+        CatalogLocation::ConfigMap { namespace, name } => Box::new(ConfigMapCatalogStore {
+            api: Api::namespaced(k8s_client, namespace),
+            name: name.clone(),
+        }),
+        CatalogLocation::LocalFile(path) => Box::new(LocalFileCatalogStore { path: path.clone() }),
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use k8s_openapi::api::core::v1::PersistentVolumeClaimSpec;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A record of the backup of a single PVC
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupCatalogEntry {
+    /// ID of the backup run the PVC was backed up in
+    pub backup_id: String,
+    /// Time the backup run started
+    pub created_at: DateTime<Utc>,
+    /// Namespace of the source PVC
+    pub source_ns: String,
+    /// Name of the source PVC
+    pub pvc_name: String,
+    /// Spec of the source PVC at the time of the backup
+    #[serde(default)]
+    pub pvc_spec: Option<PersistentVolumeClaimSpec>,
+    /// Labels of the source PVC, without the ones of the Kubernetes system
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pvc_labels: BTreeMap<String, String>,
+    /// Annotations of the source PVC, without the ones of the Kubernetes system
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pvc_annotations: BTreeMap<String, String>,
+    /// VolumeSnapshotClass name
+    pub volume_snapshot_class: String,
+    /// Name of the VolumeSnapshot in the source namespace
+    pub volume_snapshot_name: String,
+    /// Name of the VolumeSnapshotContent bound to the VolumeSnapshot
+    pub volume_snapshot_content_name: String,
+    /// ID of the EBS snapshot
+    pub snapshot_handle: String,
+    /// Size of the restored volume
+    pub restore_size: String,
+    /// Name of the VolumeGroupSnapshot the VolumeSnapshot is a member of, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_group_snapshot_name: Option<String>,
+    /// Copies of the EBS snapshot in other regions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snapshot_copies: Vec<EbsSnapshotCopy>,
+}
+
+/// A copy of an EBS snapshot in another region
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EbsSnapshotCopy {
+    /// Region of the copy
+    pub region: String,
+    /// ID of the copy
+    pub snapshot_id: String,
+}
+
+/// Generate the ID of a backup run
+///
+/// The ID only contains characters that are valid in ConfigMap keys and label values.
+///
+/// # Arguments
+///
+/// * `created_at` - Time the backup run started
+///
+/// # Returns
+///
+/// Backup ID, e.g. `20241018-120000-00ab`
+pub fn generate_backup_id(created_at: DateTime<Utc>) -> String {
+    format!(
+        "{}-{:04x}",
+        created_at.format("%Y%m%d-%H%M%S"),
+        created_at.timestamp_subsec_micros() & 0xffff
+    )
+}
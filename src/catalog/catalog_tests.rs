@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        catalog::{
+            backup_catalog::BackupCatalogStore,
+            catalog_entry::{generate_backup_id, BackupCatalogEntry},
+            config_map_store::ConfigMapCatalogStore,
+            local_file_store::LocalFileCatalogStore,
+        },
+        k8s_ops::fake_api_server::FakeApiServer,
+    };
+    use chrono::{TimeZone, Utc};
+    use k8s_openapi::api::core::v1::PersistentVolumeClaimSpec;
+    use kube::Api;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    const CONFIG_MAPS_PATH: &str = "/api/v1/namespaces/snap-kube/configmaps";
+
+    fn catalog_entry(backup_id: &str, pvc_name: &str) -> BackupCatalogEntry {
+        BackupCatalogEntry {
+            backup_id: backup_id.to_string(),
+            created_at: Utc.with_ymd_and_hms(2024, 10, 18, 12, 0, 0).unwrap(),
+            source_ns: "test-ns".to_string(),
+            pvc_name: pvc_name.to_string(),
+            pvc_spec: Some(PersistentVolumeClaimSpec {
+                access_modes: Some(vec!["ReadWriteOnce".to_string()]),
+                storage_class_name: Some("gp3".to_string()),
+                ..Default::default()
+            }),
+            volume_snapshot_class: "test-vsclass".to_string(),
+            volume_snapshot_name: format!("test-vs-{}", pvc_name),
+            volume_snapshot_content_name: "snapcontent-1234".to_string(),
+            snapshot_handle: "snap-0123456789abcdef0".to_string(),
+            restore_size: "1Gi".to_string(),
+            volume_group_snapshot_name: None,
+            pvc_labels: BTreeMap::new(),
+            pvc_annotations: BTreeMap::new(),
+            snapshot_copies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_generate_backup_id() {
+        let created_at = Utc
+            .with_ymd_and_hms(2024, 10, 18, 12, 0, 0)
+            .unwrap()
+            .checked_add_signed(chrono::Duration::microseconds(171))
+            .unwrap();
+
+        assert_eq!(generate_backup_id(created_at), "20241018-120000-00ab");
+    }
+
+    #[test]
+    fn test_catalog_entry_serialization() {
+        let entry = catalog_entry("20241018-120000-00ab", "test-pvc");
+
+        let value = serde_json::to_value(&entry).unwrap();
+        assert_eq!(value["backupId"], "20241018-120000-00ab");
+        assert_eq!(value["createdAt"], "2024-10-18T12:00:00Z");
+        assert_eq!(value["snapshotHandle"], "snap-0123456789abcdef0");
+        assert_eq!(
+            serde_json::from_value::<BackupCatalogEntry>(value).unwrap(),
+            entry
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_file_catalog_store() {
+        let path = std::env::temp_dir().join(format!(
+            "snap-kube-catalog-test-{}.json",
+            std::process::id()
+        ));
+        let store = LocalFileCatalogStore { path: path.clone() };
+
+        assert!(store.list_backup_ids().await.unwrap().is_empty());
+
+        let first = vec![catalog_entry("backup-1", "pvc-a")];
+        let second = vec![
+            catalog_entry("backup-2", "pvc-a"),
+            catalog_entry("backup-2", "pvc-b"),
+        ];
+        store.save("backup-1", &first).await.unwrap();
+        store.save("backup-2", &second).await.unwrap();
+
+        assert_eq!(
+            store.list_backup_ids().await.unwrap(),
+            vec!["backup-1", "backup-2"]
+        );
+        assert_eq!(store.get("backup-2").await.unwrap(), second);
+        assert!(store.get("backup-3").await.is_err());
+
+        store.delete("backup-1").await.unwrap();
+        assert_eq!(store.list_backup_ids().await.unwrap(), vec!["backup-2"]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_local_file_catalog_store_concurrent_saves() {
+        let path = std::env::temp_dir().join(format!(
+            "snap-kube-catalog-concurrent-test-{}.json",
+            std::process::id()
+        ));
+
+        // Each save opens its own store, like the runs of the daemon schedules
+        let saves = (0..8).map(|i| {
+            let store = LocalFileCatalogStore { path: path.clone() };
+            async move {
+                let backup_id = format!("backup-{}", i);
+                store
+                    .save(&backup_id, &[catalog_entry(&backup_id, "pvc-a")])
+                    .await
+            }
+        });
+        for saved in futures::future::join_all(saves).await {
+            saved.unwrap();
+        }
+
+        let store = LocalFileCatalogStore { path: path.clone() };
+        assert_eq!(store.list_backup_ids().await.unwrap().len(), 8);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_config_map_catalog_store() {
+        let server = FakeApiServer::new();
+        let store = ConfigMapCatalogStore {
+            api: Api::default_namespaced(server.client("snap-kube")),
+            name: "backup-catalog".to_string(),
+        };
+
+        assert!(store.list_backup_ids().await.unwrap().is_empty());
+
+        let first = vec![catalog_entry("backup-1", "pvc-a")];
+        let second = vec![
+            catalog_entry("backup-2", "pvc-a"),
+            catalog_entry("backup-2", "pvc-b"),
+        ];
+        store.save("backup-1", &first).await.unwrap();
+        store.save("backup-2", &first).await.unwrap();
+        // Saving a backup run again replaces its entries
+        store.save("backup-2", &second).await.unwrap();
+
+        let config_map = server
+            .get(&format!("{}/backup-catalog-backup-2", CONFIG_MAPS_PATH))
+            .unwrap();
+        assert_eq!(
+            config_map["metadata"]["labels"]["snap-kube/catalog"],
+            "backup-catalog"
+        );
+        assert_eq!(
+            config_map["metadata"]["labels"]["snap-kube/backup-id"],
+            "backup-2"
+        );
+        assert_eq!(
+            store.list_backup_ids().await.unwrap(),
+            vec!["backup-1", "backup-2"]
+        );
+        assert_eq!(store.get("backup-2").await.unwrap(), second);
+        assert!(store.get("backup-3").await.is_err());
+
+        store.delete("backup-1").await.unwrap();
+        assert!(server
+            .get(&format!("{}/backup-catalog-backup-1", CONFIG_MAPS_PATH))
+            .is_none());
+        assert_eq!(store.list_backup_ids().await.unwrap(), vec!["backup-2"]);
+    }
+}
@@ -0,0 +1,145 @@
+use super::{backup_catalog::BackupCatalogStore, catalog_entry::BackupCatalogEntry};
+use crate::k8s_ops::vs::volume_snapshot_naming::{BACKUP_ID_LABEL, MANAGED_BY_LABEL};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube::{
+    api::{DeleteParams, ListParams, ObjectMeta, PostParams},
+    Api,
+};
+use std::collections::BTreeMap;
+use tracing::{info, warn};
+
+/// Label of the ConfigMaps of a catalog, set to the name of the catalog
+pub const CATALOG_LABEL: &str = "snap-kube/catalog";
+
+/// Key of the ConfigMap data holding the entries of a backup run
+const ENTRIES_KEY: &str = "entries.json";
+
+/// Maximum number of attempts to save a backup run, when racing with another writer
+const MAX_SAVE_ATTEMPTS: usize = 5;
+
+/// A backup catalog stored in ConfigMaps
+///
+/// Each backup run is stored in its own `{name}-{backup_id}` ConfigMap under the
+/// `entries.json` key, as a JSON array of entries, so that the catalog is not bounded by
+/// the size limit of a single ConfigMap. The ConfigMaps are found by their catalog and
+/// backup ID labels.
+pub struct ConfigMapCatalogStore {
+    pub api: Api<ConfigMap>,
+    pub name: String,
+}
+
+impl ConfigMapCatalogStore {
+    fn config_map_name(&self, backup_id: &str) -> String {
+        format!("{}-{}", self.name, backup_id)
+    }
+
+    fn catalog_selector(&self) -> String {
+        format!("{}={}", CATALOG_LABEL, self.name)
+    }
+
+    /// Find the ConfigMap of a backup run by its labels
+    async fn find(&self, backup_id: &str) -> Result<Option<ConfigMap>> {
+        let list_params = ListParams::default().labels(&format!(
+            "{},{}={}",
+            self.catalog_selector(),
+            BACKUP_ID_LABEL,
+            backup_id
+        ));
+        Ok(self.api.list(&list_params).await?.items.into_iter().next())
+    }
+}
+
+#[async_trait]
+impl BackupCatalogStore for ConfigMapCatalogStore {
+    async fn save(&self, backup_id: &str, entries: &[BackupCatalogEntry]) -> Result<()> {
+        let name = self.config_map_name(backup_id);
+        let config_map = ConfigMap {
+            metadata: ObjectMeta {
+                name: Some(name.clone()),
+                labels: Some(BTreeMap::from([
+                    (MANAGED_BY_LABEL.to_string(), "snap-kube".to_string()),
+                    (CATALOG_LABEL.to_string(), self.name.clone()),
+                    (BACKUP_ID_LABEL.to_string(), backup_id.to_string()),
+                ])),
+                ..Default::default()
+            },
+            data: Some(BTreeMap::from([(
+                ENTRIES_KEY.to_string(),
+                serde_json::to_string(entries)?,
+            )])),
+            ..Default::default()
+        };
+
+        // Another writer may create or update the ConfigMap in between, in which case the
+        // create fails with AlreadyExists, or the replace with Conflict, and it is retried
+        let mut attempt = 1;
+        loop {
+            let saved = match self.api.get_opt(&name).await? {
+                Some(existing) => {
+                    let mut config_map = config_map.clone();
+                    config_map.metadata.resource_version = existing.metadata.resource_version;
+                    self.api
+                        .replace(&name, &PostParams::default(), &config_map)
+                        .await
+                }
+                None => self.api.create(&PostParams::default(), &config_map).await,
+            };
+            match saved {
+                Ok(_) => break,
+                Err(kube::Error::Api(e)) if e.code == 409 && attempt < MAX_SAVE_ATTEMPTS => {
+                    warn!(
+                        "Catalog ConfigMap {} was modified concurrently, retrying: {}",
+                        name, e.message
+                    );
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to save catalog ConfigMap {}", name))
+                }
+            }
+        }
+        info!(
+            "Saved backup {} to the catalog ConfigMap: {}",
+            backup_id, name
+        );
+        Ok(())
+    }
+
+    async fn get(&self, backup_id: &str) -> Result<Vec<BackupCatalogEntry>> {
+        let value = self
+            .find(backup_id)
+            .await?
+            .and_then(|config_map| config_map.data.unwrap_or_default().remove(ENTRIES_KEY))
+            .with_context(|| format!("Backup {} not found in the catalog", backup_id))?;
+        Ok(serde_json::from_str(&value)?)
+    }
+
+    async fn list_backup_ids(&self) -> Result<Vec<String>> {
+        let list_params = ListParams::default().labels(&self.catalog_selector());
+        let mut backup_ids: Vec<String> = self
+            .api
+            .list(&list_params)
+            .await?
+            .items
+            .into_iter()
+            .filter_map(|config_map| config_map.metadata.labels?.remove(BACKUP_ID_LABEL))
+            .collect();
+        backup_ids.sort();
+        Ok(backup_ids)
+    }
+
+    async fn delete(&self, backup_id: &str) -> Result<()> {
+        if let Some(config_map) = self.find(backup_id).await? {
+            let name = config_map.metadata.name.unwrap_or_default();
+            self.api.delete(&name, &DeleteParams::default()).await?;
+            info!(
+                "Deleted backup {} from the catalog ConfigMap: {}",
+                backup_id, name
+            );
+        }
+        Ok(())
+    }
+}
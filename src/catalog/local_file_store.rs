@@ -0,0 +1,85 @@
+use super::{backup_catalog::BackupCatalogStore, catalog_entry::BackupCatalogEntry};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::{collections::BTreeMap, ffi::OsString, path::PathBuf};
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Serializes the updates of the catalog files of this process, e.g. of the schedules of
+/// the daemon backing up concurrently
+static WRITE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// A backup catalog stored in a local JSON file
+///
+/// The file holds a JSON object that maps each backup ID to its entries. It is updated by
+/// writing a temporary file next to it, and renaming it over the catalog, so that a crash
+/// never leaves a truncated catalog behind.
+pub struct LocalFileCatalogStore {
+    pub path: PathBuf,
+}
+
+impl LocalFileCatalogStore {
+    async fn read(&self) -> Result<BTreeMap<String, Vec<BackupCatalogEntry>>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(content) => serde_json::from_slice(&content)
+                .with_context(|| format!("Invalid catalog file: {}", self.path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn write(&self, catalog: &BTreeMap<String, Vec<BackupCatalogEntry>>) -> Result<()> {
+        let mut temp_file_name = self
+            .path
+            .file_name()
+            .map(OsString::from)
+            .unwrap_or_default();
+        temp_file_name.push(".tmp");
+        let temp_path = self.path.with_file_name(temp_file_name);
+        tokio::fs::write(&temp_path, serde_json::to_vec_pretty(catalog)?).await?;
+        tokio::fs::rename(&temp_path, &self.path)
+            .await
+            .with_context(|| format!("Failed to write the catalog file: {}", self.path.display()))
+    }
+}
+
+#[async_trait]
+impl BackupCatalogStore for LocalFileCatalogStore {
+    async fn save(&self, backup_id: &str, entries: &[BackupCatalogEntry]) -> Result<()> {
+        let _guard = WRITE_LOCK.lock().await;
+        let mut catalog = self.read().await?;
+        catalog.insert(backup_id.to_string(), entries.to_vec());
+        self.write(&catalog).await?;
+        info!(
+            "Saved backup {} to the catalog file: {}",
+            backup_id,
+            self.path.display()
+        );
+        Ok(())
+    }
+
+    async fn get(&self, backup_id: &str) -> Result<Vec<BackupCatalogEntry>> {
+        self.read()
+            .await?
+            .remove(backup_id)
+            .with_context(|| format!("Backup {} not found in the catalog", backup_id))
+    }
+
+    async fn list_backup_ids(&self) -> Result<Vec<String>> {
+        Ok(self.read().await?.into_keys().collect())
+    }
+
+    async fn delete(&self, backup_id: &str) -> Result<()> {
+        let _guard = WRITE_LOCK.lock().await;
+        let mut catalog = self.read().await?;
+        if catalog.remove(backup_id).is_some() {
+            self.write(&catalog).await?;
+            info!(
+                "Deleted backup {} from the catalog file: {}",
+                backup_id,
+                self.path.display()
+            );
+        }
+        Ok(())
+    }
+}
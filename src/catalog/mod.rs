@@ -0,0 +1,7 @@
+pub mod backup_catalog;
+pub mod catalog_entry;
+pub mod config_map_store;
+pub mod local_file_store;
+
+#[cfg(test)]
+mod catalog_tests;
@@ -0,0 +1,490 @@
+use super::crds::{
+    OperationPhase, PvcOperationStatus, SnapKubeBackup, SnapKubeBackupStatus, SnapKubeRestore,
+    SnapKubeRestoreStatus,
+};
+use crate::{
+    backup::{backup_operator::BackupOperator, backup_summary::BackupSummary},
+    k8s_ops::{
+        ns::namespaces::{resolve_namespaces, KubeNamespaceApi},
+        pvc::pvc_selector::PvcSelector,
+    },
+    restore::{
+        restore_operator::RestoreOperator, restore_payload::RestorePayload,
+        restore_summary::RestoreSummary,
+    },
+};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use futures::StreamExt;
+use kube::{
+    api::{Patch, PatchParams},
+    runtime::{controller::Action, watcher, Controller},
+    Api, Client, ResourceExt,
+};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::{mpsc, watch};
+use tracing::{error, info, warn};
+
+/// Error returned by the reconcilers
+#[derive(Debug)]
+pub struct ControllerError(anyhow::Error);
+
+impl Display for ControllerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl std::error::Error for ControllerError {}
+
+impl From<anyhow::Error> for ControllerError {
+    fn from(e: anyhow::Error) -> Self {
+        ControllerError(e)
+    }
+}
+
+impl From<kube::Error> for ControllerError {
+    fn from(e: kube::Error) -> Self {
+        ControllerError(e.into())
+    }
+}
+
+/// The state shared by the reconcilers
+pub(crate) struct ControllerContext {
+    k8s_client: Client,
+    /// UIDs of the resources run by this process, until their final status is seen
+    started: Mutex<HashSet<String>>,
+    /// Receiver of the shutdown notification of the process
+    shutdown_rx: watch::Receiver<bool>,
+}
+
+impl ControllerContext {
+    pub(crate) fn new(k8s_client: Client, shutdown_rx: watch::Receiver<bool>) -> Self {
+        Self {
+            k8s_client,
+            started: Mutex::new(HashSet::new()),
+            shutdown_rx,
+        }
+    }
+
+    /// Future that completes once the process shuts down
+    fn shutdown(&self) -> impl Future<Output = ()> + Send + Sync + 'static {
+        let mut shutdown_rx = self.shutdown_rx.clone();
+        async move {
+            let _ = shutdown_rx.wait_for(|shutdown| *shutdown).await;
+        }
+    }
+
+    /// Whether a resource is run by this process
+    fn is_started(&self, uid: &str) -> bool {
+        self.started.lock().unwrap().contains(uid)
+    }
+
+    /// Record that a resource is run by this process
+    pub(crate) fn start(&self, uid: &str) {
+        self.started.lock().unwrap().insert(uid.to_string());
+    }
+
+    /// Forget a resource whose final status is seen
+    fn finish(&self, uid: &str) {
+        self.started.lock().unwrap().remove(uid);
+    }
+}
+
+/// A struct for running the controller of the SnapKubeBackup and SnapKubeRestore resources
+pub struct ControllerOperator;
+
+impl ControllerOperator {
+    /// Runs the controller until the shutdown future completes
+    ///
+    /// Each SnapKubeBackup and SnapKubeRestore is run once: its status moves from Pending to
+    /// Running, and then to Completed, PartiallyFailed or Failed along with the outcome of
+    /// each PVC. A finished resource is never run again, a new resource must be created.
+    ///
+    /// On shutdown, no new resource is run and the running ones are awaited. A running backup
+    /// that has scaled down workloads is interrupted instead, and scales them back up.
+    ///
+    /// # Arguments
+    ///
+    /// * `shutdown` - Future that completes when the controller should shut down
+    pub async fn run(shutdown: impl Future<Output = ()>) -> Result<()> {
+        let k8s_client = Client::try_default().await?;
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let context = Arc::new(ControllerContext::new(k8s_client.clone(), shutdown_rx));
+
+        let backups = Controller::new(
+            Api::<SnapKubeBackup>::all(k8s_client.clone()),
+            watcher::Config::default(),
+        )
+        .graceful_shutdown_on(context.shutdown())
+        .run(Self::reconcile_backup, Self::error_policy, context.clone())
+        .for_each(|result| async move {
+            match result {
+                Ok((object, _)) => info!("Reconciled SnapKubeBackup: {}", object),
+                Err(e) => warn!("Failed to reconcile a SnapKubeBackup: {}", e),
+            }
+        });
+
+        let restores = Controller::new(
+            Api::<SnapKubeRestore>::all(k8s_client.clone()),
+            watcher::Config::default(),
+        )
+        .graceful_shutdown_on(context.shutdown())
+        .run(Self::reconcile_restore, Self::error_policy, context.clone())
+        .for_each(|result| async move {
+            match result {
+                Ok((object, _)) => info!("Reconciled SnapKubeRestore: {}", object),
+                Err(e) => warn!("Failed to reconcile a SnapKubeRestore: {}", e),
+            }
+        });
+
+        let shutdown = async move {
+            shutdown.await;
+            info!("Shutting down, waiting for the running backups and restores to complete...");
+            let _ = shutdown_tx.send(true);
+        };
+
+        info!("Watching SnapKubeBackup and SnapKubeRestore resources...");
+        tokio::join!(backups, restores, shutdown);
+        Ok(())
+    }
+
+    /// Retry a failed reconciliation after a minute
+    fn error_policy<K>(
+        _object: Arc<K>,
+        e: &ControllerError,
+        _context: Arc<ControllerContext>,
+    ) -> Action {
+        error!("Reconciliation failed: {}", e);
+        Action::requeue(Duration::from_secs(60))
+    }
+
+    /// Patch the status of a resource
+    async fn patch_status<K, S>(api: &Api<K>, name: &str, status: &S) -> Result<()>
+    where
+        K: kube::Resource + Clone + serde::de::DeserializeOwned + std::fmt::Debug,
+        S: Serialize,
+    {
+        api.patch_status(
+            name,
+            &PatchParams::default(),
+            &Patch::Merge(json!({ "status": status })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Drive a backup or restore run to completion, patching the status of its resource with
+    /// the status of each PVC as soon as the PVC is done
+    ///
+    /// A failure to patch the status only warns, as the final status is patched once the run
+    /// completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `api` - Api object of the resource
+    /// * `name` - Name of the resource
+    /// * `run` - The backup or restore run
+    /// * `pvcs_rx` - Receiver of the status of each PVC done by the run
+    /// * `status` - The status of the resource, given the status of the PVCs done so far
+    pub(crate) async fn report_progress<K, S, T>(
+        api: &Api<K>,
+        name: &str,
+        run: impl Future<Output = T>,
+        mut pvcs_rx: mpsc::UnboundedReceiver<PvcOperationStatus>,
+        status: impl Fn(Vec<PvcOperationStatus>) -> S,
+    ) -> T
+    where
+        K: kube::Resource + Clone + serde::de::DeserializeOwned + std::fmt::Debug,
+        S: Serialize,
+    {
+        tokio::pin!(run);
+        let mut pvcs = Vec::new();
+        loop {
+            tokio::select! {
+                output = &mut run => return output,
+                Some(pvc) = pvcs_rx.recv() => {
+                    pvcs.push(pvc);
+                    if let Err(e) = Self::patch_status(api, name, &status(pvcs.clone())).await {
+                        warn!("Failed to report the progress of {}: {:#}", name, e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run a SnapKubeBackup, if it has not run yet
+    ///
+    /// A SnapKubeBackup found Running is only failed as interrupted if it was not started
+    /// by this process: the status patches of a run queue events of their own, which may
+    /// be reconciled before the final status reaches the cache.
+    pub(crate) async fn reconcile_backup(
+        snap_kube_backup: Arc<SnapKubeBackup>,
+        context: Arc<ControllerContext>,
+    ) -> Result<Action, ControllerError> {
+        let name = snap_kube_backup.name_any();
+        let namespace = snap_kube_backup
+            .namespace()
+            .context("SnapKubeBackup has no namespace")?;
+        let api: Api<SnapKubeBackup> = Api::namespaced(context.k8s_client.clone(), &namespace);
+
+        let uid = snap_kube_backup
+            .uid()
+            .context("SnapKubeBackup has no UID")?;
+
+        let status = snap_kube_backup.status.clone().unwrap_or_default();
+        if status.phase.is_finished() {
+            context.finish(&uid);
+            return Ok(Action::await_change());
+        }
+        if context.is_started(&uid) {
+            // Run by this process, its final status is not in the cache yet
+            return Ok(Action::await_change());
+        }
+        if status.phase == OperationPhase::Running {
+            // The backup was interrupted by a restart, since a backup runs within a single
+            // reconciliation of a process
+            let status = SnapKubeBackupStatus {
+                phase: OperationPhase::Failed,
+                completion_time: Some(Utc::now().to_rfc3339()),
+                message: Some("The backup was interrupted by a restart of the controller".into()),
+                ..status
+            };
+            Self::patch_status(&api, &name, &status).await?;
+            return Ok(Action::await_change());
+        }
+
+        info!("Running SnapKubeBackup {} on namespace {}", name, namespace);
+        let status = SnapKubeBackupStatus {
+            phase: OperationPhase::Running,
+            start_time: Some(Utc::now().to_rfc3339()),
+            ..Default::default()
+        };
+        Self::patch_status(&api, &name, &status).await?;
+        context.start(&uid);
+
+        let (pvcs_tx, pvcs_rx) = mpsc::unbounded_channel();
+        let backup_summary = Self::report_progress(
+            &api,
+            &name,
+            Self::run_backup(&snap_kube_backup, &namespace, &context, pvcs_tx),
+            pvcs_rx,
+            |pvcs| SnapKubeBackupStatus {
+                pvcs,
+                ..status.clone()
+            },
+        )
+        .await;
+        let status = match backup_summary {
+            Ok(backup_summary) => status.with_summary(&backup_summary),
+            Err(e) => SnapKubeBackupStatus {
+                phase: OperationPhase::Failed,
+                message: Some(format!("{:#}", e)),
+                ..status
+            },
+        };
+        let status = SnapKubeBackupStatus {
+            completion_time: Some(Utc::now().to_rfc3339()),
+            ..status
+        };
+        info!("SnapKubeBackup {} finished: {}", name, status.phase);
+        Self::patch_status(&api, &name, &status).await?;
+        Ok(Action::await_change())
+    }
+
+    /// Run the backup of a SnapKubeBackup
+    ///
+    /// # Arguments
+    ///
+    /// * `snap_kube_backup` - The SnapKubeBackup to run
+    /// * `namespace` - Namespace of the SnapKubeBackup
+    /// * `context` - The state shared by the reconcilers
+    /// * `pvcs_tx` - Sender of the status of each PVC as soon as it is backed up
+    async fn run_backup(
+        snap_kube_backup: &SnapKubeBackup,
+        namespace: &str,
+        context: &ControllerContext,
+        pvcs_tx: mpsc::UnboundedSender<PvcOperationStatus>,
+    ) -> Result<BackupSummary> {
+        snap_kube_backup.spec.validate_namespace(namespace)?;
+        let mut backup_spec = snap_kube_backup.spec.backup.clone();
+        if backup_spec.has_no_namespaces() {
+            backup_spec.namespaces = vec![namespace.to_string()];
+        }
+        backup_spec.validate()?;
+
+        let namespaces = resolve_namespaces(
+            &KubeNamespaceApi {
+                api: Api::all(context.k8s_client.clone()),
+            },
+            &backup_spec.namespace_selection()?,
+        )
+        .await?;
+        let backup_payload = backup_spec.to_backup_payload(
+            &snap_kube_backup.spec.region,
+            namespaces,
+            snap_kube_backup
+                .spec
+                .catalog_config_map
+                .as_ref()
+                .map(|config_map| config_map.to_catalog_location(namespace)),
+        )?;
+        BackupOperator::backup(backup_payload, context.shutdown(), |result| {
+            let _ = pvcs_tx.send(PvcOperationStatus::from_backup_result(result));
+        })
+        .await
+    }
+
+    /// Run a SnapKubeRestore, if it has not run yet
+    ///
+    /// A SnapKubeRestore found Running is only failed as interrupted if it was not started
+    /// by this process: the status patches of a run queue events of their own, which may
+    /// be reconciled before the final status reaches the cache.
+    pub(crate) async fn reconcile_restore(
+        snap_kube_restore: Arc<SnapKubeRestore>,
+        context: Arc<ControllerContext>,
+    ) -> Result<Action, ControllerError> {
+        let name = snap_kube_restore.name_any();
+        let namespace = snap_kube_restore
+            .namespace()
+            .context("SnapKubeRestore has no namespace")?;
+        let api: Api<SnapKubeRestore> = Api::namespaced(context.k8s_client.clone(), &namespace);
+
+        let uid = snap_kube_restore
+            .uid()
+            .context("SnapKubeRestore has no UID")?;
+
+        let status = snap_kube_restore.status.clone().unwrap_or_default();
+        if status.phase.is_finished() {
+            context.finish(&uid);
+            return Ok(Action::await_change());
+        }
+        if context.is_started(&uid) {
+            // Run by this process, its final status is not in the cache yet
+            return Ok(Action::await_change());
+        }
+        if status.phase == OperationPhase::Running {
+            // The restore was interrupted by a restart, since a restore runs within a single
+            // reconciliation of a process
+            let status = SnapKubeRestoreStatus {
+                phase: OperationPhase::Failed,
+                completion_time: Some(Utc::now().to_rfc3339()),
+                message: Some("The restore was interrupted by a restart of the controller".into()),
+                ..status
+            };
+            Self::patch_status(&api, &name, &status).await?;
+            return Ok(Action::await_change());
+        }
+
+        info!(
+            "Running SnapKubeRestore {} on namespace {}",
+            name, namespace
+        );
+        let status = SnapKubeRestoreStatus {
+            phase: OperationPhase::Running,
+            start_time: Some(Utc::now().to_rfc3339()),
+            ..Default::default()
+        };
+        Self::patch_status(&api, &name, &status).await?;
+        context.start(&uid);
+
+        let target_ns = snap_kube_restore
+            .spec
+            .target_ns
+            .clone()
+            .unwrap_or_else(|| namespace.clone());
+        let (pvcs_tx, pvcs_rx) = mpsc::unbounded_channel();
+        let restore_summary = Self::report_progress(
+            &api,
+            &name,
+            Self::run_restore(&snap_kube_restore, &namespace, &target_ns, pvcs_tx),
+            pvcs_rx,
+            |pvcs| SnapKubeRestoreStatus {
+                pvcs,
+                ..status.clone()
+            },
+        )
+        .await;
+
+        let status = match restore_summary {
+            Ok(restore_summary) => status.with_summary(&target_ns, &restore_summary),
+            Err(e) => SnapKubeRestoreStatus {
+                phase: OperationPhase::Failed,
+                message: Some(format!("{:#}", e)),
+                ..status
+            },
+        };
+        let status = SnapKubeRestoreStatus {
+            completion_time: Some(Utc::now().to_rfc3339()),
+            ..status
+        };
+        info!("SnapKubeRestore {} finished: {}", name, status.phase);
+        Self::patch_status(&api, &name, &status).await?;
+        Ok(Action::await_change())
+    }
+
+    /// Run the restore of a SnapKubeRestore
+    ///
+    /// # Arguments
+    ///
+    /// * `snap_kube_restore` - The SnapKubeRestore to run
+    /// * `namespace` - Namespace of the SnapKubeRestore
+    /// * `target_ns` - Namespace the PVCs are restored to
+    /// * `pvcs_tx` - Sender of the status of each PVC as soon as it is restored
+    async fn run_restore(
+        snap_kube_restore: &SnapKubeRestore,
+        namespace: &str,
+        target_ns: &str,
+        pvcs_tx: mpsc::UnboundedSender<PvcOperationStatus>,
+    ) -> Result<RestoreSummary> {
+        let spec = &snap_kube_restore.spec;
+        spec.validate_namespace(namespace)?;
+        let pvc_selector = PvcSelector::new(
+            spec.selector.clone(),
+            spec.field_selector.clone(),
+            spec.include_pvc_regex.as_deref(),
+            spec.exclude_pvc_regex.as_deref(),
+        )?;
+        let restore_payload = RestorePayload::new(
+            spec.source_ns.clone(),
+            target_ns,
+            spec.volume_snapshot_class.clone(),
+            spec.pvc_name.clone(),
+            spec.include_all_pvcs,
+            spec.volume_snapshot_name_prefix.clone().unwrap_or_default(),
+            spec.target_snapshot_content_name_prefix.clone(),
+            spec.storage_class_name.clone(),
+            spec.vsc_retain_policy,
+            spec.region.clone(),
+        )
+        .with_pvc_selector(pvc_selector)
+        .with_max_concurrency(spec.max_concurrency)
+        .with_backup_id(spec.backup_id.clone())
+        .with_catalog(
+            spec.catalog_config_map
+                .as_ref()
+                .map(|config_map| config_map.to_catalog_location(namespace)),
+        )
+        .with_volume_group_snapshot(spec.volume_group_snapshot.clone())
+        .with_bind_timeout(Duration::from_secs(spec.bind_timeout_secs))
+        .with_ebs_snapshot_source(spec.ebs_snapshot.clone())
+        .with_restore_strategy(spec.restore_strategy)
+        .with_static_volume(spec.static_volume.clone(), spec.pv_reclaim_policy)
+        .with_pvc_overrides(spec.pvc_overrides.clone())
+        .with_on_conflict(spec.on_conflict)
+        .with_pvc_rename(spec.pvc_rename.clone())
+        .with_storage_class_mappings(spec.storage_class_mappings.clone());
+        RestoreOperator::restore(restore_payload, |result| {
+            let _ = pvcs_tx.send(PvcOperationStatus::from_restore_result(target_ns, result));
+        })
+        .await
+    }
+}
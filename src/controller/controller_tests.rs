@@ -0,0 +1,447 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backup::backup_summary::{BackupSummary, PvcBackupResult, PvcBackupStatus},
+        catalog::{backup_catalog::CatalogLocation, catalog_entry::BackupCatalogEntry},
+        controller::{
+            controller_operator::{ControllerContext, ControllerOperator},
+            crds::{
+                crds_yaml, OperationPhase, PvcOperationStatus, SnapKubeBackup, SnapKubeBackupSpec,
+                SnapKubeBackupStatus, SnapKubeRestore, SnapKubeRestoreSpec, SnapKubeRestoreStatus,
+            },
+        },
+        k8s_ops::{fake_api_server::FakeApiServer, vsc::retain_policy::VSCRetainPolicy},
+        restore::restore_summary::{PvcRestoreResult, PvcRestoreStatus, RestoreSummary},
+    };
+    use chrono::Utc;
+    use kube::{runtime::controller::Action, Api, CustomResourceExt, Resource};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::{collections::BTreeMap, sync::Arc, time::Duration};
+    use tokio::sync::{mpsc, watch};
+
+    const BACKUP_PATH: &str =
+        "/apis/snap-kube.io/v1alpha1/namespaces/tenant-a/snapkubebackups/nightly";
+    const RESTORE_PATH: &str =
+        "/apis/snap-kube.io/v1alpha1/namespaces/tenant-a/snapkuberestores/data";
+
+    /// An API server with a SnapKubeBackup and a SnapKubeRestore in the Running phase
+    fn api_server_with_running_resources() -> FakeApiServer {
+        let server = FakeApiServer::new();
+        server.insert(
+            BACKUP_PATH,
+            json!({
+                "apiVersion": "snap-kube.io/v1alpha1",
+                "kind": "SnapKubeBackup",
+                "metadata": { "name": "nightly", "namespace": "tenant-a", "uid": "uid-backup" },
+                "spec": {
+                    "volumeSnapshotClass": "ebs-vsc",
+                    "volumeSnapshotNamePrefix": "nightly",
+                    "includeAllPvcs": true,
+                },
+                "status": { "phase": "Running" },
+            }),
+        );
+        server.insert(
+            RESTORE_PATH,
+            json!({
+                "apiVersion": "snap-kube.io/v1alpha1",
+                "kind": "SnapKubeRestore",
+                "metadata": { "name": "data", "namespace": "tenant-a", "uid": "uid-restore" },
+                "spec": {
+                    "sourceNs": "tenant-a",
+                    "volumeSnapshotClass": "ebs-vsc",
+                    "pvcName": "data",
+                    "targetSnapshotContentNamePrefix": "restored",
+                    "storageClassName": "gp3",
+                    "vscRetainPolicy": "Retain",
+                },
+                "status": { "phase": "Running" },
+            }),
+        );
+        server
+    }
+
+    #[test]
+    fn test_crds() {
+        assert_eq!(
+            SnapKubeBackup::crd().metadata.name.as_deref(),
+            Some("snapkubebackups.snap-kube.io")
+        );
+        assert_eq!(
+            SnapKubeRestore::crd().metadata.name.as_deref(),
+            Some("snapkuberestores.snap-kube.io")
+        );
+        assert_eq!(SnapKubeBackup::api_version(&()), "snap-kube.io/v1alpha1");
+
+        let yaml = crds_yaml().unwrap();
+        assert_eq!(yaml.matches("kind: CustomResourceDefinition").count(), 2);
+    }
+
+    #[test]
+    fn test_backup_spec_deserialization() {
+        let spec: SnapKubeBackupSpec = serde_json::from_value(serde_json::json!({
+            "volumeSnapshotClass": "ebs-vsc",
+            "volumeSnapshotNamePrefix": "nightly",
+            "includeAllPvcs": true,
+            "catalogConfigMap": { "name": "snap-kube-catalog" }
+        }))
+        .unwrap();
+
+        assert_eq!(spec.region, "eu-west-1");
+        assert!(spec.backup.has_no_namespaces());
+        assert!(spec.backup.include_all_pvcs);
+        assert_eq!(spec.backup.max_concurrency, 4);
+        assert_eq!(
+            spec.catalog_config_map
+                .unwrap()
+                .to_catalog_location("tenant-a"),
+            CatalogLocation::ConfigMap {
+                namespace: "tenant-a".to_string(),
+                name: "snap-kube-catalog".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_restore_spec_deserialization() {
+        let spec: SnapKubeRestoreSpec = serde_json::from_value(serde_json::json!({
+            "sourceNs": "tenant-a",
+            "volumeSnapshotClass": "ebs-vsc",
+            "pvcName": "data",
+            "volumeSnapshotNamePrefix": "nightly",
+            "targetSnapshotContentNamePrefix": "restored",
+            "storageClassName": "gp3",
+            "vscRetainPolicy": "Retain"
+        }))
+        .unwrap();
+
+        assert_eq!(spec.target_ns, None);
+        assert_eq!(spec.vsc_retain_policy, VSCRetainPolicy::Retain);
+        assert_eq!(spec.max_concurrency, 4);
+    }
+
+    #[test]
+    fn test_operation_phase() {
+        assert_eq!(
+            OperationPhase::from_outcome(2, 0),
+            OperationPhase::Completed
+        );
+        assert_eq!(
+            OperationPhase::from_outcome(0, 0),
+            OperationPhase::Completed
+        );
+        assert_eq!(
+            OperationPhase::from_outcome(1, 1),
+            OperationPhase::PartiallyFailed
+        );
+        assert_eq!(OperationPhase::from_outcome(0, 2), OperationPhase::Failed);
+
+        assert!(!OperationPhase::Pending.is_finished());
+        assert!(!OperationPhase::Running.is_finished());
+        assert!(OperationPhase::PartiallyFailed.is_finished());
+    }
+
+    #[test]
+    fn test_backup_status_with_summary() {
+        let summary = BackupSummary::new(
+            "backup-1",
+            vec![
+                PvcBackupResult::new(
+                    "tenant-a",
+                    "data",
+                    PvcBackupStatus::Completed(Box::new(BackupCatalogEntry {
+                        backup_id: "backup-1".to_string(),
+                        created_at: Utc::now(),
+                        source_ns: "tenant-a".to_string(),
+                        pvc_name: "data".to_string(),
+                        pvc_spec: None,
+                        volume_snapshot_class: "ebs-vsc".to_string(),
+                        volume_snapshot_name: "vs-data".to_string(),
+                        volume_snapshot_content_name: "snapcontent-data".to_string(),
+                        snapshot_handle: "snap-0123456789abcdef0".to_string(),
+                        restore_size: "1Gi".to_string(),
+                        volume_group_snapshot_name: None,
+                        pvc_labels: BTreeMap::new(),
+                        pvc_annotations: BTreeMap::new(),
+                        snapshot_copies: Vec::new(),
+                    })),
+                ),
+                PvcBackupResult::new(
+                    "tenant-a",
+                    "wal",
+                    PvcBackupStatus::Failed("timed out".to_string()),
+                ),
+            ],
+        );
+
+        let status = SnapKubeBackupStatus::default().with_summary(&summary);
+
+        assert_eq!(status.phase, OperationPhase::PartiallyFailed);
+        assert_eq!(status.backup_id.as_deref(), Some("backup-1"));
+        assert_eq!(status.pvcs.len(), 2);
+        assert_eq!(status.pvcs[0].pvc_name, "data");
+        assert_eq!(
+            status.pvcs[0].snapshot_handle.as_deref(),
+            Some("snap-0123456789abcdef0")
+        );
+        assert_eq!(status.pvcs[1].phase, OperationPhase::Failed);
+        assert_eq!(status.pvcs[1].error.as_deref(), Some("timed out"));
+    }
+
+    #[test]
+    fn test_restore_status_with_summary() {
+        let summary = RestoreSummary::new(vec![
+            PvcRestoreResult::new("data", PvcRestoreStatus::Restored),
+            PvcRestoreResult::new("wal", PvcRestoreStatus::Restored),
+        ]);
+
+        let status = SnapKubeRestoreStatus::default().with_summary("tenant-b", &summary);
+
+        assert_eq!(status.phase, OperationPhase::Completed);
+        assert!(status
+            .pvcs
+            .iter()
+            .all(|pvc| pvc.namespace == "tenant-b" && pvc.error.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_running_resources_interrupted_by_a_restart() {
+        let server = api_server_with_running_resources();
+        let context = Arc::new(ControllerContext::new(
+            server.client("default"),
+            watch::channel(false).1,
+        ));
+        let backup: SnapKubeBackup =
+            serde_json::from_value(server.get(BACKUP_PATH).unwrap()).unwrap();
+        let restore: SnapKubeRestore =
+            serde_json::from_value(server.get(RESTORE_PATH).unwrap()).unwrap();
+
+        let action = ControllerOperator::reconcile_backup(Arc::new(backup), context.clone())
+            .await
+            .unwrap();
+        assert_eq!(action, Action::await_change());
+        let action = ControllerOperator::reconcile_restore(Arc::new(restore), context)
+            .await
+            .unwrap();
+        assert_eq!(action, Action::await_change());
+
+        for path in [BACKUP_PATH, RESTORE_PATH] {
+            let status = &server.get(path).unwrap()["status"];
+            assert_eq!(status["phase"], json!("Failed"));
+            assert!(status["message"]
+                .as_str()
+                .unwrap()
+                .contains("interrupted by a restart of the controller"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_running_resources_started_by_this_process() {
+        let server = api_server_with_running_resources();
+        let context = Arc::new(ControllerContext::new(
+            server.client("default"),
+            watch::channel(false).1,
+        ));
+        context.start("uid-backup");
+        context.start("uid-restore");
+        let backup: SnapKubeBackup =
+            serde_json::from_value(server.get(BACKUP_PATH).unwrap()).unwrap();
+        let restore: SnapKubeRestore =
+            serde_json::from_value(server.get(RESTORE_PATH).unwrap()).unwrap();
+
+        let action = ControllerOperator::reconcile_backup(Arc::new(backup), context.clone())
+            .await
+            .unwrap();
+        assert_eq!(action, Action::await_change());
+        let action = ControllerOperator::reconcile_restore(Arc::new(restore), context)
+            .await
+            .unwrap();
+        assert_eq!(action, Action::await_change());
+
+        // The stale Running status queued by the run itself leaves the resources untouched
+        assert!(server.requests().is_empty());
+        for path in [BACKUP_PATH, RESTORE_PATH] {
+            assert_eq!(
+                server.get(path).unwrap()["status"]["phase"],
+                json!("Running")
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_namespace() {
+        let backup_spec = |spec: serde_json::Value| -> SnapKubeBackupSpec {
+            let mut base = json!({
+                "volumeSnapshotClass": "ebs-vsc",
+                "volumeSnapshotNamePrefix": "nightly",
+                "includeAllPvcs": true,
+            });
+            base.as_object_mut()
+                .unwrap()
+                .extend(spec.as_object().unwrap().clone());
+            serde_json::from_value(base).unwrap()
+        };
+        assert!(backup_spec(json!({}))
+            .validate_namespace("tenant-a")
+            .is_ok());
+        assert!(backup_spec(json!({ "namespaces": ["tenant-a"] }))
+            .validate_namespace("tenant-a")
+            .is_ok());
+        assert!(
+            backup_spec(json!({ "namespaces": ["tenant-a", "tenant-b"] }))
+                .validate_namespace("tenant-a")
+                .is_err()
+        );
+        assert!(backup_spec(json!({ "namespaceSelector": "team=a" }))
+            .validate_namespace("tenant-a")
+            .is_err());
+        assert!(backup_spec(json!({ "allNamespaces": true }))
+            .validate_namespace("tenant-a")
+            .is_err());
+        assert!(backup_spec(
+            json!({ "catalogConfigMap": { "namespace": "snap-kube", "name": "catalog" } })
+        )
+        .validate_namespace("tenant-a")
+        .is_err());
+
+        let restore_spec = |spec: serde_json::Value| -> SnapKubeRestoreSpec {
+            let mut base = json!({
+                "sourceNs": "tenant-a",
+                "volumeSnapshotClass": "ebs-vsc",
+                "pvcName": "data",
+                "targetSnapshotContentNamePrefix": "restored",
+                "storageClassName": "gp3",
+            });
+            base.as_object_mut()
+                .unwrap()
+                .extend(spec.as_object().unwrap().clone());
+            serde_json::from_value(base).unwrap()
+        };
+        assert!(restore_spec(json!({}))
+            .validate_namespace("tenant-a")
+            .is_ok());
+        assert!(restore_spec(json!({ "targetNs": "tenant-a" }))
+            .validate_namespace("tenant-a")
+            .is_ok());
+        assert!(restore_spec(json!({ "sourceNs": "tenant-b" }))
+            .validate_namespace("tenant-a")
+            .is_err());
+        assert!(restore_spec(json!({ "targetNs": "tenant-b" }))
+            .validate_namespace("tenant-a")
+            .is_err());
+        assert!(restore_spec(
+            json!({ "catalogConfigMap": { "namespace": "tenant-b", "name": "catalog" } })
+        )
+        .validate_namespace("tenant-a")
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_resources_of_other_namespaces() {
+        let server = FakeApiServer::new();
+        server.insert(
+            BACKUP_PATH,
+            json!({
+                "apiVersion": "snap-kube.io/v1alpha1",
+                "kind": "SnapKubeBackup",
+                "metadata": { "name": "nightly", "namespace": "tenant-a", "uid": "uid-backup" },
+                "spec": {
+                    "namespaces": ["tenant-b"],
+                    "volumeSnapshotClass": "ebs-vsc",
+                    "volumeSnapshotNamePrefix": "nightly",
+                    "includeAllPvcs": true,
+                },
+            }),
+        );
+        server.insert(
+            RESTORE_PATH,
+            json!({
+                "apiVersion": "snap-kube.io/v1alpha1",
+                "kind": "SnapKubeRestore",
+                "metadata": { "name": "data", "namespace": "tenant-a", "uid": "uid-restore" },
+                "spec": {
+                    "sourceNs": "tenant-a",
+                    "targetNs": "tenant-b",
+                    "volumeSnapshotClass": "ebs-vsc",
+                    "pvcName": "data",
+                    "targetSnapshotContentNamePrefix": "restored",
+                    "storageClassName": "gp3",
+                },
+            }),
+        );
+        let context = Arc::new(ControllerContext::new(
+            server.client("default"),
+            watch::channel(false).1,
+        ));
+        let backup: SnapKubeBackup =
+            serde_json::from_value(server.get(BACKUP_PATH).unwrap()).unwrap();
+        let restore: SnapKubeRestore =
+            serde_json::from_value(server.get(RESTORE_PATH).unwrap()).unwrap();
+
+        ControllerOperator::reconcile_backup(Arc::new(backup), context.clone())
+            .await
+            .unwrap();
+        ControllerOperator::reconcile_restore(Arc::new(restore), context)
+            .await
+            .unwrap();
+
+        // Nothing but the status of the resources is touched
+        assert!(server
+            .requests()
+            .iter()
+            .all(|request| request.starts_with("PATCH /apis/snap-kube.io/")));
+        for path in [BACKUP_PATH, RESTORE_PATH] {
+            let status = &server.get(path).unwrap()["status"];
+            assert_eq!(status["phase"], json!("Failed"));
+            assert!(status["message"]
+                .as_str()
+                .unwrap()
+                .contains("own namespace tenant-a, not tenant-b"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report_progress() {
+        let server = api_server_with_running_resources();
+        let api: Api<SnapKubeBackup> = Api::namespaced(server.client("default"), "tenant-a");
+        let (pvcs_tx, pvcs_rx) = mpsc::unbounded_channel();
+        let status = SnapKubeBackupStatus {
+            phase: OperationPhase::Running,
+            ..Default::default()
+        };
+        let pvc_status = |pvc_name: &str| PvcOperationStatus {
+            namespace: "tenant-a".to_string(),
+            pvc_name: pvc_name.to_string(),
+            phase: OperationPhase::Completed,
+            ..Default::default()
+        };
+
+        // The run completes once the status of both PVCs is reported
+        let run = async {
+            pvcs_tx.send(pvc_status("data")).unwrap();
+            pvcs_tx.send(pvc_status("logs")).unwrap();
+            while server.get(BACKUP_PATH).unwrap()["status"]["pvcs"]
+                .as_array()
+                .map_or(0, Vec::len)
+                < 2
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            "done"
+        };
+        let output = ControllerOperator::report_progress(&api, "nightly", run, pvcs_rx, |pvcs| {
+            SnapKubeBackupStatus {
+                pvcs,
+                ..status.clone()
+            }
+        })
+        .await;
+
+        assert_eq!(output, "done");
+        let status = &server.get(BACKUP_PATH).unwrap()["status"];
+        assert_eq!(status["phase"], json!("Running"));
+        assert_eq!(status["pvcs"][0]["pvcName"], json!("data"));
+        assert_eq!(status["pvcs"][1]["pvcName"], json!("logs"));
+    }
+}
@@ -0,0 +1,476 @@
+use crate::{
+    aws_ops::ebs::{EbsSnapshotSource, EbsVolumeSettings},
+    backup::{
+        backup_spec::BackupSpec,
+        backup_summary::{BackupSummary, PvcBackupResult, PvcBackupStatus},
+    },
+    catalog::backup_catalog::CatalogLocation,
+    k8s_ops::{
+        pv::reclaim_policy::PVReclaimPolicy,
+        pvc::{pvc_rename::PvcRename, pvc_template::PvcTemplate},
+        vsc::retain_policy::VSCRetainPolicy,
+    },
+    restore::{
+        conflict_policy::ConflictPolicy,
+        restore_strategy::RestoreStrategy,
+        restore_summary::{PvcRestoreResult, PvcRestoreStatus, RestoreSummary},
+    },
+};
+use anyhow::{bail, Result};
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+/// A request to backup the PVCs of the namespace of the resource
+///
+/// The controller runs with cluster-wide permissions, so the only source namespace allowed
+/// is the namespace of the resource, see [`SnapKubeBackupSpec::validate_namespace`].
+#[derive(CustomResource, Clone, Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
+#[kube(
+    group = "snap-kube.io",
+    version = "v1alpha1",
+    kind = "SnapKubeBackup",
+    namespaced,
+    status = "SnapKubeBackupStatus",
+    shortname = "skb",
+    printcolumn = r#"{"name":"Phase","type":"string","jsonPath":".status.phase"}"#,
+    printcolumn = r#"{"name":"Backup ID","type":"string","jsonPath":".status.backupId"}"#,
+    printcolumn = r#"{"name":"Age","type":"date","jsonPath":".metadata.creationTimestamp"}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapKubeBackupSpec {
+    /// Region where the EBS volumes are stored
+    #[serde(default = "default_region")]
+    pub region: String,
+    #[serde(flatten)]
+    pub backup: BackupSpec,
+    /// ConfigMap where the backup catalog is stored, if any, in the namespace of the resource
+    pub catalog_config_map: Option<ConfigMapReference>,
+}
+
+/// A request to restore the PVCs of the namespace of the resource
+///
+/// The controller runs with cluster-wide permissions, so the only source and target namespace
+/// allowed is the namespace of the resource, see [`SnapKubeRestoreSpec::validate_namespace`].
+#[derive(CustomResource, Clone, Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
+#[kube(
+    group = "snap-kube.io",
+    version = "v1alpha1",
+    kind = "SnapKubeRestore",
+    namespaced,
+    status = "SnapKubeRestoreStatus",
+    shortname = "skr",
+    printcolumn = r#"{"name":"Phase","type":"string","jsonPath":".status.phase"}"#,
+    printcolumn = r#"{"name":"Age","type":"date","jsonPath":".metadata.creationTimestamp"}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapKubeRestoreSpec {
+    /// Source namespace, which must be the namespace of the resource
+    pub source_ns: String,
+    /// Target namespace, which must be the namespace of the resource if set
+    pub target_ns: Option<String>,
+    /// VolumeSnapshotClass name
+    pub volume_snapshot_class: String,
+    /// PVC name
+    pub pvc_name: Option<String>,
+    /// Include all PVCs in the namespace
+    #[serde(default)]
+    pub include_all_pvcs: bool,
+    /// Label selector for the PVCs
+    pub selector: Option<String>,
+    /// Field selector for the PVCs
+    pub field_selector: Option<String>,
+    /// Only include the PVCs whose name matches this regex
+    pub include_pvc_regex: Option<String>,
+    /// Exclude the PVCs whose name matches this regex
+    pub exclude_pvc_regex: Option<String>,
+    /// VolumeSnapshot name prefix, required unless a backup ID or a VolumeGroupSnapshot is set
+    pub volume_snapshot_name_prefix: Option<String>,
+    /// Target VolumeSnapshotContent name prefix, followed by the target namespace and the VolumeSnapshot name
+    pub target_snapshot_content_name_prefix: String,
+    /// StorageClass name, used for the PVCs whose source StorageClass is not mapped
+    pub storage_class_name: String,
+    /// Target StorageClasses, keyed by the StorageClass of the source PVCs, e.g. `gp2: gp3`
+    #[serde(default)]
+    pub storage_class_mappings: BTreeMap<String, String>,
+    /// VSC Retain Policy
+    #[serde(default = "default_vsc_retain_policy")]
+    pub vsc_retain_policy: VSCRetainPolicy,
+    /// Maximum number of PVCs restored concurrently
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    /// Restore the PVCs of a backup ID recorded in the catalog
+    pub backup_id: Option<String>,
+    /// ConfigMap where the backup catalog is stored, required when a backup ID is set, in the
+    /// namespace of the resource
+    pub catalog_config_map: Option<ConfigMapReference>,
+    /// Restore every member PVC of a VolumeGroupSnapshot of the source namespace
+    pub volume_group_snapshot: Option<String>,
+    /// Maximum time in seconds to wait for the members of a VolumeGroupSnapshot to be Bound, or
+    /// for the replaced PVCs to be deleted
+    #[serde(default = "default_bind_timeout_secs")]
+    pub bind_timeout_secs: u64,
+    /// Region of the cluster
+    #[serde(default = "default_region")]
+    pub region: String,
+    /// Restore the PVC named pvcName from an EBS snapshot, possibly in another region or account
+    pub ebs_snapshot: Option<EbsSnapshotSource>,
+    /// How the PVCs are restored, StaticVolume working without the CSI snapshot controller
+    #[serde(default)]
+    pub restore_strategy: RestoreStrategy,
+    /// Settings of the EBS volumes created by the StaticVolume strategy, overriding the
+    /// parameters of the StorageClass
+    #[serde(default)]
+    pub static_volume: EbsVolumeSettings,
+    /// Reclaim policy of the PersistentVolumes created by the StaticVolume strategy, the one
+    /// of their StorageClass if not set
+    pub pv_reclaim_policy: Option<PVReclaimPolicy>,
+    /// Access modes, volume mode, labels and annotations of the restored PVCs, overriding the
+    /// ones of the source PVCs
+    #[serde(default)]
+    pub pvc_overrides: PvcTemplate,
+    /// What to do with the PVCs that already exist in the target namespace
+    #[serde(default)]
+    pub on_conflict: ConflictPolicy,
+    /// New names of the restored PVCs, from explicit mappings, a prefix and suffix or a regex
+    #[serde(default)]
+    pub pvc_rename: PvcRename,
+}
+
+impl SnapKubeBackupSpec {
+    /// Check that the backup only reads and writes the namespace of the resource, so that
+    /// the cluster-wide permissions of the controller do not reach the other namespaces
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the resource
+    pub fn validate_namespace(&self, namespace: &str) -> Result<()> {
+        if self.backup.all_namespaces
+            || self.backup.namespace_selector.is_some()
+            || !self.backup.exclude_namespaces.is_empty()
+        {
+            bail!(
+                "A SnapKubeBackup can only backup its own namespace {}, allNamespaces, namespaceSelector and excludeNamespaces are not allowed",
+                namespace
+            );
+        }
+        if let Some(other) = self.backup.namespaces.iter().find(|ns| *ns != namespace) {
+            bail!(
+                "A SnapKubeBackup can only backup its own namespace {}, not {}",
+                namespace,
+                other
+            );
+        }
+        if let Some(config_map) = &self.catalog_config_map {
+            config_map.validate_namespace(namespace)?;
+        }
+        Ok(())
+    }
+}
+
+impl SnapKubeRestoreSpec {
+    /// Check that the restore only reads and writes the namespace of the resource, so that
+    /// the cluster-wide permissions of the controller do not reach the other namespaces
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the resource
+    pub fn validate_namespace(&self, namespace: &str) -> Result<()> {
+        if self.source_ns != namespace {
+            bail!(
+                "A SnapKubeRestore can only restore from its own namespace {}, not {}",
+                namespace,
+                self.source_ns
+            );
+        }
+        if let Some(target_ns) = self.target_ns.as_deref().filter(|ns| *ns != namespace) {
+            bail!(
+                "A SnapKubeRestore can only restore to its own namespace {}, not {}",
+                namespace,
+                target_ns
+            );
+        }
+        if let Some(config_map) = &self.catalog_config_map {
+            config_map.validate_namespace(namespace)?;
+        }
+        Ok(())
+    }
+}
+
+fn default_region() -> String {
+    "eu-west-1".to_string()
+}
+
+fn default_vsc_retain_policy() -> VSCRetainPolicy {
+    VSCRetainPolicy::Delete
+}
+
+fn default_max_concurrency() -> usize {
+    4
+}
+
+fn default_bind_timeout_secs() -> u64 {
+    600
+}
+
+/// A reference to a ConfigMap
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigMapReference {
+    /// Namespace of the ConfigMap, the namespace of the resource if not set
+    pub namespace: Option<String>,
+    /// Name of the ConfigMap
+    pub name: String,
+}
+
+impl ConfigMapReference {
+    /// The catalog location of the ConfigMap
+    ///
+    /// # Arguments
+    ///
+    /// * `default_namespace` - Namespace of the ConfigMap, if it does not set one
+    pub fn to_catalog_location(&self, default_namespace: &str) -> CatalogLocation {
+        CatalogLocation::ConfigMap {
+            namespace: self
+                .namespace
+                .clone()
+                .unwrap_or_else(|| default_namespace.to_string()),
+            name: self.name.clone(),
+        }
+    }
+
+    /// Check that the ConfigMap is in the namespace of the resource, if it sets one
+    pub fn validate_namespace(&self, namespace: &str) -> Result<()> {
+        match self.namespace.as_deref() {
+            Some(other) if other != namespace => bail!(
+                "The catalog ConfigMap must be in the namespace of the resource {}, not {}",
+                namespace,
+                other
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Represents the phase of a backup or restore request
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+pub enum OperationPhase {
+    #[default]
+    Pending,
+    Running,
+    Completed,
+    PartiallyFailed,
+    Failed,
+}
+
+impl OperationPhase {
+    /// Whether the request is done, successfully or not
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            OperationPhase::Completed | OperationPhase::PartiallyFailed | OperationPhase::Failed
+        )
+    }
+
+    /// The phase of a finished request, given how many of its PVCs succeeded and failed
+    pub fn from_outcome(succeeded: usize, failed: usize) -> Self {
+        match (succeeded, failed) {
+            (_, 0) => OperationPhase::Completed,
+            (0, _) => OperationPhase::Failed,
+            _ => OperationPhase::PartiallyFailed,
+        }
+    }
+}
+
+impl Display for OperationPhase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            OperationPhase::Pending => write!(f, "Pending"),
+            OperationPhase::Running => write!(f, "Running"),
+            OperationPhase::Completed => write!(f, "Completed"),
+            OperationPhase::PartiallyFailed => write!(f, "PartiallyFailed"),
+            OperationPhase::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+/// The status of a single PVC of a backup or restore request
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PvcOperationStatus {
+    pub namespace: String,
+    pub pvc_name: String,
+    pub phase: OperationPhase,
+    pub volume_snapshot_name: Option<String>,
+    pub snapshot_handle: Option<String>,
+    pub restore_size: Option<String>,
+    pub error: Option<String>,
+    /// How a PVC that already existed in the target namespace was handled
+    pub conflict: Option<String>,
+    /// Name of the restored PVC, if it differs from the name of the source PVC
+    pub restored_pvc_name: Option<String>,
+    /// The error of each region the EBS snapshot failed to be copied to
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failed_copies: BTreeMap<String, String>,
+}
+
+impl PvcOperationStatus {
+    /// The status of a PVC of a backup run
+    pub fn from_backup_result(result: &PvcBackupResult) -> Self {
+        let status = PvcOperationStatus {
+            namespace: result.namespace.clone(),
+            pvc_name: result.pvc_name.clone(),
+            ..Default::default()
+        };
+        match &result.status {
+            PvcBackupStatus::Completed(entry) => PvcOperationStatus {
+                phase: OperationPhase::Completed,
+                volume_snapshot_name: Some(entry.volume_snapshot_name.clone()),
+                snapshot_handle: Some(entry.snapshot_handle.clone()),
+                restore_size: Some(entry.restore_size.clone()),
+                ..status
+            },
+            PvcBackupStatus::CopiesFailed(entry, failed_copies) => PvcOperationStatus {
+                phase: OperationPhase::PartiallyFailed,
+                volume_snapshot_name: Some(entry.volume_snapshot_name.clone()),
+                snapshot_handle: Some(entry.snapshot_handle.clone()),
+                restore_size: Some(entry.restore_size.clone()),
+                failed_copies: failed_copies.clone(),
+                ..status
+            },
+            PvcBackupStatus::Failed(e) => PvcOperationStatus {
+                phase: OperationPhase::Failed,
+                error: Some(e.clone()),
+                ..status
+            },
+        }
+    }
+
+    /// The status of a PVC of a restore run
+    ///
+    /// # Arguments
+    ///
+    /// * `target_ns` - Namespace the PVC was restored to
+    /// * `result` - Outcome of the restore of the PVC
+    pub fn from_restore_result(target_ns: &str, result: &PvcRestoreResult) -> Self {
+        PvcOperationStatus {
+            namespace: target_ns.to_string(),
+            pvc_name: result.pvc_name.clone(),
+            phase: match result.status {
+                PvcRestoreStatus::Failed(_) => OperationPhase::Failed,
+                _ => OperationPhase::Completed,
+            },
+            error: match &result.status {
+                PvcRestoreStatus::Failed(e) => Some(e.clone()),
+                _ => None,
+            },
+            conflict: match &result.status {
+                PvcRestoreStatus::Skipped
+                | PvcRestoreStatus::Replaced
+                | PvcRestoreStatus::Renamed(_) => Some(result.status.to_string()),
+                _ => None,
+            },
+            restored_pvc_name: result
+                .restored_pvc_name()
+                .filter(|restored_pvc_name| *restored_pvc_name != result.pvc_name)
+                .map(str::to_string),
+            ..Default::default()
+        }
+    }
+}
+
+/// The status of a SnapKubeBackup
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapKubeBackupStatus {
+    pub phase: OperationPhase,
+    pub backup_id: Option<String>,
+    pub start_time: Option<String>,
+    pub completion_time: Option<String>,
+    pub message: Option<String>,
+    #[serde(default)]
+    pub pvcs: Vec<PvcOperationStatus>,
+}
+
+impl SnapKubeBackupStatus {
+    /// Fill the status with the outcome of a backup run
+    pub fn with_summary(mut self, backup_summary: &BackupSummary) -> Self {
+        self.phase = OperationPhase::from_outcome(
+            backup_summary.succeeded().count(),
+            backup_summary.failed().count(),
+        );
+        self.backup_id = Some(backup_summary.backup_id.clone());
+        self.pvcs = backup_summary
+            .results()
+            .map(PvcOperationStatus::from_backup_result)
+            .collect();
+        self.message = Some(format!(
+            "{} PVC(s) backed up, {} failed",
+            backup_summary.succeeded().count(),
+            backup_summary.failed().count()
+        ));
+        // The VolumeSnapshots are taken, but cannot be restored by backup ID
+        if let Some(catalog_error) = &backup_summary.catalog_error {
+            if self.phase == OperationPhase::Completed {
+                self.phase = OperationPhase::PartiallyFailed;
+            }
+            self.message = self.message.map(|message| {
+                format!("{}, failed to save the catalog: {}", message, catalog_error)
+            });
+        }
+        self
+    }
+}
+
+/// The status of a SnapKubeRestore
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapKubeRestoreStatus {
+    pub phase: OperationPhase,
+    pub start_time: Option<String>,
+    pub completion_time: Option<String>,
+    pub message: Option<String>,
+    #[serde(default)]
+    pub pvcs: Vec<PvcOperationStatus>,
+}
+
+impl SnapKubeRestoreStatus {
+    /// Fill the status with the outcome of a restore run
+    ///
+    /// # Arguments
+    ///
+    /// * `target_ns` - Namespace the PVCs were restored to
+    /// * `restore_summary` - Summary of the restore run
+    pub fn with_summary(mut self, target_ns: &str, restore_summary: &RestoreSummary) -> Self {
+        self.phase = OperationPhase::from_outcome(
+            restore_summary.succeeded().count(),
+            restore_summary.failed().count(),
+        );
+        self.pvcs = restore_summary
+            .results
+            .iter()
+            .map(|result| PvcOperationStatus::from_restore_result(target_ns, result))
+            .collect();
+        self.message = Some(format!(
+            "{} PVC(s) restored, {} failed",
+            restore_summary.succeeded().count(),
+            restore_summary.failed().count()
+        ));
+        self
+    }
+}
+
+/// The definitions of the SnapKubeBackup and SnapKubeRestore CRDs, as a multi-document YAML
+pub fn crds_yaml() -> anyhow::Result<String> {
+    use kube::CustomResourceExt;
+
+    Ok(format!(
+        "---\n{}---\n{}",
+        serde_yaml::to_string(&SnapKubeBackup::crd())?,
+        serde_yaml::to_string(&SnapKubeRestore::crd())?
+    ))
+}
@@ -0,0 +1,7 @@
+#[cfg(feature = "full")]
+pub mod controller_operator;
+#[cfg(feature = "full")]
+pub mod crds;
+
+#[cfg(all(test, feature = "full"))]
+mod controller_tests;
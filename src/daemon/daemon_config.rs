@@ -0,0 +1,134 @@
+use crate::backup::backup_spec::BackupSpec;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use serde::Deserialize;
+use std::{collections::BTreeSet, path::Path, str::FromStr};
+
+/// The configuration of the backup daemon, read from a YAML file
+///
+/// ```yaml
+/// schedules:
+///   - name: nightly-databases
+///     cron: "0 2 * * *"
+///     namespaceSelector: tier=database
+///     volumeSnapshotClass: ebs-vsc
+///     volumeSnapshotNamePrefix: nightly
+///     includeAllPvcs: true
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DaemonConfig {
+    pub schedules: Vec<BackupSchedule>,
+}
+
+impl DaemonConfig {
+    /// Parse and validate a daemon configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - YAML content of the configuration
+    ///
+    /// # Returns
+    ///
+    /// The daemon configuration, or an error if it is invalid
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let config: DaemonConfig = serde_yaml::from_str(content)?;
+        if config.schedules.is_empty() {
+            bail!("At least one schedule is required");
+        }
+        let mut names = BTreeSet::new();
+        for schedule in &config.schedules {
+            if !names.insert(schedule.name.as_str()) {
+                bail!("Duplicate schedule name: {}", schedule.name);
+            }
+            schedule
+                .validate()
+                .with_context(|| format!("Invalid schedule: {}", schedule.name))?;
+        }
+        Ok(config)
+    }
+
+    /// Read a daemon configuration from a YAML file
+    pub async fn from_file(path: &Path) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read the daemon config: {}", path.display()))?;
+        Self::from_yaml(&content)
+            .with_context(|| format!("Invalid daemon config: {}", path.display()))
+    }
+}
+
+/// A backup taken on a cron schedule
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSchedule {
+    /// Unique name of the schedule
+    pub name: String,
+    /// Cron expression, with either 5 fields or 6/7 fields starting with the seconds, in UTC
+    pub cron: String,
+    /// Whether to run once on start up, or after a long backup, if a scheduled run was missed
+    #[serde(default = "default_catch_up")]
+    pub catch_up: bool,
+    /// The backup taken on each run
+    #[serde(flatten)]
+    pub backup: BackupSpec,
+}
+
+fn default_catch_up() -> bool {
+    true
+}
+
+/// What the daemon should do next for a schedule
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NextRun {
+    /// Run right away, to catch up with a number of missed runs
+    CatchUp { missed: usize },
+    /// Wait for the next scheduled run
+    At(DateTime<Utc>),
+}
+
+impl BackupSchedule {
+    /// Check that the schedule can be turned into backup runs
+    pub fn validate(&self) -> Result<()> {
+        self.schedule()?;
+        self.backup.validate()
+    }
+
+    /// The parsed cron schedule
+    pub fn schedule(&self) -> Result<Schedule> {
+        let expression = if self.cron.split_whitespace().count() == 5 {
+            format!("0 {}", self.cron)
+        } else {
+            self.cron.clone()
+        };
+        Schedule::from_str(&expression)
+            .with_context(|| format!("Invalid cron expression: {}", self.cron))
+    }
+
+    /// Decide when the schedule should run next
+    ///
+    /// # Arguments
+    ///
+    /// * `last_run` - The last time the schedule was due, or the start up time if it never ran
+    /// * `now` - The current time
+    ///
+    /// # Returns
+    ///
+    /// Whether to catch up right away, or the time of the next scheduled run
+    pub fn next_run(&self, last_run: DateTime<Utc>, now: DateTime<Utc>) -> Result<NextRun> {
+        let schedule = self.schedule()?;
+        let missed = schedule
+            .after(&last_run)
+            .take_while(|time| *time <= now)
+            .count();
+        if missed > 0 && self.catch_up {
+            return Ok(NextRun::CatchUp { missed });
+        }
+        schedule
+            .after(&now)
+            .next()
+            .map(NextRun::At)
+            .with_context(|| format!("Schedule {} has no upcoming run", self.name))
+    }
+}
@@ -0,0 +1,261 @@
+use super::{
+    daemon_config::{BackupSchedule, DaemonConfig, NextRun},
+    daemon_state::DaemonState,
+    namespace_locks::NamespaceLocks,
+};
+use crate::{
+    backup::backup_operator::BackupOperator,
+    catalog::backup_catalog::CatalogLocation,
+    k8s_ops::ns::namespaces::{resolve_namespaces, KubeNamespaceApi},
+};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use kube::{Api, Client};
+use std::{future::Future, path::PathBuf, sync::Arc};
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::watch,
+    task::JoinSet,
+};
+use tracing::{error, info, warn};
+
+pub struct DaemonPayload {
+    pub config: DaemonConfig,
+    pub region: String,
+    pub catalog: Option<CatalogLocation>,
+    pub state_file: Option<PathBuf>,
+}
+
+impl DaemonPayload {
+    /// Creates a new DaemonPayload
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The backup schedules
+    /// * `region` - AWS region
+    /// * `catalog` - Where the backup catalog is stored, if any
+    /// * `state_file` - File where the last run of each schedule is persisted, if any
+    ///
+    /// # Returns
+    ///
+    /// A new DaemonPayload instance
+    pub fn new(
+        config: DaemonConfig,
+        region: impl Into<String>,
+        catalog: Option<CatalogLocation>,
+        state_file: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            config,
+            region: region.into(),
+            catalog,
+            state_file,
+        }
+    }
+}
+
+/// The state shared by the tasks of the schedules
+struct DaemonContext {
+    k8s_client: Client,
+    region: String,
+    catalog: Option<CatalogLocation>,
+    state: DaemonState,
+    locks: NamespaceLocks,
+}
+
+/// A struct for running the backup schedules as a long-running daemon
+pub struct DaemonOperator;
+
+impl DaemonOperator {
+    /// Runs the backup schedules until the shutdown future completes
+    ///
+    /// Each schedule runs in its own task. A namespace covered by several schedules is never
+    /// backed up twice at the same time: a run skips the namespaces that are being backed up
+    /// by another schedule. On shutdown, no new run is started and the running backups are
+    /// awaited, so that no VolumeSnapshot is left behind untracked. A running backup that has
+    /// scaled down workloads is interrupted instead, and scales them back up.
+    ///
+    /// # Arguments
+    ///
+    /// * `daemon_payload` - Payload of the daemon
+    /// * `shutdown` - Future that completes when the daemon should shut down
+    pub async fn run(
+        daemon_payload: DaemonPayload,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<()> {
+        let context = Arc::new(DaemonContext {
+            k8s_client: Client::try_default().await?,
+            region: daemon_payload.region,
+            catalog: daemon_payload.catalog,
+            state: DaemonState::load(daemon_payload.state_file).await?,
+            locks: NamespaceLocks::default(),
+        });
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let mut tasks = JoinSet::new();
+        for schedule in daemon_payload.config.schedules {
+            info!("Starting schedule {}: {}", schedule.name, schedule.cron);
+            tasks.spawn(Self::run_schedule(
+                context.clone(),
+                schedule,
+                shutdown_rx.clone(),
+            ));
+        }
+
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    info!("Shutting down, waiting for the running backups to complete...");
+                    let _ = shutdown_tx.send(true);
+                    break;
+                }
+                result = tasks.join_next() => match result {
+                    Some(result) => Self::log_schedule_exit(result),
+                    None => break,
+                },
+            }
+        }
+        while let Some(result) = tasks.join_next().await {
+            Self::log_schedule_exit(result);
+        }
+        info!("All the schedules are stopped");
+        Ok(())
+    }
+
+    /// Wait for a SIGTERM or SIGINT signal
+    pub async fn shutdown_signal() -> Result<()> {
+        let mut sigterm = signal(SignalKind::terminate())?;
+        tokio::select! {
+            _ = sigterm.recv() => info!("Received SIGTERM"),
+            _ = tokio::signal::ctrl_c() => info!("Received SIGINT"),
+        }
+        Ok(())
+    }
+
+    fn log_schedule_exit(result: Result<Result<()>, tokio::task::JoinError>) {
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("Schedule stopped: {:#}", e),
+            Err(e) => error!("Schedule task failed: {}", e),
+        }
+    }
+
+    /// Run a schedule until shutdown
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The state shared by the schedules
+    /// * `schedule` - The schedule to run
+    /// * `shutdown_rx` - Receiver of the shutdown notification
+    async fn run_schedule(
+        context: Arc<DaemonContext>,
+        schedule: BackupSchedule,
+        mut shutdown_rx: watch::Receiver<bool>,
+    ) -> Result<()> {
+        let mut last_run: DateTime<Utc> = context
+            .state
+            .last_run(&schedule.name)
+            .await
+            .unwrap_or_else(Utc::now);
+        loop {
+            let now = Utc::now();
+            let run_at = match schedule.next_run(last_run, now)? {
+                NextRun::CatchUp { missed } => {
+                    warn!(
+                        "Schedule {} missed {} run(s) since {}, catching up",
+                        schedule.name, missed, last_run
+                    );
+                    now
+                }
+                NextRun::At(run_at) => {
+                    info!("Next run of schedule {} at {}", schedule.name, run_at);
+                    let wait = (run_at - now).to_std().unwrap_or_default();
+                    tokio::select! {
+                        _ = tokio::time::sleep(wait) => run_at,
+                        _ = shutdown_rx.changed() => return Ok(()),
+                    }
+                }
+            };
+            if *shutdown_rx.borrow() {
+                return Ok(());
+            }
+
+            if let Err(e) = Self::run_backup(&context, &schedule, shutdown_rx.clone()).await {
+                error!("Backup of schedule {} failed: {:#}", schedule.name, e);
+            }
+            last_run = run_at;
+            if let Err(e) = context.state.record_run(&schedule.name, run_at).await {
+                error!(
+                    "Failed to record the run of schedule {}: {:#}",
+                    schedule.name, e
+                );
+            }
+        }
+    }
+
+    /// Run a single backup of a schedule
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The state shared by the schedules
+    /// * `schedule` - The schedule to run
+    /// * `shutdown_rx` - Receiver of the shutdown notification
+    async fn run_backup(
+        context: &DaemonContext,
+        schedule: &BackupSchedule,
+        mut shutdown_rx: watch::Receiver<bool>,
+    ) -> Result<()> {
+        info!("Running schedule: {}", schedule.name);
+        let namespaces = resolve_namespaces(
+            &KubeNamespaceApi {
+                api: Api::all(context.k8s_client.clone()),
+            },
+            &schedule.backup.namespace_selection()?,
+        )
+        .await?;
+
+        // Lock the namespaces for the duration of the backup
+        let (lock_guard, skipped) = context.locks.try_lock(namespaces);
+        if !skipped.is_empty() {
+            warn!(
+                "Schedule {} skips the namespaces that are already being backed up: {}",
+                schedule.name,
+                skipped.join(", ")
+            );
+        }
+        if lock_guard.namespaces().is_empty() {
+            info!("Schedule {} has no namespace to backup", schedule.name);
+            return Ok(());
+        }
+
+        let backup_payload = schedule.backup.to_backup_payload(
+            &context.region,
+            lock_guard.namespaces().to_vec(),
+            context.catalog.clone(),
+        )?;
+        let backup_summary = BackupOperator::backup(
+            backup_payload,
+            async move {
+                let _ = shutdown_rx.wait_for(|shutdown| *shutdown).await;
+            },
+            |_| {},
+        )
+        .await?;
+        info!("{}", backup_summary);
+        if let Some(catalog_error) = &backup_summary.catalog_error {
+            warn!(
+                "Schedule {} failed to save the catalog: {}",
+                schedule.name, catalog_error
+            );
+        }
+        if backup_summary.failed().next().is_some() {
+            warn!(
+                "Schedule {} failed to backup {} PVC(s)",
+                schedule.name,
+                backup_summary.failed().count()
+            );
+        }
+        Ok(())
+    }
+}
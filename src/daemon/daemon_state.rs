@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::PathBuf};
+use tokio::sync::Mutex;
+
+/// The last run of each schedule, persisted so that missed runs can be caught up on restart
+#[derive(Debug, Default)]
+pub struct DaemonState {
+    path: Option<PathBuf>,
+    last_runs: Mutex<BTreeMap<String, DateTime<Utc>>>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DaemonStateFile {
+    last_runs: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl DaemonState {
+    /// Load the daemon state from a JSON file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the state file, if any. Without one, the state is kept in memory only
+    ///
+    /// # Returns
+    ///
+    /// The daemon state, which is empty if the state file does not exist yet
+    pub async fn load(path: Option<PathBuf>) -> Result<Self> {
+        let last_runs = match &path {
+            Some(path) => match tokio::fs::read(path).await {
+                Ok(content) => {
+                    serde_json::from_slice::<DaemonStateFile>(&content)
+                        .with_context(|| format!("Invalid daemon state file: {}", path.display()))?
+                        .last_runs
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+                Err(e) => return Err(e.into()),
+            },
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            path,
+            last_runs: Mutex::new(last_runs),
+        })
+    }
+
+    /// The last time a schedule was due, if it ever ran
+    pub async fn last_run(&self, schedule_name: &str) -> Option<DateTime<Utc>> {
+        self.last_runs.lock().await.get(schedule_name).copied()
+    }
+
+    /// Record the last time a schedule was due, and persist it to the state file
+    pub async fn record_run(&self, schedule_name: &str, run_at: DateTime<Utc>) -> Result<()> {
+        let mut last_runs = self.last_runs.lock().await;
+        last_runs.insert(schedule_name.to_string(), run_at);
+        if let Some(path) = &self.path {
+            let state_file = DaemonStateFile {
+                last_runs: last_runs.clone(),
+            };
+            tokio::fs::write(path, serde_json::to_vec_pretty(&state_file)?).await?;
+        }
+        Ok(())
+    }
+}
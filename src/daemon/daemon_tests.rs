@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        daemon::{
+            daemon_config::{DaemonConfig, NextRun},
+            daemon_state::DaemonState,
+            namespace_locks::NamespaceLocks,
+        },
+        k8s_ops::ns::namespaces::NamespaceSelection,
+    };
+    use chrono::{DateTime, TimeZone, Utc};
+    use pretty_assertions::assert_eq;
+
+    const CONFIG: &str = r#"
+schedules:
+  - name: nightly-databases
+    cron: "0 2 * * *"
+    namespaceSelector: tier=database
+    volumeSnapshotClass: ebs-vsc
+    volumeSnapshotNamePrefix: nightly
+    includeAllPvcs: true
+  - name: hourly-cache
+    cron: "0 0 * * * *"
+    catchUp: false
+    namespaces: [cache]
+    volumeSnapshotClass: ebs-vsc
+    volumeSnapshotNamePrefix: hourly
+    selector: app=redis
+    maxConcurrency: 8
+"#;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 10, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_daemon_config_from_yaml() {
+        let config = DaemonConfig::from_yaml(CONFIG).unwrap();
+
+        assert_eq!(config.schedules.len(), 2);
+        let nightly = &config.schedules[0];
+        assert!(nightly.catch_up);
+        assert_eq!(nightly.backup.max_concurrency, 4);
+        assert_eq!(nightly.backup.snapshot_timeout_secs, 3600);
+        assert_eq!(
+            nightly.backup.namespace_selection().unwrap(),
+            NamespaceSelection::LabelSelector("tier=database".to_string())
+        );
+
+        let hourly = &config.schedules[1];
+        assert!(!hourly.catch_up);
+        let backup_payload = hourly
+            .backup
+            .to_backup_payload("eu-west-1", vec!["cache".to_string()], None)
+            .unwrap();
+        assert_eq!(backup_payload.max_concurrency(), 8);
+        assert_eq!(
+            backup_payload.pvc_selector().label_selector.as_deref(),
+            Some("app=redis")
+        );
+    }
+
+    #[test]
+    fn test_daemon_config_validation() {
+        let duplicate = format!(
+            "{}\n{}",
+            CONFIG,
+            &CONFIG[CONFIG.find("  - name: hourly").unwrap()..]
+        );
+        assert!(DaemonConfig::from_yaml(&duplicate).is_err());
+
+        let invalid_cron = CONFIG.replace("0 2 * * *", "every night");
+        assert!(DaemonConfig::from_yaml(&invalid_cron).is_err());
+
+        let no_pvcs = CONFIG.replace("    includeAllPvcs: true\n", "");
+        assert!(DaemonConfig::from_yaml(&no_pvcs).is_err());
+
+        assert!(DaemonConfig::from_yaml("schedules: []").is_err());
+    }
+
+    #[test]
+    fn test_next_run() {
+        let config = DaemonConfig::from_yaml(CONFIG).unwrap();
+        let nightly = &config.schedules[0];
+        let hourly = &config.schedules[1];
+
+        // The next run is at 02:00 of the next day
+        assert_eq!(
+            nightly.next_run(at(18, 2, 0), at(18, 12, 0)).unwrap(),
+            NextRun::At(at(19, 2, 0))
+        );
+        // The daemon was down for two nights, so it catches up once
+        assert_eq!(
+            nightly.next_run(at(16, 2, 0), at(18, 12, 0)).unwrap(),
+            NextRun::CatchUp { missed: 2 }
+        );
+        // Without catch up, the missed runs are skipped
+        assert_eq!(
+            hourly.next_run(at(18, 9, 0), at(18, 12, 30)).unwrap(),
+            NextRun::At(at(18, 13, 0))
+        );
+    }
+
+    #[test]
+    fn test_namespace_locks() {
+        let locks = NamespaceLocks::default();
+
+        let (first, skipped) = locks.try_lock(vec!["db".to_string(), "cache".to_string()]);
+        assert!(skipped.is_empty());
+        assert_eq!(first.namespaces(), ["db", "cache"]);
+
+        let (second, skipped) = locks.try_lock(vec!["db".to_string(), "web".to_string()]);
+        assert_eq!(skipped, vec!["db"]);
+        assert_eq!(second.namespaces(), ["web"]);
+
+        drop(first);
+        assert!(!locks.is_locked("db"));
+        assert!(locks.is_locked("web"));
+    }
+
+    #[tokio::test]
+    async fn test_daemon_state() {
+        let path = std::env::temp_dir().join(format!(
+            "snap-kube-daemon-state-test-{}.json",
+            std::process::id()
+        ));
+        let state = DaemonState::load(Some(path.clone())).await.unwrap();
+        assert_eq!(state.last_run("nightly-databases").await, None);
+
+        state
+            .record_run("nightly-databases", at(18, 2, 0))
+            .await
+            .unwrap();
+
+        let state = DaemonState::load(Some(path.clone())).await.unwrap();
+        assert_eq!(
+            state.last_run("nightly-databases").await,
+            Some(at(18, 2, 0))
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
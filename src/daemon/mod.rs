@@ -0,0 +1,11 @@
+#[cfg(feature = "backup")]
+pub mod daemon_config;
+#[cfg(feature = "backup")]
+pub mod daemon_operator;
+#[cfg(feature = "backup")]
+pub mod daemon_state;
+#[cfg(feature = "backup")]
+pub mod namespace_locks;
+
+#[cfg(all(test, feature = "backup"))]
+mod daemon_tests;
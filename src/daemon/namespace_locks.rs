@@ -0,0 +1,67 @@
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+};
+
+/// The namespaces being backed up by the daemon
+///
+/// A namespace is locked for the duration of a backup run, so that two schedules covering
+/// the same namespace never back it up at the same time.
+#[derive(Clone, Debug, Default)]
+pub struct NamespaceLocks {
+    locked: Arc<Mutex<BTreeSet<String>>>,
+}
+
+impl NamespaceLocks {
+    /// Lock the namespaces that are not locked already
+    ///
+    /// # Arguments
+    ///
+    /// * `namespaces` - The namespaces to lock
+    ///
+    /// # Returns
+    ///
+    /// A guard holding the namespaces that were locked, which unlocks them when dropped,
+    /// and the namespaces that were skipped since they were locked already
+    pub fn try_lock(&self, namespaces: Vec<String>) -> (NamespaceLockGuard, Vec<String>) {
+        let mut locked = self.locked.lock().unwrap();
+        let (acquired, skipped): (Vec<String>, Vec<String>) = namespaces
+            .into_iter()
+            .partition(|namespace| !locked.contains(namespace));
+        locked.extend(acquired.iter().cloned());
+        (
+            NamespaceLockGuard {
+                locks: self.clone(),
+                namespaces: acquired,
+            },
+            skipped,
+        )
+    }
+
+    /// Whether a namespace is locked
+    pub fn is_locked(&self, namespace: &str) -> bool {
+        self.locked.lock().unwrap().contains(namespace)
+    }
+}
+
+/// A guard over locked namespaces, unlocking them when dropped
+#[derive(Debug)]
+pub struct NamespaceLockGuard {
+    locks: NamespaceLocks,
+    namespaces: Vec<String>,
+}
+
+impl NamespaceLockGuard {
+    pub fn namespaces(&self) -> &[String] {
+        &self.namespaces
+    }
+}
+
+impl Drop for NamespaceLockGuard {
+    fn drop(&mut self) {
+        let mut locked = self.locks.locked.lock().unwrap();
+        for namespace in &self.namespaces {
+            locked.remove(namespace);
+        }
+    }
+}
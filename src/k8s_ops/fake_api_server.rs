@@ -0,0 +1,278 @@
+use http::{Method, Request, Response, StatusCode};
+use kube::{client::Body, Client};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+
+/// An in-memory Kubernetes API server, for testing the operators against a real client
+///
+/// The objects are stored by the path of their item, e.g.
+/// `/api/v1/namespaces/default/persistentvolumeclaims/data`. It supports getting, listing,
+/// creating, replacing, patching and deleting them, including their `status` subresource.
+/// Listing only honours equality and existence label selectors, the field selectors are
+/// ignored.
+#[derive(Clone, Default)]
+pub struct FakeApiServer {
+    objects: Arc<Mutex<BTreeMap<String, Value>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeApiServer {
+    /// Creates an empty API server
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a client of the API server, with a default namespace
+    ///
+    /// # Arguments
+    ///
+    /// * `default_namespace` - Default namespace of the client
+    pub fn client(&self, default_namespace: &str) -> Client {
+        let server = self.clone();
+        let service = tower::service_fn(move |request: Request<Body>| {
+            let server = server.clone();
+            async move { Ok::<_, Infallible>(server.handle(request).await) }
+        });
+        Client::new(service, default_namespace)
+    }
+
+    /// Stores an object as is
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the object
+    /// * `object` - The object
+    pub fn insert(&self, path: &str, object: Value) {
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), object);
+    }
+
+    /// Get a stored object
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the object
+    pub fn get(&self, path: &str) -> Option<Value> {
+        self.objects.lock().unwrap().get(path).cloned()
+    }
+
+    /// The requests served so far, as the method followed by the path, e.g.
+    /// `DELETE /api/v1/namespaces/default/persistentvolumeclaims/data`
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let method = request.method().clone();
+        let path = request.uri().path().trim_end_matches('/').to_string();
+        let query = request.uri().query().unwrap_or_default().to_string();
+        self.requests
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", method, path));
+        let body = request
+            .into_body()
+            .collect_bytes()
+            .await
+            .unwrap_or_default();
+        let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+        let Some((collection, name)) = parse_path(&path) else {
+            return status_response(StatusCode::NOT_FOUND, "NotFound", &path);
+        };
+        let mut objects = self.objects.lock().unwrap();
+        match (method, name) {
+            (Method::GET, None) => {
+                let selector = label_selector(&query);
+                let items: Vec<Value> = objects
+                    .iter()
+                    .filter(|(key, _)| {
+                        key.strip_prefix(&format!("{}/", collection))
+                            .is_some_and(|name| !name.contains('/'))
+                    })
+                    .map(|(_, object)| object.clone())
+                    .filter(|object| matches_labels(object, &selector))
+                    .collect();
+                json_response(
+                    StatusCode::OK,
+                    &json!({
+                        "apiVersion": "v1",
+                        "kind": "List",
+                        "metadata": { "resourceVersion": "" },
+                        "items": items,
+                    }),
+                )
+            }
+            (Method::POST, None) => {
+                let mut object = body;
+                let name = object["metadata"]["name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                let key = format!("{}/{}", collection, name);
+                if objects.contains_key(&key) {
+                    return status_response(StatusCode::CONFLICT, "AlreadyExists", &name);
+                }
+                if let Some(namespace) = namespace_of(&collection) {
+                    object["metadata"]["namespace"] = json!(namespace);
+                }
+                object["metadata"]["uid"] = json!(format!("uid-{}", name));
+                object["metadata"]["resourceVersion"] = json!("1");
+                objects.insert(key, object.clone());
+                json_response(StatusCode::CREATED, &object)
+            }
+            (method, Some(name)) => {
+                let key = format!("{}/{}", collection, name);
+                let Some(object) = objects.get(&key).cloned() else {
+                    return status_response(StatusCode::NOT_FOUND, "NotFound", &name);
+                };
+                match method {
+                    Method::GET => json_response(StatusCode::OK, &object),
+                    Method::DELETE => {
+                        objects.remove(&key);
+                        json_response(StatusCode::OK, &object)
+                    }
+                    Method::PUT => {
+                        objects.insert(key, body.clone());
+                        json_response(StatusCode::OK, &body)
+                    }
+                    Method::PATCH => {
+                        let mut object = object;
+                        merge_patch(&mut object, &body);
+                        objects.insert(key, object.clone());
+                        json_response(StatusCode::OK, &object)
+                    }
+                    _ => status_response(StatusCode::METHOD_NOT_ALLOWED, "MethodNotAllowed", &name),
+                }
+            }
+            _ => status_response(StatusCode::METHOD_NOT_ALLOWED, "MethodNotAllowed", &path),
+        }
+    }
+}
+
+/// Split the path of a request into the path of its collection, and the name of the object
+/// if it targets one, ignoring the subresource
+fn parse_path(path: &str) -> Option<(String, Option<String>)> {
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let prefix_len = match segments.first() {
+        Some(&"api") => 2,
+        Some(&"apis") => 3,
+        _ => return None,
+    };
+    let resource_start = match segments.get(prefix_len..prefix_len + 3) {
+        Some([namespaces, _, _]) if *namespaces == "namespaces" => prefix_len + 2,
+        _ => prefix_len,
+    };
+    let collection = segments.get(..=resource_start)?.join("/");
+    Some((
+        format!("/{}", collection),
+        segments
+            .get(resource_start + 1)
+            .map(|name| name.to_string()),
+    ))
+}
+
+/// The namespace of a collection path, if namespaced
+fn namespace_of(collection: &str) -> Option<String> {
+    let segments: Vec<&str> = collection.split('/').collect();
+    segments
+        .iter()
+        .position(|segment| *segment == "namespaces")
+        .filter(|position| position + 2 < segments.len())
+        .map(|position| segments[position + 1].to_string())
+}
+
+/// The label selector of a query, as label keys and optional values
+fn label_selector(query: &str) -> Vec<(String, Option<String>)> {
+    query
+        .split('&')
+        .filter_map(|param| param.strip_prefix("labelSelector="))
+        .flat_map(|selector| {
+            percent_decode(selector)
+                .split(',')
+                .filter(|requirement| !requirement.is_empty())
+                .map(|requirement| match requirement.split_once('=') {
+                    Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                    None => (requirement.to_string(), None),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Decode a percent-encoded query parameter
+fn percent_decode(value: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut chars = value.bytes();
+    while let Some(byte) = chars.next() {
+        match byte {
+            b'%' => {
+                let hex: Vec<u8> = chars.by_ref().take(2).collect();
+                let hex = String::from_utf8_lossy(&hex).to_string();
+                bytes.push(u8::from_str_radix(&hex, 16).unwrap_or_default());
+            }
+            b'+' => bytes.push(b' '),
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Whether the labels of an object match a label selector
+fn matches_labels(object: &Value, selector: &[(String, Option<String>)]) -> bool {
+    selector.iter().all(|(key, value)| {
+        let label = &object["metadata"]["labels"][key];
+        match value {
+            Some(value) => label.as_str() == Some(value),
+            None => !label.is_null(),
+        }
+    })
+}
+
+/// Apply a JSON merge patch to an object
+fn merge_patch(object: &mut Value, patch: &Value) {
+    match patch {
+        Value::Object(fields) => {
+            if !object.is_object() {
+                *object = json!({});
+            }
+            for (key, value) in fields {
+                if value.is_null() {
+                    object.as_object_mut().unwrap().remove(key);
+                } else {
+                    merge_patch(&mut object[key], value);
+                }
+            }
+        }
+        _ => *object = patch.clone(),
+    }
+}
+
+fn json_response(status: StatusCode, value: &Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(value).unwrap()))
+        .unwrap()
+}
+
+fn status_response(status: StatusCode, reason: &str, name: &str) -> Response<Body> {
+    json_response(
+        status,
+        &json!({
+            "kind": "Status",
+            "apiVersion": "v1",
+            "metadata": {},
+            "status": "Failure",
+            "message": format!("{}: {}", reason, name),
+            "reason": reason,
+            "code": status.as_u16(),
+        }),
+    )
+}
@@ -1,3 +1,12 @@
+pub mod ns;
+pub mod pod;
+pub mod pv;
 pub mod pvc;
+pub mod sc;
+pub mod vgs;
 pub mod vs;
 pub mod vsc;
+pub mod workload;
+
+#[cfg(test)]
+pub(crate) mod fake_api_server;
@@ -0,0 +1,4 @@
+pub mod namespaces;
+
+#[cfg(test)]
+mod namespaces_tests;
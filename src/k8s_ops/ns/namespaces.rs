@@ -0,0 +1,81 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::Namespace;
+use kube::{api::ListParams, Api};
+use tracing::info;
+
+#[cfg(test)]
+use mockall::automock;
+
+/// Represents the namespaces that an operation covers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NamespaceSelection {
+    /// An explicit list of namespaces
+    List(Vec<String>),
+    /// The namespaces matching a label selector
+    LabelSelector(String),
+    /// All the namespaces of the cluster, except the excluded ones
+    All { exclude: Vec<String> },
+}
+
+impl NamespaceSelection {
+    /// A selection of a single namespace
+    pub fn single(namespace: impl Into<String>) -> Self {
+        NamespaceSelection::List(vec![namespace.into()])
+    }
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait NamespaceApiTrait {
+    async fn list_namespaces(&self, list_params: &ListParams) -> Result<Vec<Namespace>>;
+}
+
+pub struct KubeNamespaceApi {
+    pub api: Api<Namespace>,
+}
+
+/// Implement the NamespaceApiTrait for Namespace Api, so that it can be mocked in tests
+#[async_trait]
+impl NamespaceApiTrait for KubeNamespaceApi {
+    async fn list_namespaces(&self, list_params: &ListParams) -> Result<Vec<Namespace>> {
+        let namespaces = self.api.list(list_params).await?;
+        Ok(namespaces.items)
+    }
+}
+
+/// Resolve a NamespaceSelection to the names of the namespaces it covers
+///
+/// # Arguments
+///
+/// * `ns_api` - Api object for Namespace
+/// * `namespace_selection` - The namespaces to resolve
+///
+/// # Returns
+///
+/// Sorted and deduplicated namespace names
+pub async fn resolve_namespaces(
+    ns_api: &impl NamespaceApiTrait,
+    namespace_selection: &NamespaceSelection,
+) -> Result<Vec<String>> {
+    let mut namespaces: Vec<String> = match namespace_selection {
+        NamespaceSelection::List(namespaces) => namespaces.clone(),
+        NamespaceSelection::LabelSelector(label_selector) => ns_api
+            .list_namespaces(&ListParams::default().labels(label_selector))
+            .await?
+            .into_iter()
+            .filter_map(|ns| ns.metadata.name)
+            .collect(),
+        NamespaceSelection::All { exclude } => ns_api
+            .list_namespaces(&ListParams::default())
+            .await?
+            .into_iter()
+            .filter_map(|ns| ns.metadata.name)
+            .filter(|ns| !exclude.contains(ns))
+            .collect(),
+    };
+    namespaces.sort();
+    namespaces.dedup();
+    info!("Namespaces selected: {:?}", namespaces);
+    Ok(namespaces)
+}
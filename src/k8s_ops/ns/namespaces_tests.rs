@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use crate::k8s_ops::ns::namespaces::{
+        resolve_namespaces, MockNamespaceApiTrait, NamespaceSelection,
+    };
+    use k8s_openapi::api::core::v1::Namespace;
+    use kube::api::{ListParams, ObjectMeta};
+    use mockall::predicate;
+    use pretty_assertions::assert_eq;
+
+    fn namespaces(names: &[&str]) -> Vec<Namespace> {
+        names
+            .iter()
+            .map(|name| Namespace {
+                metadata: ObjectMeta {
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_namespaces_list() {
+        let mut mock_ns_api = MockNamespaceApiTrait::new();
+        mock_ns_api.expect_list_namespaces().times(0);
+
+        let result = resolve_namespaces(
+            &mock_ns_api,
+            &NamespaceSelection::List(vec![
+                "tenant-b".to_string(),
+                "tenant-a".to_string(),
+                "tenant-b".to_string(),
+            ]),
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), vec!["tenant-a", "tenant-b"]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_namespaces_label_selector() {
+        let mut mock_ns_api = MockNamespaceApiTrait::new();
+        mock_ns_api
+            .expect_list_namespaces()
+            .with(predicate::eq(ListParams::default().labels("tier=tenant")))
+            .times(1)
+            .returning(|_| Ok(namespaces(&["tenant-a", "tenant-b"])));
+
+        let result = resolve_namespaces(
+            &mock_ns_api,
+            &NamespaceSelection::LabelSelector("tier=tenant".to_string()),
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), vec!["tenant-a", "tenant-b"]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_namespaces_all_with_exclusions() {
+        let mut mock_ns_api = MockNamespaceApiTrait::new();
+        mock_ns_api
+            .expect_list_namespaces()
+            .with(predicate::eq(ListParams::default()))
+            .times(1)
+            .returning(|_| Ok(namespaces(&["default", "kube-system", "tenant-a"])));
+
+        let result = resolve_namespaces(
+            &mock_ns_api,
+            &NamespaceSelection::All {
+                exclude: vec!["kube-system".to_string()],
+            },
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), vec!["default", "tenant-a"]);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod pods;
+
+#[cfg(test)]
+mod pods_tests;
@@ -0,0 +1,169 @@
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use k8s_openapi::api::core::v1::Pod;
+use kube::{
+    api::{AttachParams, ListParams},
+    Api,
+};
+use tokio::io::AsyncReadExt;
+use tracing::info;
+
+#[cfg(test)]
+use mockall::automock;
+
+/// The output of a command executed in a container
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait PodApiTrait {
+    async fn list_pods(&self, list_params: &ListParams) -> Result<Vec<Pod>>;
+    async fn exec(&self, pod: &str, container: &str, command: &[String]) -> Result<ExecOutput>;
+}
+
+pub struct KubePodApi {
+    pub api: Api<Pod>,
+}
+
+/// Implement the PodApiTrait for Pod Api, so that it can be mocked in tests
+#[async_trait]
+impl PodApiTrait for KubePodApi {
+    async fn list_pods(&self, list_params: &ListParams) -> Result<Vec<Pod>> {
+        let pods = self.api.list(list_params).await?;
+        Ok(pods.items)
+    }
+
+    async fn exec(&self, pod: &str, container: &str, command: &[String]) -> Result<ExecOutput> {
+        let attach_params = AttachParams::default()
+            .container(container)
+            .stdin(false)
+            .stdout(true)
+            .stderr(true);
+        let mut attached = self
+            .api
+            .exec(pod, command.to_vec(), &attach_params)
+            .await
+            .with_context(|| format!("Failed to exec in pod {}", pod))?;
+
+        let mut output = ExecOutput::default();
+        if let Some(mut stdout) = attached.stdout() {
+            stdout.read_to_string(&mut output.stdout).await?;
+        }
+        if let Some(mut stderr) = attached.stderr() {
+            stderr.read_to_string(&mut output.stderr).await?;
+        }
+        let status = match attached.take_status() {
+            Some(status) => status.await,
+            None => None,
+        };
+        attached.join().await?;
+
+        match status {
+            Some(status) if status.status.as_deref() != Some("Success") => bail!(
+                "Command {:?} failed in pod {}: {} {}",
+                command,
+                pod,
+                status.message.unwrap_or_default(),
+                output.stderr.trim()
+            ),
+            _ => Ok(output),
+        }
+    }
+}
+
+/// Get the running pods that mount a PVC
+///
+/// # Arguments
+///
+/// * `pod_api` - Api object for Pod
+/// * `pvc_name` - Name of the PVC
+///
+/// # Returns
+///
+/// The running pods mounting the PVC
+pub async fn get_pods_mounting_pvc(pod_api: &impl PodApiTrait, pvc_name: &str) -> Result<Vec<Pod>> {
+    list_pods_mounting_pvc(
+        pod_api,
+        &ListParams::default().fields("status.phase=Running"),
+        pvc_name,
+    )
+    .await
+}
+
+/// Get the pods that are not terminated and mount a PVC
+///
+/// Unlike [`get_pods_mounting_pvc`], the pending pods are included, e.g. the ones whose
+/// containers are still being created, as they use the PVC as well.
+///
+/// # Arguments
+///
+/// * `pod_api` - Api object for Pod
+/// * `pvc_name` - Name of the PVC
+///
+/// # Returns
+///
+/// The pods that are not Succeeded or Failed and mount the PVC
+pub async fn get_pods_using_pvc(pod_api: &impl PodApiTrait, pvc_name: &str) -> Result<Vec<Pod>> {
+    list_pods_mounting_pvc(
+        pod_api,
+        &ListParams::default().fields("status.phase!=Succeeded,status.phase!=Failed"),
+        pvc_name,
+    )
+    .await
+}
+
+/// List the pods matching the list parameters that mount a PVC
+async fn list_pods_mounting_pvc(
+    pod_api: &impl PodApiTrait,
+    list_params: &ListParams,
+    pvc_name: &str,
+) -> Result<Vec<Pod>> {
+    let pods: Vec<Pod> = pod_api
+        .list_pods(list_params)
+        .await?
+        .into_iter()
+        .filter(|pod| {
+            pod.spec
+                .as_ref()
+                .and_then(|spec| spec.volumes.as_ref())
+                .is_some_and(|volumes| {
+                    volumes.iter().any(|volume| {
+                        volume
+                            .persistent_volume_claim
+                            .as_ref()
+                            .is_some_and(|claim| claim.claim_name == pvc_name)
+                    })
+                })
+        })
+        .collect();
+    info!(
+        "Pods mounting PVC {}: {:?}",
+        pvc_name,
+        pods.iter()
+            .map(|pod| pod.metadata.name.clone().unwrap_or_default())
+            .collect::<Vec<_>>()
+    );
+    Ok(pods)
+}
+
+/// Annotation naming the container that kubectl execs into by default
+pub const DEFAULT_CONTAINER_ANNOTATION: &str = "kubectl.kubernetes.io/default-container";
+
+/// Get the container to exec into when none is set, as kubectl would pick it
+pub fn default_container(pod: &Pod) -> Option<String> {
+    pod.metadata
+        .annotations
+        .as_ref()
+        .and_then(|annotations| annotations.get(DEFAULT_CONTAINER_ANNOTATION))
+        .cloned()
+        .or_else(|| {
+            pod.spec
+                .as_ref()
+                .and_then(|spec| spec.containers.first())
+                .map(|container| container.name.clone())
+        })
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use crate::k8s_ops::pod::pods::{
+        default_container, get_pods_mounting_pvc, get_pods_using_pvc, MockPodApiTrait,
+        DEFAULT_CONTAINER_ANNOTATION,
+    };
+    use k8s_openapi::api::core::v1::{
+        Container, PersistentVolumeClaimVolumeSource, Pod, PodSpec, Volume,
+    };
+    use kube::api::ObjectMeta;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    fn pod(name: &str, pvc_names: &[&str]) -> Pod {
+        Pod {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                ..Default::default()
+            },
+            spec: Some(PodSpec {
+                containers: vec![
+                    Container {
+                        name: "app".to_string(),
+                        ..Default::default()
+                    },
+                    Container {
+                        name: "sidecar".to_string(),
+                        ..Default::default()
+                    },
+                ],
+                volumes: Some(
+                    pvc_names
+                        .iter()
+                        .map(|pvc_name| Volume {
+                            name: format!("volume-{}", pvc_name),
+                            persistent_volume_claim: Some(PersistentVolumeClaimVolumeSource {
+                                claim_name: pvc_name.to_string(),
+                                read_only: None,
+                            }),
+                            ..Default::default()
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_pods_mounting_pvc() {
+        let mut mock_pod_api = MockPodApiTrait::new();
+        mock_pod_api
+            .expect_list_pods()
+            .withf(|list_params| {
+                list_params.field_selector.as_deref() == Some("status.phase=Running")
+            })
+            .returning(|_| {
+                Ok(vec![
+                    pod("postgres-0", &["data", "wal"]),
+                    pod("redis-0", &["cache"]),
+                    pod("postgres-1", &["data"]),
+                ])
+            });
+
+        let pods = get_pods_mounting_pvc(&mock_pod_api, "data").await.unwrap();
+
+        assert_eq!(
+            pods.iter()
+                .map(|pod| pod.metadata.name.clone().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["postgres-0", "postgres-1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_pods_using_pvc() {
+        let mut mock_pod_api = MockPodApiTrait::new();
+        mock_pod_api
+            .expect_list_pods()
+            .withf(|list_params| {
+                list_params.field_selector.as_deref()
+                    == Some("status.phase!=Succeeded,status.phase!=Failed")
+            })
+            .returning(|_| {
+                Ok(vec![
+                    pod("postgres-0", &["data"]),
+                    pod("redis-0", &["cache"]),
+                ])
+            });
+
+        let pods = get_pods_using_pvc(&mock_pod_api, "data").await.unwrap();
+
+        assert_eq!(pods.len(), 1);
+        assert_eq!(pods[0].metadata.name.as_deref(), Some("postgres-0"));
+    }
+
+    #[test]
+    fn test_default_container() {
+        let mut pod = pod("postgres-0", &["data"]);
+        assert_eq!(default_container(&pod), Some("app".to_string()));
+
+        pod.metadata.annotations = Some(BTreeMap::from([(
+            DEFAULT_CONTAINER_ANNOTATION.to_string(),
+            "sidecar".to_string(),
+        )]));
+        assert_eq!(default_container(&pod), Some("sidecar".to_string()));
+    }
+}
@@ -0,0 +1,9 @@
+#[cfg(feature = "restore")]
+pub mod persistent_volumes;
+#[cfg(feature = "restore")]
+pub mod reclaim_policy;
+
+#[cfg(all(test, feature = "restore"))]
+mod persistent_volumes_tests;
+#[cfg(all(test, feature = "restore"))]
+mod reclaim_policy_tests;
@@ -0,0 +1,177 @@
+use crate::k8s_ops::{
+    pvc::pvc_template::PvcTemplate,
+    sc::storage_classes::{EBS_CSI_DRIVER, ZONE_TOPOLOGY_KEYS},
+};
+use k8s_openapi::{
+    api::core::v1::{
+        CSIPersistentVolumeSource, NodeSelector, NodeSelectorRequirement, NodeSelectorTerm,
+        ObjectReference, PersistentVolume, PersistentVolumeClaim, PersistentVolumeClaimSpec,
+        PersistentVolumeSpec, VolumeNodeAffinity, VolumeResourceRequirements,
+    },
+    apimachinery::pkg::api::resource::Quantity,
+};
+use kube::api::ObjectMeta;
+use std::collections::BTreeMap;
+
+/// Label holding the ID of the EBS volume of a statically provisioned PersistentVolume
+pub const EBS_VOLUME_ID_LABEL: &str = "snap-kube/ebs-volume-id";
+
+/// Access mode of the volume when the template of the PVC sets none
+const DEFAULT_ACCESS_MODE: &str = "ReadWriteOnce";
+
+/// Volume mode of the volume when the template of the PVC sets none
+const DEFAULT_VOLUME_MODE: &str = "Filesystem";
+
+/// The access modes of a PVC template, or the default one
+fn access_modes(pvc_template: &PvcTemplate) -> Vec<String> {
+    pvc_template
+        .access_modes()
+        .unwrap_or_else(|| vec![DEFAULT_ACCESS_MODE.to_string()])
+}
+
+/// The volume mode of a PVC template, or the default one
+fn volume_mode(pvc_template: &PvcTemplate) -> String {
+    pvc_template
+        .volume_mode
+        .clone()
+        .unwrap_or_else(|| DEFAULT_VOLUME_MODE.to_string())
+}
+
+/// A statically provisioned EBS volume, restored from a snapshot without the CSI snapshot
+/// controller
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaticVolume {
+    /// Name of the PersistentVolume
+    pub pv_name: String,
+    /// ID of the EBS volume, e.g. `vol-0123456789abcdef0`
+    pub volume_id: String,
+    /// Availability zone of the EBS volume
+    pub availability_zone: String,
+    /// Size of the volume, e.g. `100Gi`
+    pub size: String,
+    /// Name of the StorageClass of the PersistentVolume and of its PVC
+    pub storage_class: String,
+    /// Filesystem of the volume, ignored for the raw block volumes
+    pub fs_type: String,
+    /// Reclaim policy of the PersistentVolume, `Retain` or `Delete`
+    pub reclaim_policy: String,
+}
+
+impl StaticVolume {
+    /// Construct the PersistentVolume of the EBS volume, reserved for a PVC
+    ///
+    /// The PersistentVolume is pinned to the availability zone of the volume, since an EBS
+    /// volume can only be attached to the nodes of its zone.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the PVC
+    /// * `pvc_name` - Name of the PVC
+    /// * `pvc_template` - Access modes and volume mode of the PVC
+    ///
+    /// # Returns
+    ///
+    /// PersistentVolume resource
+    pub fn construct_persistent_volume_resource(
+        &self,
+        namespace: &str,
+        pvc_name: &str,
+        pvc_template: &PvcTemplate,
+    ) -> PersistentVolume {
+        PersistentVolume {
+            metadata: ObjectMeta {
+                name: Some(self.pv_name.clone()),
+                labels: Some(BTreeMap::from([(
+                    EBS_VOLUME_ID_LABEL.to_string(),
+                    self.volume_id.clone(),
+                )])),
+                ..Default::default()
+            },
+            spec: Some(PersistentVolumeSpec {
+                access_modes: Some(access_modes(pvc_template)),
+                capacity: Some(BTreeMap::from([(
+                    "storage".to_string(),
+                    Quantity(self.size.clone()),
+                )])),
+                csi: Some(CSIPersistentVolumeSource {
+                    driver: EBS_CSI_DRIVER.to_string(),
+                    volume_handle: self.volume_id.clone(),
+                    fs_type: (!pvc_template.is_block()).then(|| self.fs_type.clone()),
+                    ..Default::default()
+                }),
+                claim_ref: Some(ObjectReference {
+                    namespace: Some(namespace.to_string()),
+                    name: Some(pvc_name.to_string()),
+                    ..Default::default()
+                }),
+                node_affinity: Some(VolumeNodeAffinity {
+                    required: Some(NodeSelector {
+                        node_selector_terms: ZONE_TOPOLOGY_KEYS
+                            .iter()
+                            .map(|key| NodeSelectorTerm {
+                                match_expressions: Some(vec![NodeSelectorRequirement {
+                                    key: key.to_string(),
+                                    operator: "In".to_string(),
+                                    values: Some(vec![self.availability_zone.clone()]),
+                                }]),
+                                ..Default::default()
+                            })
+                            .collect(),
+                    }),
+                }),
+                persistent_volume_reclaim_policy: Some(self.reclaim_policy.clone()),
+                storage_class_name: Some(self.storage_class.clone()),
+                volume_mode: Some(volume_mode(pvc_template)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Construct the PVC bound to the PersistentVolume of the EBS volume
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the PVC
+    /// * `pvc_name` - Name of the PVC
+    /// * `pvc_template` - Access modes, volume mode, labels and annotations of the PVC
+    ///
+    /// # Returns
+    ///
+    /// PersistentVolumeClaim resource
+    pub fn construct_persistent_volume_claim_resource(
+        &self,
+        namespace: &str,
+        pvc_name: &str,
+        pvc_template: &PvcTemplate,
+    ) -> PersistentVolumeClaim {
+        let mut labels = pvc_template.labels.clone();
+        labels.insert(EBS_VOLUME_ID_LABEL.to_string(), self.volume_id.clone());
+
+        PersistentVolumeClaim {
+            metadata: ObjectMeta {
+                name: Some(pvc_name.to_string()),
+                namespace: Some(namespace.to_string()),
+                labels: Some(labels),
+                annotations: (!pvc_template.annotations.is_empty())
+                    .then(|| pvc_template.annotations.clone()),
+                ..Default::default()
+            },
+            spec: Some(PersistentVolumeClaimSpec {
+                access_modes: Some(access_modes(pvc_template)),
+                storage_class_name: Some(self.storage_class.clone()),
+                volume_mode: Some(volume_mode(pvc_template)),
+                volume_name: Some(self.pv_name.clone()),
+                resources: Some(VolumeResourceRequirements {
+                    requests: Some(BTreeMap::from([(
+                        "storage".to_string(),
+                        Quantity(self.size.clone()),
+                    )])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+}
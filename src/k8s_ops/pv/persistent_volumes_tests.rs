@@ -0,0 +1,121 @@
+#[cfg(test)]
+mod tests {
+    use crate::k8s_ops::{
+        pv::persistent_volumes::{StaticVolume, EBS_VOLUME_ID_LABEL},
+        pvc::pvc_template::PvcTemplate,
+    };
+    use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    fn static_volume() -> StaticVolume {
+        StaticVolume {
+            pv_name: "data-postgres-0-vol-0123456789abcdef0".to_string(),
+            volume_id: "vol-0123456789abcdef0".to_string(),
+            availability_zone: "eu-west-1a".to_string(),
+            size: "100Gi".to_string(),
+            storage_class: "ebs-sc".to_string(),
+            fs_type: "xfs".to_string(),
+            reclaim_policy: "Retain".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_construct_persistent_volume_resource() {
+        let pv = static_volume().construct_persistent_volume_resource(
+            "target-ns",
+            "data-postgres-0",
+            &PvcTemplate::default(),
+        );
+        assert_eq!(
+            pv.metadata.name.unwrap(),
+            "data-postgres-0-vol-0123456789abcdef0"
+        );
+        assert_eq!(
+            pv.metadata.labels.unwrap().get(EBS_VOLUME_ID_LABEL),
+            Some(&"vol-0123456789abcdef0".to_string())
+        );
+
+        let spec = pv.spec.unwrap();
+        let csi = spec.csi.unwrap();
+        assert_eq!(csi.driver, "ebs.csi.aws.com");
+        assert_eq!(csi.volume_handle, "vol-0123456789abcdef0");
+        assert_eq!(csi.fs_type.unwrap(), "xfs");
+        assert_eq!(
+            spec.capacity.unwrap().get("storage"),
+            Some(&Quantity("100Gi".to_string()))
+        );
+        let claim_ref = spec.claim_ref.unwrap();
+        assert_eq!(claim_ref.namespace.unwrap(), "target-ns");
+        assert_eq!(claim_ref.name.unwrap(), "data-postgres-0");
+        assert_eq!(spec.persistent_volume_reclaim_policy.unwrap(), "Retain");
+        assert_eq!(spec.storage_class_name.unwrap(), "ebs-sc");
+        assert_eq!(
+            spec.access_modes.unwrap(),
+            vec!["ReadWriteOnce".to_string()]
+        );
+        assert_eq!(spec.volume_mode.unwrap(), "Filesystem");
+
+        // Each zone topology key is enough for a node to match
+        let node_selector_terms = spec
+            .node_affinity
+            .unwrap()
+            .required
+            .unwrap()
+            .node_selector_terms;
+        assert_eq!(node_selector_terms.len(), 2);
+        for term in node_selector_terms {
+            let requirement = &term.match_expressions.unwrap()[0];
+            assert_eq!(requirement.operator, "In");
+            assert_eq!(
+                requirement.values.clone().unwrap(),
+                vec!["eu-west-1a".to_string()]
+            );
+        }
+    }
+
+    #[test]
+    fn test_construct_persistent_volume_claim_resource() {
+        let pvc_template = PvcTemplate {
+            access_modes: vec!["ReadWriteOncePod".to_string()],
+            volume_mode: Some("Block".to_string()),
+            labels: BTreeMap::from([("app".to_string(), "postgres".to_string())]),
+            annotations: BTreeMap::from([("team".to_string(), "payments".to_string())]),
+        };
+        let pvc = static_volume().construct_persistent_volume_claim_resource(
+            "target-ns",
+            "data-postgres-0",
+            &pvc_template,
+        );
+        assert_eq!(pvc.metadata.name.unwrap(), "data-postgres-0");
+        assert_eq!(pvc.metadata.namespace.unwrap(), "target-ns");
+        assert_eq!(
+            pvc.metadata.labels.unwrap(),
+            BTreeMap::from([
+                ("app".to_string(), "postgres".to_string()),
+                (
+                    EBS_VOLUME_ID_LABEL.to_string(),
+                    "vol-0123456789abcdef0".to_string()
+                ),
+            ])
+        );
+        assert_eq!(pvc.metadata.annotations.unwrap(), pvc_template.annotations);
+
+        let spec = pvc.spec.unwrap();
+        assert_eq!(
+            spec.volume_name.unwrap(),
+            "data-postgres-0-vol-0123456789abcdef0"
+        );
+        assert_eq!(spec.storage_class_name.unwrap(), "ebs-sc");
+        assert!(spec.data_source.is_none());
+        assert_eq!(
+            spec.access_modes.unwrap(),
+            vec!["ReadWriteOncePod".to_string()]
+        );
+        assert_eq!(spec.volume_mode.unwrap(), "Block");
+        assert_eq!(
+            spec.resources.unwrap().requests.unwrap().get("storage"),
+            Some(&Quantity("100Gi".to_string()))
+        );
+    }
+}
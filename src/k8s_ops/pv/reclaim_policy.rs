@@ -0,0 +1,22 @@
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// Represents the reclaim policy of a statically provisioned PersistentVolume
+///
+/// It can be either Retain or Delete
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub enum PVReclaimPolicy {
+    Retain,
+    Delete,
+}
+
+impl Display for PVReclaimPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PVReclaimPolicy::Retain => write!(f, "Retain"),
+            PVReclaimPolicy::Delete => write!(f, "Delete"),
+        }
+    }
+}
@@ -0,0 +1,10 @@
+#[cfg(test)]
+mod tests {
+    use crate::k8s_ops::pv::reclaim_policy::PVReclaimPolicy;
+
+    #[test]
+    fn test_pv_reclaim_policy_display() {
+        assert_eq!(PVReclaimPolicy::Retain.to_string(), "Retain");
+        assert_eq!(PVReclaimPolicy::Delete.to_string(), "Delete");
+    }
+}
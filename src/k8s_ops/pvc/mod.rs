@@ -3,6 +3,9 @@ pub mod persistent_volume_claims;
 pub mod persistent_volume_claims_operator;
 #[cfg(feature = "restore")]
 pub mod persistent_volume_claims_payload;
+pub mod pvc_rename;
+pub mod pvc_selector;
+pub mod pvc_template;
 
 #[cfg(test)]
 mod persistent_volume_claims_tests;
@@ -1,18 +1,36 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
 use k8s_openapi::api::core::v1::PersistentVolumeClaim;
-use kube::{api::ListParams, Api};
+use kube::{
+    api::{DeleteParams, ListParams},
+    Api,
+};
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
 use tracing::info;
 
+use super::pvc_selector::PvcSelector;
+
 #[cfg(test)]
 use mockall::automock;
 
+/// Interval between two checks of the phase of the restored PVCs
+const PVC_BOUND_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Interval between two checks of a deleted PVC
+const PVC_DELETED_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Maximum number of suffixed names tried for a renamed PVC
+const MAX_RENAME_ATTEMPTS: usize = 100;
+
 #[cfg_attr(test, automock)]
 #[async_trait]
 pub trait PvcApiTrait {
-    async fn list_pvcs(&self) -> Result<Vec<PersistentVolumeClaim>>;
+    async fn list_pvcs(&self, list_params: &ListParams) -> Result<Vec<PersistentVolumeClaim>>;
     async fn get(&self, name: &str) -> Result<PersistentVolumeClaim>;
     async fn create(&self, pvc: PersistentVolumeClaim) -> Result<PersistentVolumeClaim>;
+    async fn get_opt(&self, name: &str) -> Result<Option<PersistentVolumeClaim>>;
+    async fn delete(&self, name: &str) -> Result<()>;
 }
 
 pub struct KubePvcApi {
@@ -27,8 +45,8 @@ pub struct KubePvcApi {
 /// calls to the Kubernetes API.
 #[async_trait]
 impl PvcApiTrait for KubePvcApi {
-    async fn list_pvcs(&self) -> Result<Vec<PersistentVolumeClaim>> {
-        let pvcs = self.api.list(&ListParams::default()).await?;
+    async fn list_pvcs(&self, list_params: &ListParams) -> Result<Vec<PersistentVolumeClaim>> {
+        let pvcs = self.api.list(list_params).await?;
         Ok(pvcs.items)
     }
 
@@ -41,17 +59,31 @@ impl PvcApiTrait for KubePvcApi {
         let pvc = self.api.create(&Default::default(), &pvc).await?;
         Ok(pvc)
     }
-}
 
-/// Get the list of PersistentVolumeClaims available
-pub async fn get_pvcs_available(pvc_api: &impl PvcApiTrait) -> Result<Vec<String>> {
-    let pvc_list: Vec<_> = match pvc_api.list_pvcs().await {
-        Ok(pvc) => pvc,
-        Err(e) => panic!("Failed to list PVCs: {}", e),
+    async fn get_opt(&self, name: &str) -> Result<Option<PersistentVolumeClaim>> {
+        let pvc = self.api.get_opt(name).await?;
+        Ok(pvc)
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        self.api.delete(name, &DeleteParams::default()).await?;
+        Ok(())
     }
-    .into_iter()
-    .map(|pvc| pvc.metadata.name.unwrap())
-    .collect();
+}
+
+/// Get the list of PersistentVolumeClaims available that match the selector
+pub async fn get_pvcs_available(
+    pvc_api: &impl PvcApiTrait,
+    pvc_selector: &PvcSelector,
+) -> Result<Vec<String>> {
+    let pvc_list: Vec<_> = pvc_api
+        .list_pvcs(&pvc_selector.list_params())
+        .await
+        .context("Failed to list PVCs")?
+        .into_iter()
+        .filter_map(|pvc| pvc.metadata.name)
+        .filter(|pvc_name| pvc_selector.matches_name(pvc_name))
+        .collect();
     info!("PVCs available: {:?}", pvc_list);
     Ok(pvc_list)
 }
@@ -75,8 +107,8 @@ pub async fn check_if_pvc_exists(
                 );
                 Ok(Some(pvc))
             } else {
-                panic!(
-                    "PVC does not exist: {} on target namespace {:?}",
+                bail!(
+                    "PVC already exists: {} on target namespace {:?}",
                     pvc.metadata.name.clone().unwrap(),
                     pvc.metadata.namespace.clone().unwrap()
                 );
@@ -84,7 +116,7 @@ pub async fn check_if_pvc_exists(
         }
         Err(e) => {
             if should_exist {
-                panic!("Failed to get PVC: {}", e);
+                bail!("Failed to get PVC: {}", e);
             } else {
                 info!("PVC does not exist: {}", pvc_name);
                 Ok(None)
@@ -92,3 +124,113 @@ pub async fn check_if_pvc_exists(
         }
     }
 }
+
+/// Wait untill all the PVCs are Bound
+///
+/// # Arguments
+///
+/// * `pvc_api` - Api object for the PVCs of the namespace
+/// * `pvc_names` - Names of the PVCs
+/// * `timeout` - Maximum time to wait for the PVCs to be Bound
+///
+/// # Returns
+///
+/// An error listing the PVCs that are not Bound after the timeout
+pub async fn wait_untill_pvcs_are_bound(
+    pvc_api: &impl PvcApiTrait,
+    pvc_names: &[String],
+    timeout: Duration,
+) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let mut pending = Vec::new();
+        for pvc_name in pvc_names {
+            let pvc = pvc_api.get(pvc_name).await?;
+            let phase = pvc.status.and_then(|status| status.phase);
+            if phase.as_deref() != Some("Bound") {
+                pending.push(format!(
+                    "{} ({})",
+                    pvc_name,
+                    phase.unwrap_or_else(|| "Unknown".to_string())
+                ));
+            }
+        }
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "PVCs not Bound after {}s: {}",
+                timeout.as_secs(),
+                pending.join(", ")
+            );
+        }
+        info!("Waiting for PVCs to be Bound: {}", pending.join(", "));
+        sleep(PVC_BOUND_POLL_INTERVAL).await;
+    }
+}
+
+/// Find a name under which a PVC can be restored next to an existing one
+///
+/// The names `{pvc_name}-{suffix}`, then `{pvc_name}-{suffix}-2`, `{pvc_name}-{suffix}-3` and
+/// so on are tried, until one is not taken in the namespace.
+///
+/// # Arguments
+///
+/// * `pvc_api` - Api object for the PVCs of the namespace
+/// * `pvc_name` - Name of the existing PVC
+/// * `suffix` - Suffix of the new name
+///
+/// # Returns
+///
+/// The first name that is not taken
+pub async fn find_available_pvc_name(
+    pvc_api: &impl PvcApiTrait,
+    pvc_name: &str,
+    suffix: &str,
+) -> Result<String> {
+    for attempt in 1..=MAX_RENAME_ATTEMPTS {
+        let candidate = match attempt {
+            1 => format!("{}-{}", pvc_name, suffix),
+            _ => format!("{}-{}-{}", pvc_name, suffix, attempt),
+        };
+        if pvc_api.get_opt(&candidate).await?.is_none() {
+            return Ok(candidate);
+        }
+    }
+    bail!(
+        "No available name for PVC {} after {} attempts",
+        pvc_name,
+        MAX_RENAME_ATTEMPTS
+    );
+}
+
+/// Delete a PVC and wait untill it is gone
+///
+/// # Arguments
+///
+/// * `pvc_api` - Api object for the PVCs of the namespace
+/// * `pvc_name` - Name of the PVC
+/// * `timeout` - Maximum time to wait for the PVC to be gone
+pub async fn delete_pvc(
+    pvc_api: &impl PvcApiTrait,
+    pvc_name: &str,
+    timeout: Duration,
+) -> Result<()> {
+    pvc_api.delete(pvc_name).await?;
+    info!("Deleted PVC: {}", pvc_name);
+
+    let deadline = Instant::now() + timeout;
+    while pvc_api.get_opt(pvc_name).await?.is_some() {
+        if Instant::now() >= deadline {
+            bail!(
+                "PVC {} still exists {}s after its deletion",
+                pvc_name,
+                timeout.as_secs()
+            );
+        }
+        info!("Waiting for PVC {} to be gone", pvc_name);
+        sleep(PVC_DELETED_POLL_INTERVAL).await;
+    }
+    Ok(())
+}
@@ -14,7 +14,6 @@ enum PVCResourceValues {
     K8sKind,
     ApiGroup,
     VolumeMode,
-    StorageClass,
 }
 
 impl PVCResourceValues {
@@ -24,7 +23,6 @@ impl PVCResourceValues {
             PVCResourceValues::K8sKind => "VolumeSnapshot".to_string(),
             PVCResourceValues::ApiGroup => "snapshot.storage.k8s.io".to_string(),
             PVCResourceValues::VolumeMode => "Filesystem".to_string(),
-            PVCResourceValues::StorageClass => "gp3".to_string(),
         }
     }
 }
@@ -50,23 +48,29 @@ impl PVCOperator {
     /// * `access_modes` - Access modes for the PersistentVolumeClaim resource
     /// * `volume_snapshot_name` - Name of the VolumeSnapshot resource
     /// * `restore_size` - Size of the PersistentVolumeClaim resource
+    /// * `volume_mode` - Volume mode of the PersistentVolumeClaim resource
+    /// * `labels` - Labels of the PersistentVolumeClaim resource
+    /// * `annotations` - Annotations of the PersistentVolumeClaim resource
     ///
     /// # Returns
     ///
     /// PersistentVolumeClaim resource
     pub fn construct_persistent_volume_claim_resource(&self) -> PersistentVolumeClaim {
-        // Create a base labels map
+        // Create a base labels map from the labels of the payload
         // Always add the VSc name
-        let labels = BTreeMap::from([(
+        let mut labels = self.pvc_operator_payload.labels().clone();
+        labels.insert(
             "snap-kube/volume-snapshot-name".to_string(),
             self.pvc_operator_payload.pvc_name().to_string(),
-        )]);
+        );
+        let annotations = self.pvc_operator_payload.annotations();
 
         PersistentVolumeClaim {
             metadata: ObjectMeta {
                 name: Some(String::from(self.pvc_operator_payload.pvc_name())),
                 namespace: Some(String::from(self.pvc_operator_payload.namespace())),
                 labels: Some(labels),
+                annotations: (!annotations.is_empty()).then(|| annotations.clone()),
                 ..Default::default()
             },
             spec: Some(PersistentVolumeClaimSpec {
@@ -75,12 +79,11 @@ impl PVCOperator {
                         .access_modes()
                         .unwrap_or(vec![PVCResourceValues::AccessModes.get_value()]),
                 ),
-                storage_class_name: Some(
-                    self.pvc_operator_payload
-                        .storage_class()
-                        .unwrap_or(&PVCResourceValues::StorageClass.get_value())
-                        .to_string(),
-                ),
+                // Fall back to the default StorageClass of the cluster
+                storage_class_name: self
+                    .pvc_operator_payload
+                    .storage_class()
+                    .map(str::to_string),
                 data_source: Some(TypedLocalObjectReference {
                     name: String::from(self.pvc_operator_payload.volume_snapshot_name()),
                     kind: PVCResourceValues::K8sKind.get_value(),
@@ -92,7 +95,12 @@ impl PVCOperator {
                     api_group: Some(PVCResourceValues::ApiGroup.get_value()),
                     namespace: Some(String::from(self.pvc_operator_payload.namespace())),
                 }),
-                volume_mode: Some(PVCResourceValues::VolumeMode.get_value()),
+                volume_mode: Some(
+                    self.pvc_operator_payload
+                        .volume_mode()
+                        .map(str::to_string)
+                        .unwrap_or(PVCResourceValues::VolumeMode.get_value()),
+                ),
                 volume_name: Default::default(),
                 resources: Some(VolumeResourceRequirements {
                     requests: Some(BTreeMap::from([(
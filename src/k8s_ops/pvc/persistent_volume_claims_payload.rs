@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 pub struct PVCOperatorPayload {
     pub pvc_name: String,
     pub namespace: String,
@@ -5,6 +7,9 @@ pub struct PVCOperatorPayload {
     pub access_modes: Option<Vec<String>>,
     pub volume_snapshot_name: String,
     pub restore_size: String,
+    pub volume_mode: Option<String>,
+    pub labels: BTreeMap<String, String>,
+    pub annotations: BTreeMap<String, String>,
 }
 
 impl PVCOperatorPayload {
@@ -18,10 +23,14 @@ impl PVCOperatorPayload {
     /// * `access_modes` - Access modes for the PersistentVolumeClaim resource
     /// * `volume_snapshot_name` - Name of the VolumeSnapshot resource
     /// * `restore_size` - Size of the PersistentVolumeClaim resource
+    /// * `volume_mode` - Volume mode of the PersistentVolumeClaim resource
+    /// * `labels` - Labels of the PersistentVolumeClaim resource
+    /// * `annotations` - Annotations of the PersistentVolumeClaim resource
     ///
     /// # Returns
     ///
     /// A new PVCOperatorPayload instance
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         pvc_name: impl Into<String>,
         namespace: impl Into<String>,
@@ -29,6 +38,9 @@ impl PVCOperatorPayload {
         access_modes: impl Into<Option<Vec<String>>>,
         volume_snapshot_name: impl Into<String>,
         restore_size: impl Into<String>,
+        volume_mode: impl Into<Option<String>>,
+        labels: BTreeMap<String, String>,
+        annotations: BTreeMap<String, String>,
     ) -> Self {
         Self {
             pvc_name: pvc_name.into(),
@@ -37,6 +49,9 @@ impl PVCOperatorPayload {
             access_modes: access_modes.into(),
             volume_snapshot_name: volume_snapshot_name.into(),
             restore_size: restore_size.into(),
+            volume_mode: volume_mode.into(),
+            labels,
+            annotations,
         }
     }
 
@@ -63,4 +78,16 @@ impl PVCOperatorPayload {
     pub fn restore_size(&self) -> &str {
         &self.restore_size
     }
+
+    pub fn volume_mode(&self) -> Option<&str> {
+        self.volume_mode.as_deref()
+    }
+
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        &self.labels
+    }
+
+    pub fn annotations(&self) -> &BTreeMap<String, String> {
+        &self.annotations
+    }
 }
@@ -1,21 +1,27 @@
 #[cfg(test)]
 mod tests {
     use crate::k8s_ops::pvc::{
-        persistent_volume_claims::{MockPvcApiTrait, PvcApiTrait},
+        persistent_volume_claims::{
+            delete_pvc, find_available_pvc_name, get_pvcs_available, wait_untill_pvcs_are_bound,
+            MockPvcApiTrait, PvcApiTrait,
+        },
         persistent_volume_claims_operator::PVCOperator,
         persistent_volume_claims_payload::PVCOperatorPayload,
+        pvc_rename::PvcRename,
+        pvc_selector::PvcSelector,
+        pvc_template::{is_system_key, parse_key_value, PvcTemplate},
     };
     use k8s_openapi::{
         api::core::v1::{
-            PersistentVolumeClaim, PersistentVolumeClaimSpec, TypedLocalObjectReference,
-            TypedObjectReference, VolumeResourceRequirements,
+            PersistentVolumeClaim, PersistentVolumeClaimSpec, PersistentVolumeClaimStatus,
+            TypedLocalObjectReference, TypedObjectReference, VolumeResourceRequirements,
         },
         apimachinery::pkg::api::resource::Quantity,
     };
-    use kube::api::ObjectMeta;
+    use kube::api::{ListParams, ObjectMeta};
     use mockall::predicate;
     use pretty_assertions::assert_eq;
-    use std::collections::BTreeMap;
+    use std::{collections::BTreeMap, time::Duration};
 
     #[test]
     fn test_construct_persistent_volume_claim_resource() {
@@ -26,6 +32,9 @@ mod tests {
             Some(vec![String::from("ReadWriteOnce")]),
             String::from("test-vs"),
             "1Gi".to_string(),
+            None,
+            BTreeMap::new(),
+            BTreeMap::new(),
         );
 
         let pvc_operator = PVCOperator::new(pvc_operator_payload);
@@ -76,6 +85,250 @@ mod tests {
         assert_eq!(pvc, expected_pvc);
     }
 
+    #[test]
+    fn test_construct_persistent_volume_claim_resource_with_template() {
+        let pvc_operator_payload = PVCOperatorPayload::new(
+            "test-pvc",
+            "test-ns",
+            None,
+            Some(vec![String::from("ReadWriteMany")]),
+            "test-vs",
+            "1Gi",
+            Some(String::from("Block")),
+            BTreeMap::from([
+                ("app".to_string(), "postgres".to_string()),
+                // Overridden by the label set by snap-kube
+                (
+                    "snap-kube/volume-snapshot-name".to_string(),
+                    "other".to_string(),
+                ),
+            ]),
+            BTreeMap::from([("team".to_string(), "payments".to_string())]),
+        );
+
+        let pvc =
+            PVCOperator::new(pvc_operator_payload).construct_persistent_volume_claim_resource();
+
+        assert_eq!(
+            pvc.metadata.labels.unwrap(),
+            BTreeMap::from([
+                ("app".to_string(), "postgres".to_string()),
+                (
+                    "snap-kube/volume-snapshot-name".to_string(),
+                    "test-pvc".to_string()
+                ),
+            ])
+        );
+        assert_eq!(
+            pvc.metadata.annotations.unwrap(),
+            BTreeMap::from([("team".to_string(), "payments".to_string())])
+        );
+        let spec = pvc.spec.unwrap();
+        assert_eq!(
+            spec.access_modes.unwrap(),
+            vec!["ReadWriteMany".to_string()]
+        );
+        assert_eq!(spec.volume_mode.unwrap(), "Block");
+        // The default StorageClass of the cluster is used
+        assert_eq!(spec.storage_class_name, None);
+    }
+
+    #[test]
+    fn test_pvc_template_from_pvc() {
+        let pvc = PersistentVolumeClaim {
+            metadata: ObjectMeta {
+                name: Some("data-postgres-0".to_string()),
+                labels: Some(BTreeMap::from([
+                    ("app.kubernetes.io/name".to_string(), "postgres".to_string()),
+                    ("kubernetes.io/created-for".to_string(), "x".to_string()),
+                ])),
+                annotations: Some(BTreeMap::from([
+                    (
+                        "pv.kubernetes.io/bind-completed".to_string(),
+                        "yes".to_string(),
+                    ),
+                    (
+                        "volume.kubernetes.io/selected-node".to_string(),
+                        "node-1".to_string(),
+                    ),
+                    ("team".to_string(), "payments".to_string()),
+                ])),
+                ..Default::default()
+            },
+            spec: Some(PersistentVolumeClaimSpec {
+                access_modes: Some(vec!["ReadWriteOncePod".to_string()]),
+                volume_mode: Some("Block".to_string()),
+                volume_name: Some("pvc-0123".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            PvcTemplate::from_pvc(&pvc),
+            PvcTemplate {
+                access_modes: vec!["ReadWriteOncePod".to_string()],
+                volume_mode: Some("Block".to_string()),
+                labels: BTreeMap::from([(
+                    "app.kubernetes.io/name".to_string(),
+                    "postgres".to_string()
+                )]),
+                annotations: BTreeMap::from([("team".to_string(), "payments".to_string())]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_pvc_template_with_overrides() {
+        let pvc_template = PvcTemplate {
+            access_modes: vec!["ReadWriteOnce".to_string()],
+            volume_mode: Some("Filesystem".to_string()),
+            labels: BTreeMap::from([
+                ("app".to_string(), "postgres".to_string()),
+                ("env".to_string(), "prod".to_string()),
+            ]),
+            annotations: BTreeMap::new(),
+        }
+        .with_overrides(&PvcTemplate {
+            access_modes: vec!["ReadOnlyMany".to_string()],
+            volume_mode: None,
+            labels: BTreeMap::from([("env".to_string(), "staging".to_string())]),
+            annotations: BTreeMap::from([("team".to_string(), "payments".to_string())]),
+        });
+
+        assert_eq!(
+            pvc_template.access_modes(),
+            Some(vec!["ReadOnlyMany".to_string()])
+        );
+        assert_eq!(pvc_template.volume_mode.as_deref(), Some("Filesystem"));
+        assert_eq!(
+            pvc_template.labels,
+            BTreeMap::from([
+                ("app".to_string(), "postgres".to_string()),
+                ("env".to_string(), "staging".to_string()),
+            ])
+        );
+        assert_eq!(pvc_template.annotations.len(), 1);
+        assert_eq!(PvcTemplate::default().access_modes(), None);
+    }
+
+    #[test]
+    fn test_pvc_template_validate() {
+        assert!(PvcTemplate::default().validate().is_ok());
+        assert!(PvcTemplate {
+            access_modes: vec!["ReadWriteSometimes".to_string()],
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+        assert!(PvcTemplate {
+            volume_mode: Some("block".to_string()),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_is_system_key() {
+        assert!(is_system_key("kubernetes.io/created-for"));
+        assert!(is_system_key("pv.kubernetes.io/bound-by-controller"));
+        assert!(is_system_key(
+            "volume.beta.kubernetes.io/storage-provisioner"
+        ));
+        assert!(is_system_key("k8s.io/cloud-provider"));
+        assert!(!is_system_key("app.kubernetes.io/name"));
+        assert!(!is_system_key("notkubernetes.io/x"));
+        assert!(!is_system_key("app"));
+    }
+
+    #[test]
+    fn test_parse_key_value() {
+        assert_eq!(
+            parse_key_value("app = postgres").unwrap(),
+            ("app".to_string(), "postgres".to_string())
+        );
+        assert!(parse_key_value("app").is_err());
+        assert!(parse_key_value("=postgres").is_err());
+    }
+
+    #[test]
+    fn test_pvc_rename() {
+        let pvc_rename = PvcRename {
+            mappings: BTreeMap::from([("data-redis-0".to_string(), "cache".to_string())]),
+            prefix: Some("qa-".to_string()),
+            suffix: None,
+            pattern: Some("^data-(.*)$".to_string()),
+            replacement: Some("db-$1".to_string()),
+        };
+        assert!(pvc_rename.validate().is_ok());
+        assert_eq!(pvc_rename.rename("data-redis-0").unwrap(), "cache");
+        assert_eq!(
+            pvc_rename.rename("data-postgres-0").unwrap(),
+            "qa-db-postgres-0"
+        );
+        assert_eq!(pvc_rename.rename("logs").unwrap(), "qa-logs");
+        assert_eq!(PvcRename::default().rename("logs").unwrap(), "logs");
+
+        let pvc_rename = PvcRename {
+            suffix: Some("-clone".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(pvc_rename.rename("logs").unwrap(), "logs-clone");
+    }
+
+    #[test]
+    fn test_pvc_rename_validation() {
+        assert!(PvcRename {
+            pattern: Some("^data-(.*)$".to_string()),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+        assert!(PvcRename {
+            pattern: Some("(".to_string()),
+            replacement: Some("x".to_string()),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+        assert!(PvcRename {
+            prefix: Some("QA-".to_string()),
+            ..Default::default()
+        }
+        .rename("data")
+        .is_err());
+        assert!(PvcRename {
+            mappings: BTreeMap::from([("data".to_string(), "-data".to_string())]),
+            ..Default::default()
+        }
+        .rename("data")
+        .is_err());
+    }
+
+    #[test]
+    fn test_pvc_rename_all() {
+        let pvcs = vec!["data-0".to_string(), "data-1".to_string()];
+        let pvc_rename = PvcRename {
+            mappings: BTreeMap::from([("data-0".to_string(), "restored-0".to_string())]),
+            prefix: Some("qa-".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            pvc_rename.rename_all(&pvcs).unwrap(),
+            BTreeMap::from([
+                ("data-0".to_string(), "restored-0".to_string()),
+                ("data-1".to_string(), "qa-data-1".to_string()),
+            ])
+        );
+
+        let pvc_rename = PvcRename {
+            mappings: BTreeMap::from([("data-0".to_string(), "data-1".to_string())]),
+            ..Default::default()
+        };
+        assert!(pvc_rename.rename_all(&pvcs).is_err());
+    }
+
     #[tokio::test]
     async fn test_create_pvc() {
         let mut mock_pvc_api = MockPvcApiTrait::new();
@@ -145,7 +398,7 @@ mod tests {
     async fn test_list_pvcs() {
         let mut mock_pvc_api = MockPvcApiTrait::new();
 
-        mock_pvc_api.expect_list_pvcs().times(1).returning(|| {
+        mock_pvc_api.expect_list_pvcs().times(1).returning(|_| {
             Ok(vec![PersistentVolumeClaim {
                 metadata: ObjectMeta {
                     name: Some("test-pvc".to_string()),
@@ -156,7 +409,7 @@ mod tests {
             }])
         });
 
-        let result = mock_pvc_api.list_pvcs().await;
+        let result = mock_pvc_api.list_pvcs(&ListParams::default()).await;
 
         assert!(result.is_ok());
         assert_eq!(result.as_ref().unwrap().len(), 1);
@@ -164,7 +417,7 @@ mod tests {
             result
                 .as_ref()
                 .unwrap()
-                .get(0)
+                .first()
                 .unwrap()
                 .metadata
                 .name
@@ -175,7 +428,7 @@ mod tests {
         assert_eq!(
             result
                 .unwrap()
-                .get(0)
+                .first()
                 .unwrap()
                 .metadata
                 .namespace
@@ -184,4 +437,180 @@ mod tests {
             "test-ns"
         );
     }
+
+    #[test]
+    fn test_pvc_selector_list_params() {
+        let pvc_selector = PvcSelector::new(
+            Some("app=postgres,tier!=cache"),
+            Some("metadata.name!=scratch"),
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert!(!pvc_selector.is_empty());
+        assert_eq!(
+            pvc_selector.list_params(),
+            ListParams::default()
+                .labels("app=postgres,tier!=cache")
+                .fields("metadata.name!=scratch")
+        );
+        assert!(PvcSelector::default().is_empty());
+        assert!(PvcSelector::new(None::<String>, None::<String>, Some("("), None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_pvcs_available_with_selector() {
+        let mut mock_pvc_api = MockPvcApiTrait::new();
+
+        mock_pvc_api
+            .expect_list_pvcs()
+            .with(predicate::eq(ListParams::default().labels("app=postgres")))
+            .times(1)
+            .returning(|_| {
+                Ok(["data-postgres-0", "wal-postgres-0", "data-postgres-tmp"]
+                    .into_iter()
+                    .map(|name| PersistentVolumeClaim {
+                        metadata: ObjectMeta {
+                            name: Some(name.to_string()),
+                            namespace: Some("test-ns".to_string()),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .collect())
+            });
+
+        let pvc_selector = PvcSelector::new(
+            Some("app=postgres"),
+            None::<String>,
+            Some("^data-"),
+            Some("-tmp$"),
+        )
+        .unwrap();
+
+        let result = get_pvcs_available(&mock_pvc_api, &pvc_selector).await;
+        assert_eq!(result.unwrap(), vec!["data-postgres-0".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_pvcs_available_list_error() {
+        let mut mock_pvc_api = MockPvcApiTrait::new();
+
+        mock_pvc_api
+            .expect_list_pvcs()
+            .times(1)
+            .returning(|_| Err(anyhow::anyhow!("connection refused")));
+
+        let result = get_pvcs_available(&mock_pvc_api, &PvcSelector::default()).await;
+        assert_eq!(result.unwrap_err().to_string(), "Failed to list PVCs");
+    }
+
+    #[tokio::test]
+    async fn test_wait_untill_pvcs_are_bound() {
+        let mut mock_pvc_api = MockPvcApiTrait::new();
+
+        mock_pvc_api.expect_get().returning(|name| {
+            Ok(PersistentVolumeClaim {
+                metadata: ObjectMeta {
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                },
+                status: Some(PersistentVolumeClaimStatus {
+                    phase: Some(
+                        if name == "wal-postgres-0" {
+                            "Pending"
+                        } else {
+                            "Bound"
+                        }
+                        .to_string(),
+                    ),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+        });
+
+        let result = wait_untill_pvcs_are_bound(
+            &mock_pvc_api,
+            &["data-postgres-0".to_string()],
+            Duration::ZERO,
+        )
+        .await;
+        assert!(result.is_ok());
+
+        let result = wait_untill_pvcs_are_bound(
+            &mock_pvc_api,
+            &["data-postgres-0".to_string(), "wal-postgres-0".to_string()],
+            Duration::ZERO,
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "PVCs not Bound after 0s: wal-postgres-0 (Pending)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_available_pvc_name() {
+        let mut mock_pvc_api = MockPvcApiTrait::new();
+
+        mock_pvc_api.expect_get_opt().returning(|name| {
+            Ok(["data-postgres-0-restored", "data-postgres-0-restored-2"]
+                .contains(&name)
+                .then(|| PersistentVolumeClaim {
+                    metadata: ObjectMeta {
+                        name: Some(name.to_string()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }))
+        });
+
+        assert_eq!(
+            find_available_pvc_name(&mock_pvc_api, "data-postgres-0", "restored")
+                .await
+                .unwrap(),
+            "data-postgres-0-restored-3"
+        );
+        assert_eq!(
+            find_available_pvc_name(&mock_pvc_api, "wal-postgres-0", "restored")
+                .await
+                .unwrap(),
+            "wal-postgres-0-restored"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_pvc() {
+        let mut mock_pvc_api = MockPvcApiTrait::new();
+
+        mock_pvc_api
+            .expect_delete()
+            .with(predicate::eq("data-postgres-0"))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_pvc_api
+            .expect_get_opt()
+            .with(predicate::eq("data-postgres-0"))
+            .times(1)
+            .returning(|_| Ok(None));
+        assert!(delete_pvc(&mock_pvc_api, "data-postgres-0", Duration::ZERO)
+            .await
+            .is_ok());
+
+        // A PVC held by its protection finalizer is never gone
+        let mut mock_pvc_api = MockPvcApiTrait::new();
+        mock_pvc_api.expect_delete().returning(|_| Ok(()));
+        mock_pvc_api
+            .expect_get_opt()
+            .returning(|_| Ok(Some(PersistentVolumeClaim::default())));
+        assert_eq!(
+            delete_pvc(&mock_pvc_api, "data-postgres-0", Duration::ZERO)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "PVC data-postgres-0 still exists 0s after its deletion"
+        );
+    }
 }
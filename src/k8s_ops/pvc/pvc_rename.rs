@@ -0,0 +1,126 @@
+use crate::k8s_ops::vs::volume_snapshot_naming::MAX_NAME_LENGTH;
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Renames the PVCs on restore
+///
+/// An explicit mapping of a PVC takes precedence. The other PVCs are rewritten by the regex,
+/// if any, then get the prefix and the suffix, if any.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PvcRename {
+    /// New names, keyed by the name of the source PVC
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mappings: BTreeMap<String, String>,
+    /// Prefix added to the names
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// Suffix added to the names
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+    /// Regex rewriting the names, e.g. `^data-(.*)$`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Replacement of the regex, with `$1` or `${name}` referring to its capture groups, e.g.
+    /// `db-$1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+impl PvcRename {
+    /// Check that the regex is valid and has a replacement
+    pub fn validate(&self) -> Result<()> {
+        self.regex()?;
+        Ok(())
+    }
+
+    /// Compile the regex and pair it with its replacement
+    fn regex(&self) -> Result<Option<(Regex, &str)>> {
+        match (&self.pattern, &self.replacement) {
+            (Some(pattern), Some(replacement)) => Ok(Some((
+                Regex::new(pattern).context("Invalid PVC rename regex")?,
+                replacement,
+            ))),
+            (Some(_), None) => bail!("A replacement is required with the PVC rename regex"),
+            (None, Some(_)) => bail!("A regex is required with the PVC rename replacement"),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Get the name a PVC is restored under
+    ///
+    /// # Arguments
+    ///
+    /// * `pvc_name` - Name of the source PVC
+    ///
+    /// # Returns
+    ///
+    /// The new name, or an error if it is not a valid PVC name
+    pub fn rename(&self, pvc_name: &str) -> Result<String> {
+        let renamed = match self.mappings.get(pvc_name) {
+            Some(renamed) => renamed.clone(),
+            None => {
+                let rewritten = match self.regex()? {
+                    Some((regex, replacement)) => regex.replace(pvc_name, replacement).into_owned(),
+                    None => pvc_name.to_string(),
+                };
+                format!(
+                    "{}{}{}",
+                    self.prefix.as_deref().unwrap_or_default(),
+                    rewritten,
+                    self.suffix.as_deref().unwrap_or_default()
+                )
+            }
+        };
+        validate_pvc_name(&renamed)
+            .with_context(|| format!("Invalid new name for PVC {}", pvc_name))?;
+        Ok(renamed)
+    }
+
+    /// Get the names a set of PVCs are restored under
+    ///
+    /// # Arguments
+    ///
+    /// * `pvc_names` - Names of the source PVCs
+    ///
+    /// # Returns
+    ///
+    /// The new names, keyed by the names of the source PVCs, or an error if two PVCs would be
+    /// restored under the same name
+    pub fn rename_all<'a>(
+        &self,
+        pvc_names: impl IntoIterator<Item = &'a String>,
+    ) -> Result<BTreeMap<String, String>> {
+        let mut renamed = BTreeMap::new();
+        let mut taken = BTreeSet::new();
+        for pvc_name in pvc_names {
+            let new_name = self.rename(pvc_name)?;
+            if !taken.insert(new_name.clone()) {
+                bail!(
+                    "Several PVCs would be restored as {}, including {}",
+                    new_name,
+                    pvc_name
+                );
+            }
+            renamed.insert(pvc_name.clone(), new_name);
+        }
+        Ok(renamed)
+    }
+}
+
+/// Check that a name is a valid PVC name, i.e. a DNS subdomain
+pub fn validate_pvc_name(pvc_name: &str) -> Result<()> {
+    let dns_subdomain =
+        Regex::new(r"^[a-z0-9]([-a-z0-9]*[a-z0-9])?(\.[a-z0-9]([-a-z0-9]*[a-z0-9])?)*$")?;
+    if pvc_name.len() > MAX_NAME_LENGTH || !dns_subdomain.is_match(pvc_name) {
+        bail!(
+            "{} is not a valid PVC name, expected at most {} lowercase alphanumeric characters, '-' or '.'",
+            pvc_name,
+            MAX_NAME_LENGTH
+        );
+    }
+    Ok(())
+}
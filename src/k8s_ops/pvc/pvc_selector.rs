@@ -0,0 +1,128 @@
+use anyhow::{bail, Context, Result};
+use kube::api::ListParams;
+use regex::Regex;
+use std::collections::BTreeMap;
+
+/// Selects the PVCs of a namespace
+///
+/// The label and field selectors are evaluated by the Kubernetes API server,
+/// while the include/exclude regexes are matched against the PVC names afterwards.
+#[derive(Clone, Debug, Default)]
+pub struct PvcSelector {
+    pub label_selector: Option<String>,
+    pub field_selector: Option<String>,
+    pub include_regex: Option<Regex>,
+    pub exclude_regex: Option<Regex>,
+}
+
+impl PvcSelector {
+    /// Creates a new PvcSelector
+    ///
+    /// # Arguments
+    ///
+    /// * `label_selector` - Label selector, e.g. `app=postgres,tier!=cache`
+    /// * `field_selector` - Field selector, e.g. `metadata.name!=scratch`
+    /// * `include_regex` - Only PVC names matching this regex are selected
+    /// * `exclude_regex` - PVC names matching this regex are never selected
+    ///
+    /// # Returns
+    ///
+    /// A new PvcSelector instance, or an error if a regex is invalid
+    pub fn new(
+        label_selector: Option<impl Into<String>>,
+        field_selector: Option<impl Into<String>>,
+        include_regex: Option<&str>,
+        exclude_regex: Option<&str>,
+    ) -> Result<Self> {
+        Ok(Self {
+            label_selector: label_selector.map(|selector| selector.into()),
+            field_selector: field_selector.map(|selector| selector.into()),
+            include_regex: include_regex
+                .map(|regex| Regex::new(regex).context("Invalid include PVC regex"))
+                .transpose()?,
+            exclude_regex: exclude_regex
+                .map(|regex| Regex::new(regex).context("Invalid exclude PVC regex"))
+                .transpose()?,
+        })
+    }
+
+    /// Whether no selection criteria are set
+    pub fn is_empty(&self) -> bool {
+        self.label_selector.is_none()
+            && self.field_selector.is_none()
+            && self.include_regex.is_none()
+            && self.exclude_regex.is_none()
+    }
+
+    /// Get the ListParams carrying the label and field selectors
+    pub fn list_params(&self) -> ListParams {
+        let mut list_params = ListParams::default();
+        if let Some(label_selector) = &self.label_selector {
+            list_params = list_params.labels(label_selector);
+        }
+        if let Some(field_selector) = &self.field_selector {
+            list_params = list_params.fields(field_selector);
+        }
+        list_params
+    }
+
+    /// Whether a PVC name passes the include and exclude regexes
+    pub fn matches_name(&self, pvc_name: &str) -> bool {
+        let included = match &self.include_regex {
+            Some(regex) => regex.is_match(pvc_name),
+            None => true,
+        };
+        let excluded = match &self.exclude_regex {
+            Some(regex) => regex.is_match(pvc_name),
+            None => false,
+        };
+        included && !excluded
+    }
+}
+
+/// Whether a set of labels matches an equality-based label selector
+///
+/// Supports the `key=value`, `key==value`, `key!=value`, `key` and `!key` requirements.
+/// Set-based requirements are evaluated by the Kubernetes API server only.
+///
+/// # Arguments
+///
+/// * `label_selector` - Label selector, e.g. `app=postgres,tier!=cache`
+/// * `labels` - Labels of the resource
+///
+/// # Returns
+///
+/// Whether all the requirements of the selector hold, or an error if the selector is not supported
+pub fn label_selector_matches(
+    label_selector: &str,
+    labels: &BTreeMap<String, String>,
+) -> Result<bool> {
+    for requirement in label_selector
+        .split(',')
+        .map(str::trim)
+        .filter(|requirement| !requirement.is_empty())
+    {
+        if requirement.contains(" in ") || requirement.contains(" notin ") {
+            bail!(
+                "Set-based label selectors are not supported: {}",
+                requirement
+            );
+        }
+        let matches = if let Some((key, value)) = requirement.split_once("!=") {
+            labels.get(key.trim()).map(String::as_str) != Some(value.trim())
+        } else if let Some((key, value)) = requirement
+            .split_once("==")
+            .or_else(|| requirement.split_once('='))
+        {
+            labels.get(key.trim()).map(String::as_str) == Some(value.trim())
+        } else if let Some(key) = requirement.strip_prefix('!') {
+            !labels.contains_key(key.trim())
+        } else {
+            labels.contains_key(requirement)
+        };
+        if !matches {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
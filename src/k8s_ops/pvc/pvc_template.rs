@@ -0,0 +1,170 @@
+use anyhow::{bail, Result};
+use k8s_openapi::api::core::v1::{PersistentVolumeClaim, PersistentVolumeClaimSpec};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Access modes of the PVCs supported by Kubernetes
+pub const ACCESS_MODES: [&str; 4] = [
+    "ReadWriteOnce",
+    "ReadOnlyMany",
+    "ReadWriteMany",
+    "ReadWriteOncePod",
+];
+
+/// Volume mode of the PVCs consumed as raw block devices
+pub const BLOCK_VOLUME_MODE: &str = "Block";
+
+/// Volume modes of the PVCs supported by Kubernetes
+pub const VOLUME_MODES: [&str; 2] = ["Filesystem", BLOCK_VOLUME_MODE];
+
+/// The parts of a source PVC carried over to the PVC restored from its snapshot
+///
+/// The same struct holds the explicit overrides of a restore, in which case the fields that
+/// are not set keep the values of the source PVC.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PvcTemplate {
+    /// Access modes, e.g. `ReadWriteOnce`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub access_modes: Vec<String>,
+    /// Volume mode, `Filesystem` or `Block`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_mode: Option<String>,
+    /// Labels, without the ones of the Kubernetes system
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// Annotations, without the ones of the Kubernetes system
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl PvcTemplate {
+    /// Read the template of a source PVC
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - Spec of the source PVC
+    /// * `labels` - Labels of the source PVC
+    /// * `annotations` - Annotations of the source PVC
+    ///
+    /// # Returns
+    ///
+    /// The template, without the labels and annotations of the Kubernetes system
+    pub fn from_source(
+        spec: Option<&PersistentVolumeClaimSpec>,
+        labels: Option<&BTreeMap<String, String>>,
+        annotations: Option<&BTreeMap<String, String>>,
+    ) -> Self {
+        let without_system_keys = |values: Option<&BTreeMap<String, String>>| {
+            values
+                .into_iter()
+                .flatten()
+                .filter(|(key, _)| !is_system_key(key))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        };
+        Self {
+            access_modes: spec
+                .and_then(|spec| spec.access_modes.clone())
+                .unwrap_or_default(),
+            volume_mode: spec.and_then(|spec| spec.volume_mode.clone()),
+            labels: without_system_keys(labels),
+            annotations: without_system_keys(annotations),
+        }
+    }
+
+    /// Read the template of a source PVC
+    pub fn from_pvc(pvc: &PersistentVolumeClaim) -> Self {
+        Self::from_source(
+            pvc.spec.as_ref(),
+            pvc.metadata.labels.as_ref(),
+            pvc.metadata.annotations.as_ref(),
+        )
+    }
+
+    /// Apply explicit overrides to the template
+    ///
+    /// The access modes and volume mode of the overrides replace the ones of the template,
+    /// and their labels and annotations are merged into the ones of the template.
+    pub fn with_overrides(mut self, overrides: &PvcTemplate) -> Self {
+        if !overrides.access_modes.is_empty() {
+            self.access_modes = overrides.access_modes.clone();
+        }
+        if overrides.volume_mode.is_some() {
+            self.volume_mode = overrides.volume_mode.clone();
+        }
+        self.labels.extend(overrides.labels.clone());
+        self.annotations.extend(overrides.annotations.clone());
+        self
+    }
+
+    /// Check that the access modes and the volume mode are supported by Kubernetes
+    pub fn validate(&self) -> Result<()> {
+        for access_mode in &self.access_modes {
+            if !ACCESS_MODES.contains(&access_mode.as_str()) {
+                bail!(
+                    "Invalid access mode {}, expected one of: {}",
+                    access_mode,
+                    ACCESS_MODES.join(", ")
+                );
+            }
+        }
+        if let Some(volume_mode) = &self.volume_mode {
+            if !VOLUME_MODES.contains(&volume_mode.as_str()) {
+                bail!(
+                    "Invalid volume mode {}, expected one of: {}",
+                    volume_mode,
+                    VOLUME_MODES.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the volume is a raw block device
+    pub fn is_block(&self) -> bool {
+        self.volume_mode.as_deref() == Some(BLOCK_VOLUME_MODE)
+    }
+
+    /// The access modes, if any are set
+    pub fn access_modes(&self) -> Option<Vec<String>> {
+        if self.access_modes.is_empty() {
+            None
+        } else {
+            Some(self.access_modes.clone())
+        }
+    }
+}
+
+/// Whether a label or annotation key belongs to the Kubernetes system, i.e. its prefix is
+/// `kubernetes.io` or `k8s.io` or one of their subdomains, apart from the recommended
+/// `app.kubernetes.io` labels
+///
+/// Such keys, e.g. `pv.kubernetes.io/bind-completed` or
+/// `volume.kubernetes.io/selected-node`, describe the binding of the source PVC and would
+/// break the restored one.
+pub fn is_system_key(key: &str) -> bool {
+    match key.split_once('/') {
+        Some((prefix, _)) => {
+            prefix != "app.kubernetes.io"
+                && ["kubernetes.io", "k8s.io"]
+                    .iter()
+                    .any(|domain| prefix == *domain || prefix.ends_with(&format!(".{}", domain)))
+        }
+        None => false,
+    }
+}
+
+/// Parse a label or annotation given as `key=value`
+pub fn parse_key_value(key_value: &str) -> Result<(String, String)> {
+    match key_value.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => bail!(
+            "Invalid label or annotation, expected <key>=<value>: {}",
+            key_value
+        ),
+    }
+}
@@ -0,0 +1,5 @@
+#[cfg(feature = "restore")]
+pub mod storage_classes;
+
+#[cfg(all(test, feature = "restore"))]
+mod storage_classes_tests;
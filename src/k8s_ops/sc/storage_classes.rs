@@ -0,0 +1,164 @@
+use crate::k8s_ops::pvc::pvc_template::BLOCK_VOLUME_MODE;
+use anyhow::{bail, Context, Result};
+use k8s_openapi::api::storage::v1::StorageClass;
+use kube::Api;
+
+/// Name of the EBS CSI driver, the provisioner of its StorageClasses
+pub const EBS_CSI_DRIVER: &str = "ebs.csi.aws.com";
+
+/// Topology keys holding the availability zone of a node, as set by the EBS CSI driver and by
+/// Kubernetes
+pub const ZONE_TOPOLOGY_KEYS: [&str; 2] = [
+    "topology.ebs.csi.aws.com/zone",
+    "topology.kubernetes.io/zone",
+];
+
+/// In-tree provisioner of the EBS volumes, migrated to the EBS CSI driver
+pub const EBS_IN_TREE_PROVISIONER: &str = "kubernetes.io/aws-ebs";
+
+/// Prefix of the in-tree provisioners of Kubernetes, e.g. `kubernetes.io/aws-ebs`
+const IN_TREE_PROVISIONER_PREFIX: &str = "kubernetes.io/";
+
+/// Filesystem of the volumes of a StorageClass that does not set one
+const DEFAULT_FS_TYPE: &str = "ext4";
+
+/// Get a StorageClass
+///
+/// # Arguments
+///
+/// * `storage_class_api` - Api object for the StorageClasses
+/// * `storage_class_name` - Name of the StorageClass
+///
+/// # Returns
+///
+/// The StorageClass, or an error if it does not exist
+pub async fn get_storage_class(
+    storage_class_api: &Api<StorageClass>,
+    storage_class_name: &str,
+) -> Result<StorageClass> {
+    storage_class_api
+        .get_opt(storage_class_name)
+        .await?
+        .with_context(|| format!("StorageClass {} not found", storage_class_name))
+}
+
+/// Check that a StorageClass is provisioned by a CSI driver
+///
+/// # Arguments
+///
+/// * `storage_class` - The StorageClass
+/// * `driver` - Name of the CSI driver
+pub fn ensure_storage_class_provisioner(storage_class: &StorageClass, driver: &str) -> Result<()> {
+    if storage_class.provisioner != driver {
+        bail!(
+            "StorageClass {} is provisioned by {}, expected {}",
+            storage_class.metadata.name.clone().unwrap_or_default(),
+            storage_class.provisioner,
+            driver
+        );
+    }
+    Ok(())
+}
+
+/// Check that a StorageClass can provision volumes from the snapshots of a CSI driver
+///
+/// The StorageClass must be provisioned by the driver itself, or by the in-tree provisioner
+/// migrated to it, e.g. `kubernetes.io/aws-ebs` for the EBS CSI driver.
+///
+/// # Arguments
+///
+/// * `storage_class` - The StorageClass
+/// * `driver` - Name of the CSI driver of the snapshots
+pub fn ensure_storage_class_consumes_snapshots(
+    storage_class: &StorageClass,
+    driver: &str,
+) -> Result<()> {
+    let migrated_driver = match storage_class.provisioner.as_str() {
+        EBS_IN_TREE_PROVISIONER => Some(EBS_CSI_DRIVER),
+        _ => None,
+    };
+    if storage_class.provisioner != driver && migrated_driver != Some(driver) {
+        bail!(
+            "StorageClass {} is provisioned by {}, which cannot restore the snapshots of {}",
+            storage_class.metadata.name.clone().unwrap_or_default(),
+            storage_class.provisioner,
+            driver
+        );
+    }
+    Ok(())
+}
+
+/// Check that a StorageClass can provision the volumes of a volume mode
+///
+/// Raw block volumes are only provisioned by CSI drivers, the in-tree `kubernetes.io`
+/// provisioners cannot restore them from a VolumeSnapshot.
+///
+/// # Arguments
+///
+/// * `storage_class` - The StorageClass
+/// * `volume_mode` - Volume mode of the volumes, `Filesystem` or `Block`
+pub fn ensure_storage_class_supports_volume_mode(
+    storage_class: &StorageClass,
+    volume_mode: &str,
+) -> Result<()> {
+    if volume_mode == BLOCK_VOLUME_MODE
+        && storage_class
+            .provisioner
+            .starts_with(IN_TREE_PROVISIONER_PREFIX)
+    {
+        bail!(
+            "StorageClass {} is provisioned by the in-tree {} provisioner, which does not support the Block volume mode",
+            storage_class.metadata.name.clone().unwrap_or_default(),
+            storage_class.provisioner
+        );
+    }
+    Ok(())
+}
+
+/// Get the availability zones a StorageClass restricts its volumes to
+///
+/// # Arguments
+///
+/// * `storage_class` - The StorageClass
+///
+/// # Returns
+///
+/// The zones of the allowed topologies of the StorageClass, sorted and deduplicated
+pub fn storage_class_zones(storage_class: &StorageClass) -> Vec<String> {
+    let mut zones: Vec<String> = storage_class
+        .allowed_topologies
+        .iter()
+        .flatten()
+        .flat_map(|term| term.match_label_expressions.iter().flatten())
+        .filter(|requirement| ZONE_TOPOLOGY_KEYS.contains(&requirement.key.as_str()))
+        .flat_map(|requirement| requirement.values.clone())
+        .collect();
+    zones.sort();
+    zones.dedup();
+    zones
+}
+
+/// Get the filesystem of the volumes of a StorageClass
+///
+/// # Arguments
+///
+/// * `storage_class` - The StorageClass
+///
+/// # Returns
+///
+/// The `csi.storage.k8s.io/fstype` parameter, or the legacy `fsType` one, `ext4` if not set
+pub fn storage_class_fs_type(storage_class: &StorageClass) -> String {
+    storage_class
+        .parameters
+        .as_ref()
+        .and_then(|parameters| {
+            parameters
+                .iter()
+                .find(|(key, _)| {
+                    key.eq_ignore_ascii_case("csi.storage.k8s.io/fstype")
+                        || key.eq_ignore_ascii_case("fsType")
+                })
+                .map(|(_, value)| value.clone())
+        })
+        .unwrap_or_else(|| DEFAULT_FS_TYPE.to_string())
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use crate::k8s_ops::sc::storage_classes::{
+        ensure_storage_class_consumes_snapshots, ensure_storage_class_provisioner,
+        ensure_storage_class_supports_volume_mode, storage_class_fs_type, storage_class_zones,
+        EBS_CSI_DRIVER, EBS_IN_TREE_PROVISIONER,
+    };
+    use k8s_openapi::api::{
+        core::v1::{TopologySelectorLabelRequirement, TopologySelectorTerm},
+        storage::v1::StorageClass,
+    };
+    use kube::api::ObjectMeta;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    fn storage_class(provisioner: &str, parameters: &[(&str, &str)]) -> StorageClass {
+        StorageClass {
+            metadata: ObjectMeta {
+                name: Some("ebs-sc".to_string()),
+                ..Default::default()
+            },
+            provisioner: provisioner.to_string(),
+            parameters: Some(
+                parameters
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect::<BTreeMap<_, _>>(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ensure_storage_class_supports_volume_mode() {
+        let csi_storage_class = storage_class(EBS_CSI_DRIVER, &[]);
+        assert!(ensure_storage_class_supports_volume_mode(&csi_storage_class, "Block").is_ok());
+        assert!(
+            ensure_storage_class_supports_volume_mode(&csi_storage_class, "Filesystem").is_ok()
+        );
+
+        let in_tree_storage_class = storage_class("kubernetes.io/aws-ebs", &[]);
+        assert!(
+            ensure_storage_class_supports_volume_mode(&in_tree_storage_class, "Filesystem").is_ok()
+        );
+        assert_eq!(
+            ensure_storage_class_supports_volume_mode(&in_tree_storage_class, "Block")
+                .unwrap_err()
+                .to_string(),
+            "StorageClass ebs-sc is provisioned by the in-tree kubernetes.io/aws-ebs provisioner, which does not support the Block volume mode"
+        );
+    }
+
+    #[test]
+    fn test_ensure_storage_class_consumes_snapshots() {
+        assert!(ensure_storage_class_consumes_snapshots(
+            &storage_class(EBS_CSI_DRIVER, &[]),
+            EBS_CSI_DRIVER
+        )
+        .is_ok());
+        assert!(ensure_storage_class_consumes_snapshots(
+            &storage_class(EBS_IN_TREE_PROVISIONER, &[]),
+            EBS_CSI_DRIVER
+        )
+        .is_ok());
+        assert_eq!(
+            ensure_storage_class_consumes_snapshots(
+                &storage_class("efs.csi.aws.com", &[]),
+                EBS_CSI_DRIVER
+            )
+            .unwrap_err()
+            .to_string(),
+            "StorageClass ebs-sc is provisioned by efs.csi.aws.com, which cannot restore the snapshots of ebs.csi.aws.com"
+        );
+    }
+
+    #[test]
+    fn test_ensure_storage_class_provisioner() {
+        assert!(ensure_storage_class_provisioner(
+            &storage_class(EBS_CSI_DRIVER, &[]),
+            EBS_CSI_DRIVER
+        )
+        .is_ok());
+        let e = ensure_storage_class_provisioner(
+            &storage_class("kubernetes.io/aws-ebs", &[]),
+            EBS_CSI_DRIVER,
+        )
+        .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "StorageClass ebs-sc is provisioned by kubernetes.io/aws-ebs, expected ebs.csi.aws.com"
+        );
+    }
+
+    #[test]
+    fn test_storage_class_zones() {
+        let requirement = |key: &str, values: &[&str]| TopologySelectorLabelRequirement {
+            key: key.to_string(),
+            values: values.iter().map(|value| value.to_string()).collect(),
+        };
+        let mut storage_class = storage_class(EBS_CSI_DRIVER, &[]);
+        assert!(storage_class_zones(&storage_class).is_empty());
+
+        storage_class.allowed_topologies = Some(vec![
+            TopologySelectorTerm {
+                match_label_expressions: Some(vec![
+                    requirement("topology.ebs.csi.aws.com/zone", &["eu-west-1b"]),
+                    requirement("node.kubernetes.io/instance-type", &["m5.large"]),
+                ]),
+            },
+            TopologySelectorTerm {
+                match_label_expressions: Some(vec![requirement(
+                    "topology.kubernetes.io/zone",
+                    &["eu-west-1a", "eu-west-1b"],
+                )]),
+            },
+        ]);
+        assert_eq!(
+            storage_class_zones(&storage_class),
+            vec!["eu-west-1a".to_string(), "eu-west-1b".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_storage_class_fs_type() {
+        assert_eq!(
+            storage_class_fs_type(&storage_class(EBS_CSI_DRIVER, &[])),
+            "ext4"
+        );
+        assert_eq!(
+            storage_class_fs_type(&storage_class(
+                EBS_CSI_DRIVER,
+                &[("csi.storage.k8s.io/fstype", "xfs")]
+            )),
+            "xfs"
+        );
+        assert_eq!(
+            storage_class_fs_type(&storage_class(EBS_CSI_DRIVER, &[("fstype", "ext3")])),
+            "ext3"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod volume_group_snapshots;
+
+#[cfg(test)]
+mod volume_group_snapshots_tests;
@@ -0,0 +1,204 @@
+use crate::k8s_ops::vs::volume_snapshots_error::VolumeSnapshotError;
+use anyhow::{bail, Result};
+use kube::{
+    api::ObjectMeta,
+    runtime::wait::{await_condition, Condition},
+};
+use kube_custom_resources_rs::groupsnapshot_storage_k8s_io::v1alpha1::volumegroupsnapshots::{
+    VolumeGroupSnapshot, VolumeGroupSnapshotSource, VolumeGroupSnapshotSourceSelector,
+    VolumeGroupSnapshotSourceSelectorMatchExpressions, VolumeGroupSnapshotSpec,
+    VolumeGroupSnapshotStatus,
+};
+use std::{collections::BTreeMap, time::Duration};
+use tracing::info;
+
+/// Convert an equality-based label selector to the selector of a VolumeGroupSnapshot
+///
+/// # Arguments
+///
+/// * `label_selector` - Label selector, e.g. `app=postgres,tier!=cache`
+///
+/// # Returns
+///
+/// The selector of the VolumeGroupSnapshot, or an error if the label selector is not supported
+pub fn group_snapshot_selector(label_selector: &str) -> Result<VolumeGroupSnapshotSourceSelector> {
+    let mut match_labels = BTreeMap::new();
+    let mut match_expressions = Vec::new();
+    for requirement in label_selector
+        .split(',')
+        .map(str::trim)
+        .filter(|requirement| !requirement.is_empty())
+    {
+        if requirement.contains(" in ") || requirement.contains(" notin ") {
+            bail!(
+                "Set-based label selectors are not supported: {}",
+                requirement
+            );
+        }
+        if let Some((key, value)) = requirement.split_once("!=") {
+            match_expressions.push(VolumeGroupSnapshotSourceSelectorMatchExpressions {
+                key: key.trim().to_string(),
+                operator: "NotIn".to_string(),
+                values: Some(vec![value.trim().to_string()]),
+            });
+        } else if let Some((key, value)) = requirement
+            .split_once("==")
+            .or_else(|| requirement.split_once('='))
+        {
+            match_labels.insert(key.trim().to_string(), value.trim().to_string());
+        } else if let Some(key) = requirement.strip_prefix('!') {
+            match_expressions.push(VolumeGroupSnapshotSourceSelectorMatchExpressions {
+                key: key.trim().to_string(),
+                operator: "DoesNotExist".to_string(),
+                values: None,
+            });
+        } else {
+            match_expressions.push(VolumeGroupSnapshotSourceSelectorMatchExpressions {
+                key: requirement.to_string(),
+                operator: "Exists".to_string(),
+                values: None,
+            });
+        }
+    }
+    if match_labels.is_empty() && match_expressions.is_empty() {
+        bail!("The label selector of a VolumeGroupSnapshot cannot be empty");
+    }
+    Ok(VolumeGroupSnapshotSourceSelector {
+        match_labels: (!match_labels.is_empty()).then_some(match_labels),
+        match_expressions: (!match_expressions.is_empty()).then_some(match_expressions),
+    })
+}
+
+/// Construct a VolumeGroupSnapshot resource
+///
+/// # Arguments
+///
+/// * `name` - Name of the VolumeGroupSnapshot resource
+/// * `namespace` - Namespace of the VolumeGroupSnapshot resource
+/// * `volume_group_snapshot_class` - Name of the VolumeGroupSnapshotClass resource
+/// * `selector` - Selector of the PVCs of the group
+/// * `labels` - Labels of the VolumeGroupSnapshot resource
+///
+/// # Returns
+///
+/// VolumeGroupSnapshot resource
+pub fn construct_volume_group_snapshot_resource(
+    name: &str,
+    namespace: &str,
+    volume_group_snapshot_class: &str,
+    selector: VolumeGroupSnapshotSourceSelector,
+    labels: BTreeMap<String, String>,
+) -> VolumeGroupSnapshot {
+    VolumeGroupSnapshot {
+        metadata: ObjectMeta {
+            name: Some(name.to_string()),
+            namespace: Some(namespace.to_string()),
+            labels: Some(labels),
+            ..Default::default()
+        },
+        spec: VolumeGroupSnapshotSpec {
+            source: VolumeGroupSnapshotSource {
+                selector: Some(selector),
+                volume_group_snapshot_content_name: None,
+            },
+            volume_group_snapshot_class_name: Some(volume_group_snapshot_class.to_string()),
+        },
+        status: None,
+    }
+}
+
+/// Condition that holds once the VolumeGroupSnapshot is cut, or it has reported an error
+pub fn is_volume_group_snapshot_bound_or_failed() -> impl Condition<VolumeGroupSnapshot> {
+    |obj: Option<&VolumeGroupSnapshot>| {
+        obj.and_then(|group_snapshot| group_snapshot.status.as_ref())
+            .map(|status| {
+                (status.bound_volume_group_snapshot_content_name.is_some()
+                    && status.creation_time.is_some())
+                    || status.error.is_some()
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Condition that holds once the VolumeGroupSnapshot is ready to use, or it has reported an error
+pub fn is_volume_group_snapshot_ready_or_failed() -> impl Condition<VolumeGroupSnapshot> {
+    |obj: Option<&VolumeGroupSnapshot>| {
+        obj.and_then(|group_snapshot| group_snapshot.status.as_ref())
+            .map(|status| status.ready_to_use.unwrap_or(false) || status.error.is_some())
+            .unwrap_or(false)
+    }
+}
+
+/// Wait untill a condition holds on a VolumeGroupSnapshot
+///
+/// # Arguments
+///
+/// * `vgs_api` - Api object for VolumeGroupSnapshot
+/// * `volume_group_snapshot_name` - Name of the VolumeGroupSnapshot resource
+/// * `condition` - The condition to wait for
+/// * `timeout` - Maximum time to wait for the condition
+///
+/// # Returns
+///
+/// VolumeGroupSnapshotStatus, or a VolumeSnapshotError if the group snapshot failed or timed out
+pub async fn wait_untill_group_snapshot(
+    vgs_api: &kube::Api<VolumeGroupSnapshot>,
+    volume_group_snapshot_name: &str,
+    condition: impl Condition<VolumeGroupSnapshot>,
+    timeout: Duration,
+) -> Result<VolumeGroupSnapshotStatus> {
+    let group_snapshot = tokio::time::timeout(
+        timeout,
+        await_condition(vgs_api.clone(), volume_group_snapshot_name, condition),
+    )
+    .await
+    .map_err(|_| VolumeSnapshotError::Timeout {
+        name: volume_group_snapshot_name.to_string(),
+        timeout,
+    })??
+    .ok_or_else(|| VolumeSnapshotError::Deleted {
+        name: volume_group_snapshot_name.to_string(),
+    })?;
+
+    let status = group_snapshot.status.unwrap_or_default();
+    if let Some(error) = status.error {
+        return Err(VolumeSnapshotError::Failed {
+            name: volume_group_snapshot_name.to_string(),
+            message: error.message.unwrap_or_default(),
+        }
+        .into());
+    }
+    info!(
+        "VolumeGroupSnapshot {} is bound to {}, ready: {}",
+        volume_group_snapshot_name,
+        status
+            .bound_volume_group_snapshot_content_name
+            .clone()
+            .unwrap_or_default(),
+        status.ready_to_use.unwrap_or(false)
+    );
+    Ok(status)
+}
+
+/// Get the members of a VolumeGroupSnapshot
+///
+/// # Arguments
+///
+/// * `status` - Status of the VolumeGroupSnapshot
+///
+/// # Returns
+///
+/// The name of each PVC of the group mapped to the name of its VolumeSnapshot
+pub fn group_snapshot_members(status: &VolumeGroupSnapshotStatus) -> BTreeMap<String, String> {
+    status
+        .pvc_volume_snapshot_ref_list
+        .iter()
+        .flatten()
+        .filter_map(|member| {
+            Some((
+                member.persistent_volume_claim_ref.as_ref()?.name.clone()?,
+                member.volume_snapshot_ref.as_ref()?.name.clone()?,
+            ))
+        })
+        .collect()
+}
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+    use crate::k8s_ops::vgs::volume_group_snapshots::{
+        construct_volume_group_snapshot_resource, group_snapshot_members, group_snapshot_selector,
+        is_volume_group_snapshot_bound_or_failed, is_volume_group_snapshot_ready_or_failed,
+    };
+    use kube::runtime::wait::Condition;
+    use kube_custom_resources_rs::groupsnapshot_storage_k8s_io::v1alpha1::volumegroupsnapshots::{
+        VolumeGroupSnapshot, VolumeGroupSnapshotSourceSelectorMatchExpressions,
+        VolumeGroupSnapshotStatus, VolumeGroupSnapshotStatusError,
+        VolumeGroupSnapshotStatusPvcVolumeSnapshotRefList,
+        VolumeGroupSnapshotStatusPvcVolumeSnapshotRefListPersistentVolumeClaimRef,
+        VolumeGroupSnapshotStatusPvcVolumeSnapshotRefListVolumeSnapshotRef,
+    };
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    fn group_snapshot(status: Option<VolumeGroupSnapshotStatus>) -> VolumeGroupSnapshot {
+        let mut group_snapshot = construct_volume_group_snapshot_resource(
+            "postgres-group",
+            "default",
+            "csi-aws-vgsc",
+            group_snapshot_selector("app=postgres").unwrap(),
+            BTreeMap::new(),
+        );
+        group_snapshot.status = status;
+        group_snapshot
+    }
+
+    #[test]
+    fn test_group_snapshot_selector() {
+        let selector =
+            group_snapshot_selector("app=postgres, tier!=cache,backup,!scratch").unwrap();
+        assert_eq!(
+            selector.match_labels,
+            Some(BTreeMap::from([(
+                "app".to_string(),
+                "postgres".to_string()
+            )]))
+        );
+        assert_eq!(
+            selector.match_expressions,
+            Some(vec![
+                VolumeGroupSnapshotSourceSelectorMatchExpressions {
+                    key: "tier".to_string(),
+                    operator: "NotIn".to_string(),
+                    values: Some(vec!["cache".to_string()]),
+                },
+                VolumeGroupSnapshotSourceSelectorMatchExpressions {
+                    key: "backup".to_string(),
+                    operator: "Exists".to_string(),
+                    values: None,
+                },
+                VolumeGroupSnapshotSourceSelectorMatchExpressions {
+                    key: "scratch".to_string(),
+                    operator: "DoesNotExist".to_string(),
+                    values: None,
+                },
+            ])
+        );
+
+        assert!(group_snapshot_selector("tier in (db,cache)").is_err());
+        assert!(group_snapshot_selector(" , ").is_err());
+    }
+
+    #[test]
+    fn test_construct_volume_group_snapshot_resource() {
+        let group_snapshot = construct_volume_group_snapshot_resource(
+            "postgres-group",
+            "default",
+            "csi-aws-vgsc",
+            group_snapshot_selector("app=postgres").unwrap(),
+            BTreeMap::from([("snap-kube/backup-id".to_string(), "abc".to_string())]),
+        );
+        assert_eq!(group_snapshot.metadata.name.unwrap(), "postgres-group");
+        assert_eq!(group_snapshot.metadata.namespace.unwrap(), "default");
+        assert_eq!(
+            group_snapshot
+                .metadata
+                .labels
+                .unwrap()
+                .get("snap-kube/backup-id"),
+            Some(&"abc".to_string())
+        );
+        assert_eq!(
+            group_snapshot
+                .spec
+                .volume_group_snapshot_class_name
+                .unwrap(),
+            "csi-aws-vgsc"
+        );
+        assert_eq!(
+            group_snapshot
+                .spec
+                .source
+                .selector
+                .unwrap()
+                .match_labels
+                .unwrap()
+                .get("app"),
+            Some(&"postgres".to_string())
+        );
+        assert!(group_snapshot.status.is_none());
+    }
+
+    #[test]
+    fn test_volume_group_snapshot_conditions() {
+        let pending = group_snapshot(Some(VolumeGroupSnapshotStatus::default()));
+        let bound = group_snapshot(Some(VolumeGroupSnapshotStatus {
+            bound_volume_group_snapshot_content_name: Some("groupsnapcontent-1".to_string()),
+            creation_time: Some("2024-09-01T00:00:00Z".to_string()),
+            ready_to_use: Some(false),
+            ..Default::default()
+        }));
+        let ready = group_snapshot(Some(VolumeGroupSnapshotStatus {
+            ready_to_use: Some(true),
+            ..bound.status.clone().unwrap()
+        }));
+        let failed = group_snapshot(Some(VolumeGroupSnapshotStatus {
+            error: Some(VolumeGroupSnapshotStatusError {
+                message: Some("driver does not support group snapshots".to_string()),
+                time: None,
+            }),
+            ..Default::default()
+        }));
+
+        let bound_or_failed = is_volume_group_snapshot_bound_or_failed();
+        assert!(!bound_or_failed.matches_object(None));
+        assert!(!bound_or_failed.matches_object(Some(&group_snapshot(None))));
+        assert!(!bound_or_failed.matches_object(Some(&pending)));
+        assert!(bound_or_failed.matches_object(Some(&bound)));
+        assert!(bound_or_failed.matches_object(Some(&failed)));
+
+        let ready_or_failed = is_volume_group_snapshot_ready_or_failed();
+        assert!(!ready_or_failed.matches_object(Some(&pending)));
+        assert!(!ready_or_failed.matches_object(Some(&bound)));
+        assert!(ready_or_failed.matches_object(Some(&ready)));
+        assert!(ready_or_failed.matches_object(Some(&failed)));
+    }
+
+    #[test]
+    fn test_group_snapshot_members() {
+        let member = |pvc: Option<&str>, volume_snapshot: Option<&str>| {
+            VolumeGroupSnapshotStatusPvcVolumeSnapshotRefList {
+                persistent_volume_claim_ref: Some(
+                    VolumeGroupSnapshotStatusPvcVolumeSnapshotRefListPersistentVolumeClaimRef {
+                        name: pvc.map(str::to_string),
+                    },
+                ),
+                volume_snapshot_ref: Some(
+                    VolumeGroupSnapshotStatusPvcVolumeSnapshotRefListVolumeSnapshotRef {
+                        name: volume_snapshot.map(str::to_string),
+                    },
+                ),
+            }
+        };
+        let status = VolumeGroupSnapshotStatus {
+            pvc_volume_snapshot_ref_list: Some(vec![
+                member(Some("data-postgres-0"), Some("snapshot-1")),
+                member(Some("wal-postgres-0"), Some("snapshot-2")),
+                // Not created yet
+                member(Some("logs-postgres-0"), None),
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            group_snapshot_members(&status),
+            BTreeMap::from([
+                ("data-postgres-0".to_string(), "snapshot-1".to_string()),
+                ("wal-postgres-0".to_string(), "snapshot-2".to_string()),
+            ])
+        );
+        assert!(group_snapshot_members(&VolumeGroupSnapshotStatus::default()).is_empty());
+    }
+}
@@ -1,6 +1,10 @@
+pub mod volume_snapshot_naming;
 #[cfg(feature = "backup")]
 pub mod volume_snapshots;
+pub mod volume_snapshots_error;
 pub mod volume_snapshots_operator;
 
+#[cfg(test)]
+mod volume_snapshot_naming_tests;
 #[cfg(test)]
 mod volume_snapshots_tests;
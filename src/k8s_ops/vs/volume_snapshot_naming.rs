@@ -0,0 +1,285 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use kube::{api::ListParams, Api};
+use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshots::VolumeSnapshot;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// Default template of the VolumeSnapshot names, versioned by the time of the backup
+pub const DEFAULT_VS_NAME_TEMPLATE: &str = "{prefix}-{pvc}-{timestamp}";
+
+/// Maximum length of a Kubernetes resource name (DNS subdomain)
+pub const MAX_NAME_LENGTH: usize = 253;
+
+/// Maximum length of a Kubernetes label value
+pub const MAX_LABEL_VALUE_LENGTH: usize = 63;
+
+/// Label set on every resource created by snap-kube
+pub const MANAGED_BY_LABEL: &str = "app.kubernetes.io/managed-by";
+/// Label holding the name of the PVC a VolumeSnapshot was taken from
+pub const SOURCE_PVC_LABEL: &str = "snap-kube/source-pvc";
+/// Label holding the name prefix of a VolumeSnapshot
+pub const NAME_PREFIX_LABEL: &str = "snap-kube/name-prefix";
+/// Label holding the ID of the backup run a VolumeSnapshot belongs to
+pub const BACKUP_ID_LABEL: &str = "snap-kube/backup-id";
+/// Label holding how a VolumeSnapshot came to be, a backup or the import of an EBS snapshot
+pub const ORIGIN_LABEL: &str = "snap-kube/origin";
+/// Origin of the VolumeSnapshots taken by a backup
+pub const BACKUP_ORIGIN: &str = "backup";
+/// Origin of the VolumeSnapshots importing an existing EBS snapshot
+pub const IMPORT_ORIGIN: &str = "import";
+
+/// Placeholders supported in the VolumeSnapshot name templates
+const PLACEHOLDERS: [&str; 5] = ["prefix", "namespace", "pvc", "timestamp", "hash"];
+
+/// Length of the short hashes used in names and label values
+const HASH_LENGTH: usize = 8;
+
+/// A template for the names of the VolumeSnapshots taken by a backup
+///
+/// The template supports the `{prefix}`, `{namespace}`, `{pvc}`, `{timestamp}` and `{hash}`
+/// placeholders, where `{hash}` is a short hash of the namespace, the PVC and the timestamp.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VolumeSnapshotNameTemplate {
+    template: String,
+}
+
+impl Default for VolumeSnapshotNameTemplate {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_VS_NAME_TEMPLATE.to_string(),
+        }
+    }
+}
+
+impl VolumeSnapshotNameTemplate {
+    /// Creates a new VolumeSnapshotNameTemplate
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - Template of the names, which must reference `{pvc}` or `{hash}` so that
+    ///   every PVC gets its own VolumeSnapshot
+    ///
+    /// # Returns
+    ///
+    /// A new VolumeSnapshotNameTemplate instance, or an error if the template is invalid
+    pub fn new(template: impl Into<String>) -> Result<Self> {
+        let template = template.into();
+        let placeholder = Regex::new(r"\{([^{}]*)\}")?;
+        for captures in placeholder.captures_iter(&template) {
+            if !PLACEHOLDERS.contains(&&captures[1]) {
+                bail!(
+                    "Unknown placeholder {{{}}} in VolumeSnapshot name template: {}",
+                    &captures[1],
+                    template
+                );
+            }
+        }
+        if !template.contains("{pvc}") && !template.contains("{hash}") {
+            bail!(
+                "VolumeSnapshot name template must contain {{pvc}} or {{hash}}: {}",
+                template
+            );
+        }
+        Ok(Self { template })
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Render the name of the VolumeSnapshot of a PVC
+    ///
+    /// The rendered name is sanitized to a valid DNS subdomain. If it is longer than 253
+    /// characters, it is truncated and suffixed with a short hash of the full name.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - VolumeSnapshot name prefix
+    /// * `namespace` - Namespace of the PVC
+    /// * `pvc` - Name of the PVC
+    /// * `created_at` - Time of the backup
+    ///
+    /// # Returns
+    ///
+    /// The VolumeSnapshot name
+    pub fn render(
+        &self,
+        prefix: &str,
+        namespace: &str,
+        pvc: &str,
+        created_at: DateTime<Utc>,
+    ) -> String {
+        let timestamp = created_at.format("%Y%m%d-%H%M%S").to_string();
+        let hash = short_hash(&format!(
+            "{}/{}/{}",
+            namespace,
+            pvc,
+            created_at.to_rfc3339()
+        ));
+        let name = self
+            .template
+            .replace("{prefix}", prefix)
+            .replace("{namespace}", namespace)
+            .replace("{pvc}", pvc)
+            .replace("{timestamp}", &timestamp)
+            .replace("{hash}", &hash);
+        truncate_with_hash(&sanitize_name(&name), MAX_NAME_LENGTH)
+    }
+}
+
+/// The name of the VolumeSnapshotContent restoring a VolumeSnapshot in a target namespace
+///
+/// VolumeSnapshotContents are cluster-scoped, so the name is derived from the versioned name
+/// of the VolumeSnapshot and from its namespace, and each restored snapshot gets its own one.
+///
+/// # Arguments
+///
+/// * `prefix` - VolumeSnapshotContent name prefix
+/// * `namespace` - Target namespace
+/// * `volume_snapshot_name` - Name of the VolumeSnapshot in the target namespace
+///
+/// # Returns
+///
+/// The VolumeSnapshotContent name
+pub fn restored_snapshot_content_name(
+    prefix: &str,
+    namespace: &str,
+    volume_snapshot_name: &str,
+) -> String {
+    let name = format!("{}-{}-{}", prefix, namespace, volume_snapshot_name);
+    truncate_with_hash(&sanitize_name(&name), MAX_NAME_LENGTH)
+}
+
+/// Compute a short hex hash of a value
+pub fn short_hash(value: &str) -> String {
+    Sha256::digest(value.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>()[..HASH_LENGTH]
+        .to_string()
+}
+
+/// Convert a value to a valid label value
+///
+/// Values longer than 63 characters are truncated and suffixed with a short hash of the
+/// full value, so that distinct values keep distinct label values.
+pub fn to_label_value(value: &str) -> String {
+    truncate_with_hash(&sanitize_name(value), MAX_LABEL_VALUE_LENGTH)
+}
+
+/// The labels of a VolumeSnapshot taken by a backup
+///
+/// # Arguments
+///
+/// * `prefix` - VolumeSnapshot name prefix
+/// * `pvc` - Name of the source PVC
+/// * `backup_id` - ID of the backup run
+///
+/// # Returns
+///
+/// The labels of the VolumeSnapshot
+pub fn volume_snapshot_labels(
+    prefix: &str,
+    pvc: &str,
+    backup_id: &str,
+) -> BTreeMap<String, String> {
+    BTreeMap::from([
+        (MANAGED_BY_LABEL.to_string(), "snap-kube".to_string()),
+        (SOURCE_PVC_LABEL.to_string(), to_label_value(pvc)),
+        (NAME_PREFIX_LABEL.to_string(), to_label_value(prefix)),
+        (BACKUP_ID_LABEL.to_string(), to_label_value(backup_id)),
+        (ORIGIN_LABEL.to_string(), BACKUP_ORIGIN.to_string()),
+    ])
+}
+
+/// The label selector of the VolumeSnapshots taken from a PVC with a name prefix
+pub fn volume_snapshot_label_selector(prefix: &str, pvc: &str) -> String {
+    format!(
+        "{}=snap-kube,{}={},{}={}",
+        MANAGED_BY_LABEL,
+        SOURCE_PVC_LABEL,
+        to_label_value(pvc),
+        NAME_PREFIX_LABEL,
+        to_label_value(prefix)
+    )
+}
+
+/// Select the newest ready VolumeSnapshot of a PVC
+///
+/// The VolumeSnapshots are ordered by their creation timestamp, and then by name.
+///
+/// # Arguments
+///
+/// * `volume_snapshots` - Candidate VolumeSnapshots
+/// * `pvc` - Name of the source PVC
+///
+/// # Returns
+///
+/// The newest ready VolumeSnapshot taken from the PVC, if any
+pub fn select_latest_volume_snapshot(
+    volume_snapshots: Vec<VolumeSnapshot>,
+    pvc: &str,
+) -> Option<VolumeSnapshot> {
+    volume_snapshots
+        .into_iter()
+        .filter(|vs| vs.spec.source.persistent_volume_claim_name.as_deref() == Some(pvc))
+        .filter(|vs| {
+            vs.status
+                .as_ref()
+                .and_then(|status| status.ready_to_use)
+                .unwrap_or(false)
+        })
+        .max_by_key(|vs| {
+            (
+                vs.metadata.creation_timestamp.clone().map(|time| time.0),
+                vs.metadata.name.clone(),
+            )
+        })
+}
+
+/// Find the newest ready VolumeSnapshot taken from a PVC with a name prefix
+///
+/// # Arguments
+///
+/// * `vs_api` - VolumeSnapshot API of the namespace of the PVC
+/// * `prefix` - VolumeSnapshot name prefix
+/// * `pvc` - Name of the source PVC
+///
+/// # Returns
+///
+/// The newest ready VolumeSnapshot, if any
+pub async fn find_latest_volume_snapshot(
+    vs_api: &Api<VolumeSnapshot>,
+    prefix: &str,
+    pvc: &str,
+) -> Result<Option<VolumeSnapshot>> {
+    let list_params = ListParams::default().labels(&volume_snapshot_label_selector(prefix, pvc));
+    let volume_snapshots = vs_api.list(&list_params).await?.items;
+    Ok(select_latest_volume_snapshot(volume_snapshots, pvc))
+}
+
+/// Replace the characters that are not allowed in a DNS subdomain
+fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' | '-' | '.' => c,
+            _ => '-',
+        })
+        .collect();
+    name.trim_matches(|c: char| !c.is_ascii_alphanumeric())
+        .to_string()
+}
+
+/// Truncate a name to a maximum length, suffixing it with a short hash of the full name
+fn truncate_with_hash(name: &str, max_length: usize) -> String {
+    if name.len() <= max_length {
+        return name.to_string();
+    }
+    let truncated =
+        name[..max_length - HASH_LENGTH - 1].trim_end_matches(|c: char| !c.is_ascii_alphanumeric());
+    format!("{}-{}", truncated, short_hash(name))
+}
@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use crate::k8s_ops::vs::volume_snapshot_naming::{
+        restored_snapshot_content_name, select_latest_volume_snapshot, to_label_value,
+        volume_snapshot_label_selector, volume_snapshot_labels, VolumeSnapshotNameTemplate,
+        BACKUP_ORIGIN, MAX_LABEL_VALUE_LENGTH, MAX_NAME_LENGTH, ORIGIN_LABEL, SOURCE_PVC_LABEL,
+    };
+    use chrono::{TimeZone, Utc};
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+    use kube::api::ObjectMeta;
+    use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshots::{
+        VolumeSnapshot, VolumeSnapshotSource, VolumeSnapshotSpec, VolumeSnapshotStatus,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn volume_snapshot(name: &str, pvc: &str, hour: u32, ready: bool) -> VolumeSnapshot {
+        VolumeSnapshot {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                creation_timestamp: Some(Time(
+                    Utc.with_ymd_and_hms(2024, 10, 18, hour, 0, 0).unwrap(),
+                )),
+                ..Default::default()
+            },
+            spec: VolumeSnapshotSpec {
+                source: VolumeSnapshotSource {
+                    persistent_volume_claim_name: Some(pvc.to_string()),
+                    volume_snapshot_content_name: None,
+                },
+                volume_snapshot_class_name: None,
+            },
+            status: Some(VolumeSnapshotStatus {
+                ready_to_use: Some(ready),
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_render_default_template() {
+        let created_at = Utc.with_ymd_and_hms(2024, 10, 18, 12, 30, 0).unwrap();
+
+        assert_eq!(
+            VolumeSnapshotNameTemplate::default().render("nightly", "test-ns", "data", created_at),
+            "nightly-data-20241018-123000"
+        );
+    }
+
+    #[test]
+    fn test_render_sanitizes_and_hashes() {
+        let created_at = Utc.with_ymd_and_hms(2024, 10, 18, 12, 30, 0).unwrap();
+        let template = VolumeSnapshotNameTemplate::new("{namespace}_{PVC-placeholder}").err();
+        assert!(template.is_some());
+
+        let template =
+            VolumeSnapshotNameTemplate::new("{prefix}.{namespace}.{pvc}-{hash}").unwrap();
+        let name = template.render("Nightly", "test-ns", "data_0", created_at);
+        assert!(name.starts_with("nightly.test-ns.data-0-"));
+        assert_eq!(name.len(), "nightly.test-ns.data-0-".len() + 8);
+        assert_ne!(
+            name,
+            template.render(
+                "Nightly",
+                "test-ns",
+                "data_0",
+                created_at + chrono::Duration::seconds(1)
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_respects_name_length() {
+        let created_at = Utc.with_ymd_and_hms(2024, 10, 18, 12, 30, 0).unwrap();
+        let template = VolumeSnapshotNameTemplate::default();
+        let first = template.render("nightly", "test-ns", &"a".repeat(250), created_at);
+        let second = template.render("nightly", "test-ns", &"a".repeat(251), created_at);
+
+        assert_eq!(first.len(), MAX_NAME_LENGTH);
+        assert_eq!(second.len(), MAX_NAME_LENGTH);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_restored_snapshot_content_name() {
+        assert_eq!(
+            restored_snapshot_content_name("vsc", "qa", "nightly-data-20241018-120000"),
+            "vsc-qa-nightly-data-20241018-120000"
+        );
+        assert_ne!(
+            restored_snapshot_content_name("vsc", "qa", "nightly-data-20241018-120000"),
+            restored_snapshot_content_name("vsc", "qa", "nightly-data-20241019-120000")
+        );
+        assert_ne!(
+            restored_snapshot_content_name("vsc", "qa", "nightly-data-20241018-120000"),
+            restored_snapshot_content_name("vsc", "staging", "nightly-data-20241018-120000")
+        );
+
+        let long_name = restored_snapshot_content_name("vsc", "qa", &"a".repeat(260));
+        assert_eq!(long_name.len(), MAX_NAME_LENGTH);
+    }
+
+    #[test]
+    fn test_template_requires_pvc_or_hash() {
+        assert!(VolumeSnapshotNameTemplate::new("{prefix}-{timestamp}").is_err());
+        assert!(VolumeSnapshotNameTemplate::new("{prefix}-{hash}").is_ok());
+    }
+
+    #[test]
+    fn test_volume_snapshot_labels() {
+        let pvc = "p".repeat(100);
+        let labels = volume_snapshot_labels("nightly", &pvc, "20241018-120000-00ab");
+
+        assert_eq!(labels[SOURCE_PVC_LABEL].len(), MAX_LABEL_VALUE_LENGTH);
+        assert_eq!(labels[SOURCE_PVC_LABEL], to_label_value(&pvc));
+        assert_eq!(labels[ORIGIN_LABEL], BACKUP_ORIGIN);
+        assert_eq!(
+            volume_snapshot_label_selector("nightly", "data"),
+            "app.kubernetes.io/managed-by=snap-kube,snap-kube/source-pvc=data,snap-kube/name-prefix=nightly"
+        );
+    }
+
+    #[test]
+    fn test_select_latest_volume_snapshot() {
+        let volume_snapshots = vec![
+            volume_snapshot("nightly-data-1", "data", 10, true),
+            volume_snapshot("nightly-data-3", "data", 12, false),
+            volume_snapshot("nightly-data-2", "data", 11, true),
+            volume_snapshot("nightly-other-4", "other", 13, true),
+        ];
+
+        let latest = select_latest_volume_snapshot(volume_snapshots, "data").unwrap();
+        assert_eq!(latest.metadata.name.unwrap(), "nightly-data-2");
+        assert!(select_latest_volume_snapshot(vec![], "data").is_none());
+    }
+}
@@ -1,61 +1,269 @@
-use crate::aws_ops::ebs::get_ebs_snapshot_progress;
+use super::volume_snapshots_error::VolumeSnapshotError;
+use crate::aws_ops::ebs::{get_ebs_snapshot_progress, EbsSnapshotProgress};
 use crate::k8s_ops::vsc::volume_snapshot_contents::get_snapshot_handle;
 use anyhow::Result;
 use aws_sdk_ec2::Client as EbsClient;
+use kube::runtime::wait::{await_condition, Condition};
 use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshotcontents::VolumeSnapshotContent;
 use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshots::{
     VolumeSnapshot, VolumeSnapshotStatus,
 };
 use std::time::Duration;
-use tokio::time::sleep;
+use tokio::time::{interval, sleep};
 use tracing::{info, warn};
 
+/// Interval between two progress reports while waiting for a VolumeSnapshot
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Condition that holds once the VolumeSnapshot is ready to use
+///
+/// An error reported in its status does not end the wait, as the snapshot controller
+/// retries and then clears the errors it can recover from.
+pub fn is_volume_snapshot_ready() -> impl Condition<VolumeSnapshot> {
+    |obj: Option<&VolumeSnapshot>| {
+        obj.and_then(|snapshot| snapshot.status.as_ref())
+            .and_then(|status| status.ready_to_use)
+            .unwrap_or(false)
+    }
+}
+
 /// Wait untill the VolumeSnapshot is ready
 ///
+/// The VolumeSnapshot is watched instead of polled. Meanwhile, the VolumeSnapshot, its bound
+/// VolumeSnapshotContent and the EBS snapshot are checked periodically, to report the
+/// progress and to detect errors. An EBS snapshot in the error state fails the wait at once,
+/// whereas an error reported by the VolumeSnapshot or the VolumeSnapshotContent only fails it
+/// if it is still there at the timeout, since the snapshot controller retries it. A failed
+/// request to the Kubernetes or EC2 APIs during these checks is only logged, as the snapshot
+/// may still be progressing.
+///
 /// # Arguments
 ///
 /// * `vs_api` - Api object for VolumeSnapshot
 /// * `vsc_api` - Api object for VolumeSnapshotContent
 /// * `ebs_client` - EBS Client object
 /// * `volume_snapshot_name` - Name of the VolumeSnapshot resource
+/// * `timeout` - Maximum time to wait for the VolumeSnapshot to be ready
 ///
 /// # Returns
 ///
-/// VolumeSnapshotStatus
+/// VolumeSnapshotStatus, or a VolumeSnapshotError if the snapshot failed or timed out
 pub async fn wait_untill_snapshot_is_ready(
     vs_api: &kube::Api<VolumeSnapshot>,
     vsc_api: &kube::Api<VolumeSnapshotContent>,
     ebs_client: &EbsClient,
     volume_snapshot_name: &str,
+    timeout: Duration,
 ) -> Result<VolumeSnapshotStatus> {
+    let ready = await_condition(
+        vs_api.clone(),
+        volume_snapshot_name,
+        is_volume_snapshot_ready(),
+    );
+    tokio::pin!(ready);
+    let deadline = sleep(timeout);
+    tokio::pin!(deadline);
+    let mut progress_interval = interval(PROGRESS_INTERVAL);
+    let mut last_error = None;
+
     loop {
-        let snapshot = vs_api.get(volume_snapshot_name).await?;
-        if let Some(status) = snapshot.status {
-            if status.ready_to_use.unwrap_or(false) {
+        tokio::select! {
+            snapshot = &mut ready => {
+                let snapshot = snapshot?.ok_or_else(|| VolumeSnapshotError::Deleted {
+                    name: volume_snapshot_name.to_string(),
+                })?;
+                let status = snapshot.status.unwrap_or_default();
                 info!("Snapshot is ready: {:?}", status);
                 return Ok(status);
             }
-            info!("Waiting for VolumeSnapshot to be ready...");
-
-            let vsc_name = status.bound_volume_snapshot_content_name.unwrap();
-            match get_snapshot_handle(vsc_api.clone(), &vsc_name).await {
-                Ok(snapshot_handle) => {
-                    let progress =
-                        get_ebs_snapshot_progress(ebs_client.clone(), snapshot_handle.clone())
-                            .await?;
+            _ = &mut deadline => {
+                // Check once more, so that an error reported since the last check is not
+                // reported as a mere timeout
+                if let Some(error) =
+                    check_snapshot_progress(vs_api, vsc_api, ebs_client, volume_snapshot_name)
+                        .await?
+                        .or(last_error)
+                {
+                    return Err(error.into());
+                }
+                return Err(VolumeSnapshotError::Timeout {
+                    name: volume_snapshot_name.to_string(),
+                    timeout,
+                }
+                .into());
+            }
+            _ = progress_interval.tick() => {
+                last_error =
+                    check_snapshot_progress(vs_api, vsc_api, ebs_client, volume_snapshot_name)
+                        .await?;
+                if let Some(error) = &last_error {
+                    warn!("{}, waiting for it to be retried", error);
+                }
+            }
+        }
+    }
+}
+
+/// Condition that holds once the point in time of the VolumeSnapshot is set, or it has reported an error
+pub fn is_volume_snapshot_bound_or_failed() -> impl Condition<VolumeSnapshot> {
+    |obj: Option<&VolumeSnapshot>| {
+        obj.and_then(|snapshot| snapshot.status.as_ref())
+            .map(|status| {
+                (status.bound_volume_snapshot_content_name.is_some()
+                    && status.creation_time.is_some())
+                    || status.error.is_some()
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Wait untill the VolumeSnapshot is bound to a VolumeSnapshotContent and the snapshot is cut
+///
+/// The data of the snapshot is fixed from then on, even though the EBS snapshot may still be
+/// uploading, so it is safe to resume the writes of the application.
+///
+/// # Arguments
+///
+/// * `vs_api` - Api object for VolumeSnapshot
+/// * `volume_snapshot_name` - Name of the VolumeSnapshot resource
+/// * `timeout` - Maximum time to wait for the VolumeSnapshot to be bound
+///
+/// # Returns
+///
+/// VolumeSnapshotStatus, or a VolumeSnapshotError if the snapshot failed or timed out
+pub async fn wait_untill_snapshot_is_bound(
+    vs_api: &kube::Api<VolumeSnapshot>,
+    volume_snapshot_name: &str,
+    timeout: Duration,
+) -> Result<VolumeSnapshotStatus> {
+    let snapshot = tokio::time::timeout(
+        timeout,
+        await_condition(
+            vs_api.clone(),
+            volume_snapshot_name,
+            is_volume_snapshot_bound_or_failed(),
+        ),
+    )
+    .await
+    .map_err(|_| VolumeSnapshotError::Timeout {
+        name: volume_snapshot_name.to_string(),
+        timeout,
+    })??
+    .ok_or_else(|| VolumeSnapshotError::Deleted {
+        name: volume_snapshot_name.to_string(),
+    })?;
+
+    let status = snapshot.status.unwrap_or_default();
+    if let Some(error) = status.error {
+        return Err(VolumeSnapshotError::Failed {
+            name: volume_snapshot_name.to_string(),
+            message: error.message.unwrap_or_default(),
+        }
+        .into());
+    }
+    info!(
+        "VolumeSnapshot {} is bound to {}",
+        volume_snapshot_name,
+        status
+            .bound_volume_snapshot_content_name
+            .clone()
+            .unwrap_or_default()
+    );
+    Ok(status)
+}
+
+/// Log the progress of a VolumeSnapshot that is not ready yet
+///
+/// # Arguments
+///
+/// * `vs_api` - Api object for VolumeSnapshot
+/// * `vsc_api` - Api object for VolumeSnapshotContent
+/// * `ebs_client` - EBS Client object
+/// * `volume_snapshot_name` - Name of the VolumeSnapshot resource
+///
+/// # Returns
+///
+/// The error currently reported by the VolumeSnapshot or its VolumeSnapshotContent, which
+/// may still be retried, or a VolumeSnapshotError if the EBS snapshot failed for good
+async fn check_snapshot_progress(
+    vs_api: &kube::Api<VolumeSnapshot>,
+    vsc_api: &kube::Api<VolumeSnapshotContent>,
+    ebs_client: &EbsClient,
+    volume_snapshot_name: &str,
+) -> Result<Option<VolumeSnapshotError>, VolumeSnapshotError> {
+    info!("Waiting for VolumeSnapshot to be ready...");
+
+    let volume_snapshot = match vs_api.get_opt(volume_snapshot_name).await {
+        Ok(volume_snapshot) => volume_snapshot,
+        Err(e) => {
+            warn!(
+                "Failed to get VolumeSnapshot {}: {}",
+                volume_snapshot_name, e
+            );
+            return Ok(None);
+        }
+    };
+    let status = volume_snapshot
+        .and_then(|snapshot| snapshot.status)
+        .unwrap_or_default();
+    if let Some(error) = status.error {
+        return Ok(Some(VolumeSnapshotError::Failed {
+            name: volume_snapshot_name.to_string(),
+            message: error.message.unwrap_or_default(),
+        }));
+    }
+    let Some(vsc_name) = status.bound_volume_snapshot_content_name else {
+        return Ok(None);
+    };
+
+    let volume_snapshot_content = match vsc_api.get_opt(&vsc_name).await {
+        Ok(volume_snapshot_content) => volume_snapshot_content,
+        Err(e) => {
+            warn!("Failed to get VolumeSnapshotContent {}: {}", vsc_name, e);
+            return Ok(None);
+        }
+    };
+    if let Some(error) = volume_snapshot_content
+        .and_then(|snapshot_content| snapshot_content.status)
+        .and_then(|status| status.error)
+    {
+        return Ok(Some(VolumeSnapshotError::ContentFailed {
+            name: volume_snapshot_name.to_string(),
+            vsc_name,
+            message: error.message.unwrap_or_default(),
+        }));
+    }
+
+    match get_snapshot_handle(vsc_api.clone(), &vsc_name).await {
+        Ok(snapshot_handle) => {
+            match get_ebs_snapshot_progress(ebs_client.clone(), snapshot_handle.clone()).await {
+                Ok(EbsSnapshotProgress::Failed(message)) => {
+                    return Err(VolumeSnapshotError::EbsSnapshotFailed {
+                        name: volume_snapshot_name.to_string(),
+                        snapshot_handle,
+                        message,
+                    });
+                }
+                Ok(EbsSnapshotProgress::Pending(progress)) => {
                     info!(
-                        "{}",
-                        format!(
-                            "Progress for EBS snapshot {} regarding VS {} is: {}",
-                            snapshot_handle, volume_snapshot_name, progress
-                        )
+                        "Progress for EBS snapshot {} regarding VS {} is: {}",
+                        snapshot_handle, volume_snapshot_name, progress
+                    );
+                }
+                Ok(EbsSnapshotProgress::Completed) => {
+                    info!(
+                        "EBS snapshot {} regarding VS {} is completed",
+                        snapshot_handle, volume_snapshot_name
                     );
                 }
                 Err(e) => {
-                    warn!("Failed to get snapshot handle: {}", e);
+                    warn!("Failed to get EBS snapshot {}: {:#}", snapshot_handle, e);
                 }
             }
-            sleep(Duration::from_secs(5)).await;
+        }
+        Err(e) => {
+            warn!("Failed to get snapshot handle: {}", e);
         }
     }
+    Ok(None)
 }
@@ -0,0 +1,69 @@
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// Represents the reasons a VolumeSnapshot can fail to become ready
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VolumeSnapshotError {
+    /// The VolumeSnapshot did not become ready within the timeout
+    Timeout { name: String, timeout: Duration },
+    /// The VolumeSnapshot reported an error in its status
+    Failed { name: String, message: String },
+    /// The bound VolumeSnapshotContent reported an error in its status
+    ContentFailed {
+        name: String,
+        vsc_name: String,
+        message: String,
+    },
+    /// The EBS snapshot behind the VolumeSnapshot is in the error state
+    EbsSnapshotFailed {
+        name: String,
+        snapshot_handle: String,
+        message: String,
+    },
+    /// The VolumeSnapshot was deleted while waiting for it
+    Deleted { name: String },
+    /// The VolumeSnapshot is ready but a field of its status is not set
+    Incomplete { name: String, field: String },
+}
+
+impl Display for VolumeSnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VolumeSnapshotError::Timeout { name, timeout } => write!(
+                f,
+                "VolumeSnapshot {} was not ready after {}s",
+                name,
+                timeout.as_secs()
+            ),
+            VolumeSnapshotError::Failed { name, message } => {
+                write!(f, "VolumeSnapshot {} failed: {}", name, message)
+            }
+            VolumeSnapshotError::ContentFailed {
+                name,
+                vsc_name,
+                message,
+            } => write!(
+                f,
+                "VolumeSnapshotContent {} of VolumeSnapshot {} failed: {}",
+                vsc_name, name, message
+            ),
+            VolumeSnapshotError::EbsSnapshotFailed {
+                name,
+                snapshot_handle,
+                message,
+            } => write!(
+                f,
+                "EBS snapshot {} of VolumeSnapshot {} failed: {}",
+                snapshot_handle, name, message
+            ),
+            VolumeSnapshotError::Deleted { name } => {
+                write!(f, "VolumeSnapshot {} was deleted", name)
+            }
+            VolumeSnapshotError::Incomplete { name, field } => {
+                write!(f, "VolumeSnapshot {} is ready but has no {}", name, field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VolumeSnapshotError {}
@@ -26,6 +26,7 @@ pub struct VolumeSnapshotOperator {
     pub volume_snapshot_class: String,
     pub source_pvc_name: Option<String>,
     pub vsc_name: Option<String>,
+    pub labels: BTreeMap<String, String>,
 }
 
 impl VolumeSnapshotOperator {
@@ -35,6 +36,7 @@ impl VolumeSnapshotOperator {
         volume_snapshot_class: String,
         source_pvc_name: Option<String>,
         vsc_name: Option<String>,
+        labels: BTreeMap<String, String>,
     ) -> Self {
         Self {
             name,
@@ -42,6 +44,7 @@ impl VolumeSnapshotOperator {
             volume_snapshot_class,
             source_pvc_name,
             vsc_name,
+            labels,
         }
     }
 
@@ -54,6 +57,7 @@ impl VolumeSnapshotOperator {
     /// * `volume_snapshot_class` - Name of the VolumeSnapshotClass resource
     /// * `source_pvc_name` - Name of the PersistentVolumeClaim resource
     /// * `vsc_name` - Name of the VolumeSnapshotContent resource
+    /// * `labels` - Additional labels of the VolumeSnapshot resource
     /// * `snapshot_handle` - Handle - Snapshot ID of the source volume
     /// * `restore_size` - Size of the restored volume
     ///
@@ -85,11 +89,12 @@ impl VolumeSnapshotOperator {
         }
 
         // Create a base labels map
-        // Always add the namespace name
-        let labels = BTreeMap::from([(
+        // Always add the namespace name, along with any additional labels
+        let mut labels = BTreeMap::from([(
             "app.kubernetes.io/instance".to_string(),
             self.namespace.clone(),
         )]);
+        labels.extend(self.labels.clone());
 
         VolumeSnapshot {
             metadata: ObjectMeta {
@@ -4,6 +4,7 @@ mod tests {
         vs::volume_snapshots_operator::VolumeSnapshotOperator, vsc::retain_policy::VSCRetainPolicy,
     };
     use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
 
     #[tokio::test]
     async fn test_construct_volume_snapshot_resource() {
@@ -13,6 +14,7 @@ mod tests {
             "ebs.csi.aws.com".to_string(),
             Some("test-pvc".to_string()),
             Some("test-volume-snapshot-content".to_string()),
+            BTreeMap::from([("snap-kube/source-pvc".to_string(), "test-pvc".to_string())]),
         );
         let volume_snapshot = vs_operator.construct_volume_snapshot_resource(
             Some("test-snapshot-handle".to_string()),
@@ -63,10 +65,19 @@ mod tests {
             volume_snapshot
                 .metadata
                 .labels
+                .as_ref()
                 .unwrap()
                 .get("app.kubernetes.io/instance"),
             Some(&"default".to_string())
         );
+        assert_eq!(
+            volume_snapshot
+                .metadata
+                .labels
+                .unwrap()
+                .get("snap-kube/source-pvc"),
+            Some(&"test-pvc".to_string())
+        );
         assert_eq!(
             volume_snapshot.spec.volume_snapshot_class_name.unwrap(),
             "ebs.csi.aws.com"
@@ -88,4 +99,78 @@ mod tests {
             "test-volume-snapshot-content"
         );
     }
+
+    #[cfg(feature = "backup")]
+    #[test]
+    fn test_is_volume_snapshot_ready() {
+        use crate::k8s_ops::vs::volume_snapshots::is_volume_snapshot_ready;
+        use kube::runtime::wait::Condition;
+        use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshots::{
+            VolumeSnapshot, VolumeSnapshotStatus, VolumeSnapshotStatusError,
+        };
+
+        let condition = is_volume_snapshot_ready();
+        let with_status = |status: Option<VolumeSnapshotStatus>| VolumeSnapshot {
+            status,
+            ..Default::default()
+        };
+
+        assert!(!condition.matches_object(None));
+        assert!(!condition.matches_object(Some(&with_status(None))));
+        assert!(
+            !condition.matches_object(Some(&with_status(Some(VolumeSnapshotStatus {
+                ready_to_use: Some(false),
+                ..Default::default()
+            }))))
+        );
+        assert!(
+            condition.matches_object(Some(&with_status(Some(VolumeSnapshotStatus {
+                ready_to_use: Some(true),
+                ..Default::default()
+            }))))
+        );
+        // An error may still be retried by the snapshot controller
+        assert!(
+            !condition.matches_object(Some(&with_status(Some(VolumeSnapshotStatus {
+                ready_to_use: Some(false),
+                error: Some(VolumeSnapshotStatusError {
+                    message: Some("Failed to create snapshot".to_string()),
+                    time: None,
+                }),
+                ..Default::default()
+            }))))
+        );
+    }
+
+    #[cfg(feature = "backup")]
+    #[test]
+    fn test_is_volume_snapshot_bound_or_failed() {
+        use crate::k8s_ops::vs::volume_snapshots::is_volume_snapshot_bound_or_failed;
+        use kube::runtime::wait::Condition;
+        use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshots::{
+            VolumeSnapshot, VolumeSnapshotStatus,
+        };
+
+        let condition = is_volume_snapshot_bound_or_failed();
+        let with_status = |status: VolumeSnapshotStatus| VolumeSnapshot {
+            status: Some(status),
+            ..Default::default()
+        };
+
+        assert!(!condition.matches_object(None));
+        assert!(
+            !condition.matches_object(Some(&with_status(VolumeSnapshotStatus {
+                bound_volume_snapshot_content_name: Some("snapcontent-1".to_string()),
+                ..Default::default()
+            })))
+        );
+        assert!(
+            condition.matches_object(Some(&with_status(VolumeSnapshotStatus {
+                bound_volume_snapshot_content_name: Some("snapcontent-1".to_string()),
+                creation_time: Some("2024-10-18T12:00:00Z".to_string()),
+                ready_to_use: Some(false),
+                ..Default::default()
+            })))
+        );
+    }
 }
@@ -3,5 +3,7 @@ pub mod volume_snapshot_contents;
 #[cfg(feature = "restore")]
 pub mod volume_snapshot_contents_operator;
 
+#[cfg(test)]
+mod retain_policy_tests;
 #[cfg(test)]
 mod volume_snapshot_contents_tests;
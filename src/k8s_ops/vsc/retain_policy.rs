@@ -1,11 +1,13 @@
 use clap::ValueEnum;
 use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshotcontents::VolumeSnapshotContentDeletionPolicy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display, Formatter};
 
 /// Represents the VolumeSnapshotContent Retain Policy
 ///
 /// It can be either Retain or Delete
-#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Eq)]
+#[derive(ValueEnum, Clone, Debug, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 pub enum VSCRetainPolicy {
     Retain,
     Delete,
@@ -28,26 +30,3 @@ impl From<VSCRetainPolicy> for VolumeSnapshotContentDeletionPolicy {
         }
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_vsc_retain_policy_display() {
-        assert_eq!(VSCRetainPolicy::Retain.to_string(), "Retain");
-        assert_eq!(VSCRetainPolicy::Delete.to_string(), "Delete");
-    }
-
-    #[test]
-    fn test_vsc_retain_policy_into() {
-        assert_eq!(
-            VolumeSnapshotContentDeletionPolicy::from(VSCRetainPolicy::Retain),
-            VolumeSnapshotContentDeletionPolicy::Retain
-        );
-        assert_eq!(
-            VolumeSnapshotContentDeletionPolicy::from(VSCRetainPolicy::Delete),
-            VolumeSnapshotContentDeletionPolicy::Delete
-        );
-    }
-}
@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use crate::k8s_ops::vsc::retain_policy::VSCRetainPolicy;
+    use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshotcontents::VolumeSnapshotContentDeletionPolicy;
+
+    #[test]
+    fn test_vsc_retain_policy_display() {
+        assert_eq!(VSCRetainPolicy::Retain.to_string(), "Retain");
+        assert_eq!(VSCRetainPolicy::Delete.to_string(), "Delete");
+    }
+
+    #[test]
+    fn test_vsc_retain_policy_into() {
+        assert_eq!(
+            VolumeSnapshotContentDeletionPolicy::from(VSCRetainPolicy::Retain),
+            VolumeSnapshotContentDeletionPolicy::Retain
+        );
+        assert_eq!(
+            VolumeSnapshotContentDeletionPolicy::from(VSCRetainPolicy::Delete),
+            VolumeSnapshotContentDeletionPolicy::Delete
+        );
+    }
+}
@@ -1,5 +1,8 @@
-use anyhow::{bail, Result};
-use kube::Api;
+use anyhow::{bail, Context, Result};
+use kube::{
+    api::{ApiResource, DynamicObject, GroupVersionKind},
+    Api, Client,
+};
 use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshotcontents::VolumeSnapshotContent;
 
 /// Get the snapshot handle from the VolumeSnapshotContent
@@ -16,11 +19,86 @@ pub async fn get_snapshot_handle(
     vsc_api: Api<VolumeSnapshotContent>,
     volume_snapshot_content_name: &str,
 ) -> Result<String> {
+    get_snapshot_source(vsc_api, volume_snapshot_content_name)
+        .await
+        .map(|(snapshot_handle, _)| snapshot_handle)
+}
+
+/// Get the snapshot handle and the volume mode of the source volume from the
+/// VolumeSnapshotContent
+///
+/// # Arguments
+///
+/// * `vsc_api` - Api object for VolumeSnapshotContent
+/// * `volume_snapshot_content_name` - Name of the VolumeSnapshotContent resource
+///
+/// # Returns
+///
+/// Snapshot handle, and the volume mode of the source volume if the snapshot controller
+/// recorded it
+pub async fn get_snapshot_source(
+    vsc_api: Api<VolumeSnapshotContent>,
+    volume_snapshot_content_name: &str,
+) -> Result<(String, Option<String>)> {
     let volume_snapshot_content = vsc_api.get(volume_snapshot_content_name).await?;
 
     if let Some(status) = volume_snapshot_content.status {
-        Ok(status.snapshot_handle.unwrap())
+        Ok((
+            status.snapshot_handle.unwrap(),
+            volume_snapshot_content.spec.source_volume_mode,
+        ))
     } else {
         bail!("Status of VolumeSnapshotContent is not available")
     }
 }
+
+/// Api object for the VolumeSnapshotClasses
+///
+/// The VolumeSnapshotClass has no spec, so it has no typed resource and is read as a
+/// dynamic object.
+///
+/// # Arguments
+///
+/// * `k8s_client` - Kubernetes client
+pub fn volume_snapshot_class_api(k8s_client: Client) -> Api<DynamicObject> {
+    let api_resource = ApiResource::from_gvk_with_plural(
+        &GroupVersionKind::gvk("snapshot.storage.k8s.io", "v1", "VolumeSnapshotClass"),
+        "volumesnapshotclasses",
+    );
+    Api::all_with(k8s_client, &api_resource)
+}
+
+/// Get the CSI driver of a VolumeSnapshotClass, which takes the snapshots of its
+/// VolumeSnapshotContents
+///
+/// # Arguments
+///
+/// * `vs_class_api` - Api object for VolumeSnapshotClass, see [`volume_snapshot_class_api`]
+/// * `volume_snapshot_class_name` - Name of the VolumeSnapshotClass
+///
+/// # Returns
+///
+/// Name of the CSI driver, or an error if the VolumeSnapshotClass does not exist
+pub async fn get_snapshot_class_driver(
+    vs_class_api: &Api<DynamicObject>,
+    volume_snapshot_class_name: &str,
+) -> Result<String> {
+    let volume_snapshot_class = vs_class_api
+        .get_opt(volume_snapshot_class_name)
+        .await?
+        .with_context(|| {
+            format!(
+                "VolumeSnapshotClass {} not found",
+                volume_snapshot_class_name
+            )
+        })?;
+    volume_snapshot_class.data["driver"]
+        .as_str()
+        .map(str::to_string)
+        .with_context(|| {
+            format!(
+                "VolumeSnapshotClass {} has no driver",
+                volume_snapshot_class_name
+            )
+        })
+}
@@ -30,6 +30,7 @@ pub struct VolumeSnapshotContentOperator {
     pub volume_snapshot_class: Option<String>,
     pub source_volume_handle: Option<String>,
     pub vsc_retain_policy: VSCRetainPolicy,
+    pub source_volume_mode: Option<String>,
 }
 
 impl VolumeSnapshotContentOperator {
@@ -40,6 +41,7 @@ impl VolumeSnapshotContentOperator {
         volume_snapshot_class: Option<String>,
         source_volume_handle: Option<String>,
         vsc_retain_policy: VSCRetainPolicy,
+        source_volume_mode: Option<String>,
     ) -> Self {
         Self {
             name,
@@ -48,6 +50,7 @@ impl VolumeSnapshotContentOperator {
             volume_snapshot_class,
             source_volume_handle,
             vsc_retain_policy,
+            source_volume_mode,
         }
     }
 
@@ -60,6 +63,7 @@ impl VolumeSnapshotContentOperator {
     /// * `volume_snapshot_name` - Name of the VolumeSnapshot resource
     /// * `volume_snapshot_class` - Name of the VolumeSnapshotClass resource
     /// * `source_volume_handle` - Handle - Snapshot ID of the source volume
+    /// * `source_volume_mode` - Volume mode of the source volume, `Filesystem` if not set
     ///
     /// # Returns
     ///
@@ -88,7 +92,11 @@ impl VolumeSnapshotContentOperator {
                     ..Default::default()
                 },
                 volume_snapshot_class_name: self.volume_snapshot_class.clone(),
-                source_volume_mode: Some(VSCResourceValues::SourceVolumeMode.get_value()),
+                source_volume_mode: Some(
+                    self.source_volume_mode
+                        .clone()
+                        .unwrap_or(VSCResourceValues::SourceVolumeMode.get_value()),
+                ),
             },
             status: Some(VolumeSnapshotContentStatus {
                 snapshot_handle: self.source_volume_handle.clone(),
@@ -14,6 +14,7 @@ mod tests {
             Some("ebs.csi.aws.com".to_string()),
             Some("test-snapshot-handle".to_string()),
             VSCRetainPolicy::Delete,
+            None,
         );
         let volume_snapshot_content = vsc_operator.construct_volume_snapshot_content_resource();
         assert_eq!(
@@ -58,4 +59,22 @@ mod tests {
             "test-snapshot-handle"
         );
     }
+
+    #[test]
+    fn test_construct_block_volume_snapshot_content_resource() {
+        let vsc_operator = VolumeSnapshotContentOperator::new(
+            "test-volume-snapshot-content".to_string(),
+            "default".to_string(),
+            "test-volume-snapshot".to_string(),
+            None,
+            Some("test-snapshot-handle".to_string()),
+            VSCRetainPolicy::Retain,
+            Some("Block".to_string()),
+        );
+        let volume_snapshot_content = vsc_operator.construct_volume_snapshot_content_resource();
+        assert_eq!(
+            volume_snapshot_content.spec.source_volume_mode.unwrap(),
+            "Block"
+        );
+    }
 }
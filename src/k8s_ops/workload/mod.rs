@@ -0,0 +1,4 @@
+pub mod workloads;
+
+#[cfg(test)]
+mod workloads_tests;
@@ -0,0 +1,371 @@
+use crate::k8s_ops::pod::pods::{get_pods_mounting_pvc, PodApiTrait};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use k8s_openapi::api::{
+    apps::v1::{Deployment, StatefulSet},
+    core::v1::PodTemplateSpec,
+};
+use kube::{
+    api::{ListParams, ObjectMeta, Patch, PatchParams},
+    Api,
+};
+use serde_json::{json, Value};
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+use tokio::time::{sleep, Instant};
+use tracing::{error, info};
+
+#[cfg(test)]
+use mockall::automock;
+
+/// Annotation holding the replicas of a workload before it was scaled down for a backup
+pub const ORIGINAL_REPLICAS_ANNOTATION: &str = "snap-kube/original-replicas";
+
+/// Interval between two checks of the pods of the scaled down workloads
+const POD_TERMINATION_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Represents the kinds of workloads that can be scaled down
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WorkloadKind {
+    Deployment,
+    StatefulSet,
+}
+
+impl Display for WorkloadKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkloadKind::Deployment => write!(f, "Deployment"),
+            WorkloadKind::StatefulSet => write!(f, "StatefulSet"),
+        }
+    }
+}
+
+/// A Deployment or StatefulSet, along with its replicas
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Workload {
+    pub kind: WorkloadKind,
+    pub name: String,
+    pub replicas: i32,
+    /// The replicas recorded by a previous scale down, if it was not restored
+    pub original_replicas: Option<i32>,
+}
+
+impl Workload {
+    fn new(kind: WorkloadKind, metadata: &ObjectMeta, replicas: Option<i32>) -> Self {
+        Self {
+            kind,
+            name: metadata.name.clone().unwrap_or_default(),
+            replicas: replicas.unwrap_or(1),
+            original_replicas: metadata
+                .annotations
+                .as_ref()
+                .and_then(|annotations| annotations.get(ORIGINAL_REPLICAS_ANNOTATION))
+                .and_then(|replicas| replicas.parse().ok()),
+        }
+    }
+
+    /// The replicas to restore the workload to
+    pub fn replicas_to_restore(&self) -> i32 {
+        self.original_replicas.unwrap_or(self.replicas)
+    }
+}
+
+impl Display for Workload {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.kind, self.name)
+    }
+}
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait WorkloadApiTrait {
+    async fn list_deployments(&self) -> Result<Vec<Deployment>>;
+    async fn list_stateful_sets(&self) -> Result<Vec<StatefulSet>>;
+    async fn patch(&self, kind: WorkloadKind, name: &str, patch: Value) -> Result<()>;
+}
+
+pub struct KubeWorkloadApi {
+    pub deployments_api: Api<Deployment>,
+    pub stateful_sets_api: Api<StatefulSet>,
+}
+
+/// Implement the WorkloadApiTrait for the Deployment and StatefulSet Apis, so that it can be
+/// mocked in tests
+#[async_trait]
+impl WorkloadApiTrait for KubeWorkloadApi {
+    async fn list_deployments(&self) -> Result<Vec<Deployment>> {
+        let deployments = self.deployments_api.list(&ListParams::default()).await?;
+        Ok(deployments.items)
+    }
+
+    async fn list_stateful_sets(&self) -> Result<Vec<StatefulSet>> {
+        let stateful_sets = self.stateful_sets_api.list(&ListParams::default()).await?;
+        Ok(stateful_sets.items)
+    }
+
+    async fn patch(&self, kind: WorkloadKind, name: &str, patch: Value) -> Result<()> {
+        let pp = PatchParams::default();
+        match kind {
+            WorkloadKind::Deployment => {
+                self.deployments_api
+                    .patch(name, &pp, &Patch::Merge(patch))
+                    .await?;
+            }
+            WorkloadKind::StatefulSet => {
+                self.stateful_sets_api
+                    .patch(name, &pp, &Patch::Merge(patch))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a pod template mounts one of the PVCs
+fn template_mounts_pvc(template: &PodTemplateSpec, pvc_names: &[String]) -> bool {
+    template
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.volumes.as_ref())
+        .is_some_and(|volumes| {
+            volumes.iter().any(|volume| {
+                volume
+                    .persistent_volume_claim
+                    .as_ref()
+                    .is_some_and(|claim| pvc_names.contains(&claim.claim_name))
+            })
+        })
+}
+
+/// Whether one of the PVCs was created from a volume claim template of the StatefulSet,
+/// i.e. it is named `<template>-<statefulset>-<ordinal>`
+fn stateful_set_owns_pvc(stateful_set: &StatefulSet, pvc_names: &[String]) -> bool {
+    let stateful_set_name = stateful_set.metadata.name.clone().unwrap_or_default();
+    stateful_set
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.volume_claim_templates.as_ref())
+        .is_some_and(|templates| {
+            templates.iter().any(|template| {
+                let prefix = format!(
+                    "{}-{}-",
+                    template.metadata.name.clone().unwrap_or_default(),
+                    stateful_set_name
+                );
+                pvc_names.iter().any(|pvc_name| {
+                    pvc_name.strip_prefix(&prefix).is_some_and(|ordinal| {
+                        !ordinal.is_empty() && ordinal.chars().all(|c| c.is_ascii_digit())
+                    })
+                })
+            })
+        })
+}
+
+/// Get the Deployments and StatefulSets whose pods mount one of the PVCs
+///
+/// # Arguments
+///
+/// * `workload_api` - Api object for the Deployments and StatefulSets of the namespace
+/// * `pvc_names` - Names of the PVCs
+///
+/// # Returns
+///
+/// The workloads mounting the PVCs, sorted by kind and name
+pub async fn get_workloads_mounting_pvcs(
+    workload_api: &impl WorkloadApiTrait,
+    pvc_names: &[String],
+) -> Result<Vec<Workload>> {
+    let mut workloads: Vec<Workload> = Vec::new();
+    for deployment in workload_api.list_deployments().await? {
+        if let Some(spec) = &deployment.spec {
+            if template_mounts_pvc(&spec.template, pvc_names) {
+                workloads.push(Workload::new(
+                    WorkloadKind::Deployment,
+                    &deployment.metadata,
+                    spec.replicas,
+                ));
+            }
+        }
+    }
+    for stateful_set in workload_api.list_stateful_sets().await? {
+        if let Some(spec) = &stateful_set.spec {
+            if template_mounts_pvc(&spec.template, pvc_names)
+                || stateful_set_owns_pvc(&stateful_set, pvc_names)
+            {
+                workloads.push(Workload::new(
+                    WorkloadKind::StatefulSet,
+                    &stateful_set.metadata,
+                    spec.replicas,
+                ));
+            }
+        }
+    }
+    workloads.sort();
+    Ok(workloads)
+}
+
+/// Scale the workloads to zero, recording their replicas in an annotation
+///
+/// A workload that already holds the annotation, because a previous run was interrupted,
+/// keeps the replicas it records.
+///
+/// # Arguments
+///
+/// * `workload_api` - Api object for the Deployments and StatefulSets of the namespace
+/// * `workloads` - The workloads to scale down
+pub async fn scale_down_workloads(
+    workload_api: &impl WorkloadApiTrait,
+    workloads: &[Workload],
+) -> Result<()> {
+    for workload in workloads {
+        info!(
+            "Scaling down {} from {} replica(s)",
+            workload,
+            workload.replicas_to_restore()
+        );
+        workload_api
+            .patch(
+                workload.kind,
+                &workload.name,
+                json!({
+                    "metadata": {
+                        "annotations": {
+                            ORIGINAL_REPLICAS_ANNOTATION: workload.replicas_to_restore().to_string()
+                        }
+                    },
+                    "spec": { "replicas": 0 }
+                }),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Scale the workloads back to their original replicas, and remove the annotation
+///
+/// All the workloads are restored, even if some of them fail.
+///
+/// # Arguments
+///
+/// * `workload_api` - Api object for the Deployments and StatefulSets of the namespace
+/// * `workloads` - The workloads to restore
+pub async fn restore_workloads(
+    workload_api: &impl WorkloadApiTrait,
+    workloads: &[Workload],
+) -> Result<()> {
+    let mut errors = Vec::new();
+    for workload in workloads {
+        info!(
+            "Scaling {} back to {} replica(s)",
+            workload,
+            workload.replicas_to_restore()
+        );
+        if let Err(e) = workload_api
+            .patch(
+                workload.kind,
+                &workload.name,
+                json!({
+                    "metadata": {
+                        "annotations": { ORIGINAL_REPLICAS_ANNOTATION: null }
+                    },
+                    "spec": { "replicas": workload.replicas_to_restore() }
+                }),
+            )
+            .await
+        {
+            error!("Failed to scale {} back: {:#}", workload, e);
+            errors.push(format!("{}: {:#}", workload, e));
+        }
+    }
+    if !errors.is_empty() {
+        bail!(
+            "Failed to restore the replicas of {} workload(s): {}",
+            errors.len(),
+            errors.join("; ")
+        );
+    }
+    Ok(())
+}
+
+/// Restore the workloads left scaled down by an interrupted backup
+///
+/// # Arguments
+///
+/// * `workload_api` - Api object for the Deployments and StatefulSets of the namespace
+///
+/// # Returns
+///
+/// The workloads that were restored
+pub async fn recover_workloads(workload_api: &impl WorkloadApiTrait) -> Result<Vec<Workload>> {
+    let mut workloads: Vec<Workload> = workload_api
+        .list_deployments()
+        .await?
+        .iter()
+        .map(|deployment| {
+            Workload::new(
+                WorkloadKind::Deployment,
+                &deployment.metadata,
+                deployment.spec.as_ref().and_then(|spec| spec.replicas),
+            )
+        })
+        .chain(
+            workload_api
+                .list_stateful_sets()
+                .await?
+                .iter()
+                .map(|stateful_set| {
+                    Workload::new(
+                        WorkloadKind::StatefulSet,
+                        &stateful_set.metadata,
+                        stateful_set.spec.as_ref().and_then(|spec| spec.replicas),
+                    )
+                }),
+        )
+        .filter(|workload| workload.original_replicas.is_some())
+        .collect();
+    workloads.sort();
+    restore_workloads(workload_api, &workloads).await?;
+    Ok(workloads)
+}
+
+/// Wait untill no running pod mounts the PVCs
+///
+/// # Arguments
+///
+/// * `pod_api` - Api object for the Pods of the namespace
+/// * `pvc_names` - Names of the PVCs
+/// * `timeout` - Maximum time to wait for the pods to terminate
+pub async fn wait_for_pods_to_terminate(
+    pod_api: &impl PodApiTrait,
+    pvc_names: &[String],
+    timeout: Duration,
+) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let mut pods = Vec::new();
+        for pvc_name in pvc_names {
+            pods.extend(
+                get_pods_mounting_pvc(pod_api, pvc_name)
+                    .await?
+                    .into_iter()
+                    .filter_map(|pod| pod.metadata.name),
+            );
+        }
+        pods.sort();
+        pods.dedup();
+        if pods.is_empty() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "Pods still running after {}s: {}",
+                timeout.as_secs(),
+                pods.join(", ")
+            );
+        }
+        info!("Waiting for pods to terminate: {}", pods.join(", "));
+        sleep(POD_TERMINATION_POLL_INTERVAL).await;
+    }
+}
@@ -0,0 +1,249 @@
+#[cfg(test)]
+mod tests {
+    use crate::k8s_ops::workload::workloads::{
+        get_workloads_mounting_pvcs, recover_workloads, restore_workloads, scale_down_workloads,
+        MockWorkloadApiTrait, Workload, WorkloadKind, ORIGINAL_REPLICAS_ANNOTATION,
+    };
+    use k8s_openapi::api::{
+        apps::v1::{Deployment, DeploymentSpec, StatefulSet, StatefulSetSpec},
+        core::v1::{
+            PersistentVolumeClaim, PersistentVolumeClaimVolumeSource, PodSpec, PodTemplateSpec,
+            Volume,
+        },
+    };
+    use kube::api::ObjectMeta;
+    use mockall::predicate;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn metadata(name: &str, original_replicas: Option<&str>) -> ObjectMeta {
+        ObjectMeta {
+            name: Some(name.to_string()),
+            annotations: original_replicas.map(|replicas| {
+                BTreeMap::from([(
+                    ORIGINAL_REPLICAS_ANNOTATION.to_string(),
+                    replicas.to_string(),
+                )])
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn template(pvc_names: &[&str]) -> PodTemplateSpec {
+        PodTemplateSpec {
+            spec: Some(PodSpec {
+                volumes: Some(
+                    pvc_names
+                        .iter()
+                        .map(|pvc_name| Volume {
+                            name: pvc_name.to_string(),
+                            persistent_volume_claim: Some(PersistentVolumeClaimVolumeSource {
+                                claim_name: pvc_name.to_string(),
+                                read_only: None,
+                            }),
+                            ..Default::default()
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn deployment(name: &str, replicas: i32, pvc_names: &[&str]) -> Deployment {
+        Deployment {
+            metadata: metadata(name, None),
+            spec: Some(DeploymentSpec {
+                replicas: Some(replicas),
+                template: template(pvc_names),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn stateful_set(
+        name: &str,
+        replicas: i32,
+        volume_claim_template: &str,
+        original_replicas: Option<&str>,
+    ) -> StatefulSet {
+        StatefulSet {
+            metadata: metadata(name, original_replicas),
+            spec: Some(StatefulSetSpec {
+                replicas: Some(replicas),
+                template: template(&[]),
+                volume_claim_templates: Some(vec![PersistentVolumeClaim {
+                    metadata: ObjectMeta {
+                        name: Some(volume_claim_template.to_string()),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn workload(kind: WorkloadKind, name: &str, replicas: i32, original: Option<i32>) -> Workload {
+        Workload {
+            kind,
+            name: name.to_string(),
+            replicas,
+            original_replicas: original,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_workloads_mounting_pvcs() {
+        let mut mock_workload_api = MockWorkloadApiTrait::new();
+        mock_workload_api.expect_list_deployments().returning(|| {
+            Ok(vec![
+                deployment("web", 3, &["uploads"]),
+                deployment("worker", 2, &["scratch"]),
+            ])
+        });
+        mock_workload_api.expect_list_stateful_sets().returning(|| {
+            Ok(vec![
+                stateful_set("postgres", 2, "data", None),
+                // data-postgres-replica-0 is not owned by postgres
+                stateful_set("postgres-replica", 1, "wal", None),
+            ])
+        });
+
+        let workloads = get_workloads_mounting_pvcs(
+            &mock_workload_api,
+            &[
+                "uploads".to_string(),
+                "data-postgres-1".to_string(),
+                "data-postgres-replica-0".to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            workloads,
+            vec![
+                workload(WorkloadKind::Deployment, "web", 3, None),
+                workload(WorkloadKind::StatefulSet, "postgres", 2, None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scale_down_keeps_recorded_replicas() {
+        let mut mock_workload_api = MockWorkloadApiTrait::new();
+        mock_workload_api
+            .expect_patch()
+            .with(
+                predicate::eq(WorkloadKind::Deployment),
+                predicate::eq("web"),
+                predicate::eq(json!({
+                    "metadata": { "annotations": { ORIGINAL_REPLICAS_ANNOTATION: "3" } },
+                    "spec": { "replicas": 0 }
+                })),
+            )
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        // Left scaled down by an interrupted run, it keeps its recorded replicas
+        mock_workload_api
+            .expect_patch()
+            .with(
+                predicate::eq(WorkloadKind::StatefulSet),
+                predicate::eq("postgres"),
+                predicate::eq(json!({
+                    "metadata": { "annotations": { ORIGINAL_REPLICAS_ANNOTATION: "2" } },
+                    "spec": { "replicas": 0 }
+                })),
+            )
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        scale_down_workloads(
+            &mock_workload_api,
+            &[
+                workload(WorkloadKind::Deployment, "web", 3, None),
+                workload(WorkloadKind::StatefulSet, "postgres", 0, Some(2)),
+            ],
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_restore_workloads_restores_all() {
+        let mut mock_workload_api = MockWorkloadApiTrait::new();
+        mock_workload_api
+            .expect_patch()
+            .with(
+                predicate::eq(WorkloadKind::Deployment),
+                predicate::eq("web"),
+                predicate::always(),
+            )
+            .times(1)
+            .returning(|_, _, _| Err(anyhow::anyhow!("conflict")));
+        mock_workload_api
+            .expect_patch()
+            .with(
+                predicate::eq(WorkloadKind::StatefulSet),
+                predicate::eq("postgres"),
+                predicate::eq(json!({
+                    "metadata": { "annotations": { ORIGINAL_REPLICAS_ANNOTATION: null } },
+                    "spec": { "replicas": 2 }
+                })),
+            )
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let result = restore_workloads(
+            &mock_workload_api,
+            &[
+                workload(WorkloadKind::Deployment, "web", 3, None),
+                workload(WorkloadKind::StatefulSet, "postgres", 2, None),
+            ],
+        )
+        .await;
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Deployment/web: conflict"));
+    }
+
+    #[tokio::test]
+    async fn test_recover_workloads() {
+        let mut mock_workload_api = MockWorkloadApiTrait::new();
+        mock_workload_api
+            .expect_list_deployments()
+            .returning(|| Ok(vec![deployment("web", 3, &["uploads"])]));
+        mock_workload_api.expect_list_stateful_sets().returning(|| {
+            Ok(vec![
+                stateful_set("postgres", 0, "data", Some("2")),
+                stateful_set("redis", 1, "cache", None),
+            ])
+        });
+        mock_workload_api
+            .expect_patch()
+            .with(
+                predicate::eq(WorkloadKind::StatefulSet),
+                predicate::eq("postgres"),
+                predicate::eq(json!({
+                    "metadata": { "annotations": { ORIGINAL_REPLICAS_ANNOTATION: null } },
+                    "spec": { "replicas": 2 }
+                })),
+            )
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let recovered = recover_workloads(&mock_workload_api).await.unwrap();
+
+        assert_eq!(
+            recovered,
+            vec![workload(WorkloadKind::StatefulSet, "postgres", 0, Some(2))]
+        );
+    }
+}
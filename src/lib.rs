@@ -1,4 +1,9 @@
 pub mod aws_ops;
 pub mod backup;
+pub mod catalog;
+pub mod controller;
+pub mod daemon;
 pub mod k8s_ops;
+pub mod prune;
 pub mod restore;
+pub mod share;
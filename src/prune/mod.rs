@@ -0,0 +1,14 @@
+#[cfg(feature = "backup")]
+pub mod prune_operator;
+#[cfg(feature = "backup")]
+pub mod prune_payload;
+#[cfg(feature = "backup")]
+pub mod prune_summary;
+pub mod retention_policy;
+
+#[cfg(all(test, feature = "backup"))]
+mod prune_operator_tests;
+#[cfg(all(test, feature = "backup"))]
+mod prune_summary_tests;
+#[cfg(test)]
+mod retention_policy_tests;
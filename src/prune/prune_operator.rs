@@ -0,0 +1,249 @@
+use super::{
+    prune_payload::PrunePayload,
+    prune_summary::{PruneResult, PruneStatus, PruneSummary},
+    retention_policy::{is_protected, select_expired_volume_snapshots},
+};
+use crate::{
+    catalog::backup_catalog::{open_catalog_store, BackupCatalogStore},
+    k8s_ops::vs::volume_snapshot_naming::{
+        to_label_value, BACKUP_ID_LABEL, BACKUP_ORIGIN, MANAGED_BY_LABEL, NAME_PREFIX_LABEL,
+        ORIGIN_LABEL,
+    },
+};
+use anyhow::Result;
+use chrono::Utc;
+use kube::{
+    api::{DeleteParams, ListParams},
+    Api, Client,
+};
+use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::{
+    volumesnapshotcontents::{VolumeSnapshotContent, VolumeSnapshotContentDeletionPolicy},
+    volumesnapshots::VolumeSnapshot,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{error, info, warn};
+
+/// A struct for pruning the VolumeSnapshots taken by snap-kube
+pub struct PruneOperator;
+
+impl PruneOperator {
+    /// Prunes the VolumeSnapshots of a namespace that are expired by a retention policy
+    ///
+    /// Only the VolumeSnapshots created by a backup, labelled with their backup ID, are
+    /// considered: the ones importing an EBS snapshot and the ones created to restore a PVC
+    /// are left alone, and the ones carrying the protected label are never deleted. Deleting
+    /// a VolumeSnapshot follows the deletion policy of its VolumeSnapshotContent, so the EBS
+    /// snapshot of a VolumeSnapshotContent with a Retain policy is left in place and reported
+    /// in the returned summary.
+    ///
+    /// If a catalog location is set, the entries of the deleted VolumeSnapshots are removed
+    /// from the catalog, along with the backup runs left without any entry.
+    pub async fn prune(prune_payload: PrunePayload) -> Result<PruneSummary> {
+        // Create a Kubernetes client
+        let k8s_client = Client::try_default().await?;
+
+        let vs_api: Api<VolumeSnapshot> =
+            Api::namespaced(k8s_client.clone(), prune_payload.namespace());
+        let vsc_api: Api<VolumeSnapshotContent> = Api::all(k8s_client.clone());
+
+        // List the VolumeSnapshots taken by a backup, optionally with a specific name prefix
+        let mut label_selector = format!(
+            "{}=snap-kube,{}={},{}",
+            MANAGED_BY_LABEL, ORIGIN_LABEL, BACKUP_ORIGIN, BACKUP_ID_LABEL
+        );
+        if let Some(vs_name_prefix) = prune_payload.vs_name_prefix() {
+            label_selector.push_str(&format!(
+                ",{}={}",
+                NAME_PREFIX_LABEL,
+                to_label_value(vs_name_prefix)
+            ));
+        }
+        let volume_snapshots = vs_api
+            .list(&ListParams::default().labels(&label_selector))
+            .await?
+            .items;
+        let protected = volume_snapshots
+            .iter()
+            .filter(|vs| is_protected(vs))
+            .count();
+        if protected > 0 {
+            info!(
+                "Skipping {} protected VolumeSnapshot(s) on namespace: {}",
+                protected,
+                prune_payload.namespace()
+            );
+        }
+
+        let total = volume_snapshots.len();
+        let expired = select_expired_volume_snapshots(
+            volume_snapshots,
+            prune_payload.retention_policy(),
+            Utc::now(),
+        );
+        let kept = total - expired.len();
+
+        let mut results = Vec::new();
+        let mut pruned: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for volume_snapshot in expired {
+            let volume_snapshot_name = volume_snapshot.metadata.name.clone().unwrap_or_default();
+            let backup_id = volume_snapshot
+                .metadata
+                .labels
+                .as_ref()
+                .and_then(|labels| labels.get(BACKUP_ID_LABEL))
+                .cloned()
+                .unwrap_or_default();
+            let status = match Self::prune_volume_snapshot(
+                &prune_payload,
+                &vs_api,
+                &vsc_api,
+                volume_snapshot,
+            )
+            .await
+            {
+                Ok(status) => status,
+                Err(e) => {
+                    error!(
+                        "Failed to prune VolumeSnapshot {}: {:#}",
+                        volume_snapshot_name, e
+                    );
+                    PruneStatus::Failed(format!("{:#}", e))
+                }
+            };
+            if matches!(
+                status,
+                PruneStatus::Deleted | PruneStatus::DeletedContentRetained(_)
+            ) {
+                pruned
+                    .entry(backup_id)
+                    .or_default()
+                    .insert(volume_snapshot_name.clone());
+            }
+            results.push(PruneResult::new(volume_snapshot_name, status));
+        }
+
+        let mut prune_summary = PruneSummary::new(prune_payload.namespace(), kept, results);
+        if let Some(catalog_location) = prune_payload.catalog() {
+            if let Err(e) = Self::remove_catalog_entries(
+                open_catalog_store(k8s_client, catalog_location).as_ref(),
+                prune_payload.namespace(),
+                &pruned,
+            )
+            .await
+            {
+                error!(
+                    "Failed to remove the pruned VolumeSnapshots from the catalog: {:#}",
+                    e
+                );
+                prune_summary.catalog_error = Some(format!("{:#}", e));
+            }
+        }
+        Ok(prune_summary)
+    }
+
+    /// Remove the entries of the pruned VolumeSnapshots from the catalog
+    ///
+    /// A backup run left without any entry is deleted from the catalog.
+    ///
+    /// # Arguments
+    ///
+    /// * `catalog_store` - The backup catalog store
+    /// * `namespace` - Namespace of the pruned VolumeSnapshots
+    /// * `pruned` - The names of the pruned VolumeSnapshots, per backup ID
+    pub(crate) async fn remove_catalog_entries(
+        catalog_store: &dyn BackupCatalogStore,
+        namespace: &str,
+        pruned: &BTreeMap<String, BTreeSet<String>>,
+    ) -> Result<()> {
+        let backup_ids = catalog_store.list_backup_ids().await?;
+        for (backup_id, volume_snapshot_names) in pruned {
+            if !backup_ids.contains(backup_id) {
+                continue;
+            }
+            let entries = catalog_store.get(backup_id).await?;
+            let total = entries.len();
+            let remaining: Vec<_> = entries
+                .into_iter()
+                .filter(|entry| {
+                    entry.source_ns != namespace
+                        || !volume_snapshot_names.contains(&entry.volume_snapshot_name)
+                })
+                .collect();
+            if remaining.len() == total {
+                continue;
+            }
+            info!(
+                "Removing {} pruned VolumeSnapshot(s) from backup {} in the catalog",
+                total - remaining.len(),
+                backup_id
+            );
+            if remaining.is_empty() {
+                catalog_store.delete(backup_id).await?;
+            } else {
+                catalog_store.save(backup_id, &remaining).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Prune a single expired VolumeSnapshot
+    ///
+    /// # Arguments
+    ///
+    /// * `prune_payload` - Payload of the prune operation
+    /// * `vs_api` - VolumeSnapshot API of the namespace
+    /// * `vsc_api` - VolumeSnapshotContent API
+    /// * `volume_snapshot` - The expired VolumeSnapshot
+    ///
+    /// # Returns
+    ///
+    /// The outcome of pruning the VolumeSnapshot
+    async fn prune_volume_snapshot(
+        prune_payload: &PrunePayload,
+        vs_api: &Api<VolumeSnapshot>,
+        vsc_api: &Api<VolumeSnapshotContent>,
+        volume_snapshot: VolumeSnapshot,
+    ) -> Result<PruneStatus> {
+        let volume_snapshot_name = volume_snapshot.metadata.name.unwrap_or_default();
+
+        // Check whether the bound VolumeSnapshotContent retains the EBS snapshot
+        let bound_vsc_name = volume_snapshot
+            .status
+            .and_then(|status| status.bound_volume_snapshot_content_name);
+        let retained_vsc_name = match bound_vsc_name {
+            Some(vsc_name) => match vsc_api.get_opt(&vsc_name).await? {
+                Some(vsc)
+                    if vsc.spec.deletion_policy == VolumeSnapshotContentDeletionPolicy::Retain =>
+                {
+                    Some(vsc_name)
+                }
+                _ => None,
+            },
+            None => None,
+        };
+        if let Some(vsc_name) = &retained_vsc_name {
+            warn!(
+                "VolumeSnapshotContent {} of VolumeSnapshot {} has a Retain policy, its EBS snapshot will not be deleted",
+                vsc_name, volume_snapshot_name
+            );
+        }
+
+        if prune_payload.dry_run() {
+            info!("Would delete VolumeSnapshot: {}", volume_snapshot_name);
+            return Ok(PruneStatus::WouldDelete);
+        }
+
+        vs_api
+            .delete(&volume_snapshot_name, &DeleteParams::default())
+            .await?;
+        info!(
+            "Deleted VolumeSnapshot: {} on namespace: {}",
+            volume_snapshot_name,
+            prune_payload.namespace()
+        );
+        Ok(match retained_vsc_name {
+            Some(vsc_name) => PruneStatus::DeletedContentRetained(vsc_name),
+            None => PruneStatus::Deleted,
+        })
+    }
+}
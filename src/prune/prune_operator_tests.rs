@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        catalog::{
+            backup_catalog::BackupCatalogStore, catalog_entry::BackupCatalogEntry,
+            local_file_store::LocalFileCatalogStore,
+        },
+        prune::prune_operator::PruneOperator,
+    };
+    use chrono::{TimeZone, Utc};
+    use pretty_assertions::assert_eq;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn catalog_entry(backup_id: &str, namespace: &str, pvc_name: &str) -> BackupCatalogEntry {
+        BackupCatalogEntry {
+            backup_id: backup_id.to_string(),
+            created_at: Utc.with_ymd_and_hms(2024, 10, 18, 12, 0, 0).unwrap(),
+            source_ns: namespace.to_string(),
+            pvc_name: pvc_name.to_string(),
+            pvc_spec: None,
+            volume_snapshot_class: "test-vsclass".to_string(),
+            volume_snapshot_name: format!("vs-{}-{}", pvc_name, backup_id),
+            volume_snapshot_content_name: format!("snapcontent-{}", pvc_name),
+            snapshot_handle: "snap-0123456789abcdef0".to_string(),
+            restore_size: "1Gi".to_string(),
+            volume_group_snapshot_name: None,
+            pvc_labels: BTreeMap::new(),
+            pvc_annotations: BTreeMap::new(),
+            snapshot_copies: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_catalog_entries() {
+        let path =
+            std::env::temp_dir().join(format!("snap-kube-prune-test-{}.json", std::process::id()));
+        let store = LocalFileCatalogStore { path: path.clone() };
+        store
+            .save("backup-1", &[catalog_entry("backup-1", "tenant-a", "data")])
+            .await
+            .unwrap();
+        store
+            .save(
+                "backup-2",
+                &[
+                    catalog_entry("backup-2", "tenant-a", "data"),
+                    catalog_entry("backup-2", "tenant-a", "wal"),
+                    catalog_entry("backup-2", "tenant-b", "data"),
+                ],
+            )
+            .await
+            .unwrap();
+
+        let pruned = BTreeMap::from([
+            (
+                "backup-1".to_string(),
+                BTreeSet::from(["vs-data-backup-1".to_string()]),
+            ),
+            (
+                "backup-2".to_string(),
+                BTreeSet::from(["vs-data-backup-2".to_string()]),
+            ),
+            // A backup run missing from the catalog is ignored
+            (
+                "backup-3".to_string(),
+                BTreeSet::from(["vs-data-backup-3".to_string()]),
+            ),
+        ]);
+        PruneOperator::remove_catalog_entries(&store, "tenant-a", &pruned)
+            .await
+            .unwrap();
+
+        // The backup run left without any entry is deleted, and the entries of the other
+        // namespaces are kept
+        assert_eq!(store.list_backup_ids().await.unwrap(), vec!["backup-2"]);
+        assert_eq!(
+            store.get("backup-2").await.unwrap(),
+            vec![
+                catalog_entry("backup-2", "tenant-a", "wal"),
+                catalog_entry("backup-2", "tenant-b", "data"),
+            ]
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
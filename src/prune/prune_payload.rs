@@ -0,0 +1,62 @@
+use super::retention_policy::RetentionPolicy;
+use crate::catalog::backup_catalog::CatalogLocation;
+
+pub struct PrunePayload {
+    pub namespace: String,
+    pub vs_name_prefix: Option<String>,
+    pub retention_policy: RetentionPolicy,
+    pub dry_run: bool,
+    pub catalog: Option<CatalogLocation>,
+}
+
+impl PrunePayload {
+    /// Creates a new PrunePayload
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Namespace of the VolumeSnapshots
+    /// * `vs_name_prefix` - Only prune the VolumeSnapshots with this name prefix, if set
+    /// * `retention_policy` - Retention rules of the VolumeSnapshots of each PVC
+    /// * `dry_run` - Only report the VolumeSnapshots that would be deleted
+    /// * `catalog` - Where the backup catalog is stored, to remove the entries of the pruned
+    ///   VolumeSnapshots from
+    ///
+    /// # Returns
+    ///
+    /// A new PrunePayload instance
+    pub fn new(
+        namespace: impl Into<String>,
+        vs_name_prefix: Option<impl Into<String>>,
+        retention_policy: RetentionPolicy,
+        dry_run: bool,
+        catalog: Option<CatalogLocation>,
+    ) -> Self {
+        Self {
+            namespace: namespace.into(),
+            vs_name_prefix: vs_name_prefix.map(|vs_name_prefix| vs_name_prefix.into()),
+            retention_policy,
+            dry_run,
+            catalog,
+        }
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn vs_name_prefix(&self) -> Option<&str> {
+        self.vs_name_prefix.as_deref()
+    }
+
+    pub fn retention_policy(&self) -> &RetentionPolicy {
+        &self.retention_policy
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn catalog(&self) -> Option<&CatalogLocation> {
+        self.catalog.as_ref()
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Represents the outcome of pruning a single VolumeSnapshot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PruneStatus {
+    /// The VolumeSnapshot would be deleted, on a dry run
+    WouldDelete,
+    /// The VolumeSnapshot was deleted, along with its VolumeSnapshotContent and EBS snapshot
+    Deleted,
+    /// The VolumeSnapshot was deleted, but its VolumeSnapshotContent and EBS snapshot are
+    /// retained, since the deletion policy of the VolumeSnapshotContent is Retain
+    DeletedContentRetained(String),
+    Failed(String),
+}
+
+impl Display for PruneStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PruneStatus::WouldDelete => write!(f, "Would delete"),
+            PruneStatus::Deleted => write!(f, "Deleted"),
+            PruneStatus::DeletedContentRetained(vsc_name) => write!(
+                f,
+                "Deleted (VolumeSnapshotContent {} and its EBS snapshot are retained)",
+                vsc_name
+            ),
+            PruneStatus::Failed(e) => write!(f, "Failed: {}", e),
+        }
+    }
+}
+
+/// The result of pruning a single VolumeSnapshot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PruneResult {
+    pub volume_snapshot_name: String,
+    pub status: PruneStatus,
+}
+
+impl PruneResult {
+    pub fn new(volume_snapshot_name: impl Into<String>, status: PruneStatus) -> Self {
+        Self {
+            volume_snapshot_name: volume_snapshot_name.into(),
+            status,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        !matches!(self.status, PruneStatus::Failed(_))
+    }
+}
+
+/// A per-VolumeSnapshot summary of a prune operation
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PruneSummary {
+    pub namespace: String,
+    pub kept: usize,
+    pub results: Vec<PruneResult>,
+    /// The error of removing the pruned VolumeSnapshots from the catalog, if it failed
+    pub catalog_error: Option<String>,
+}
+
+impl PruneSummary {
+    /// Creates a new PruneSummary, sorted by VolumeSnapshot name
+    pub fn new(namespace: impl Into<String>, kept: usize, mut results: Vec<PruneResult>) -> Self {
+        results.sort_by(|a, b| a.volume_snapshot_name.cmp(&b.volume_snapshot_name));
+        Self {
+            namespace: namespace.into(),
+            kept,
+            results,
+            catalog_error: None,
+        }
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &PruneResult> {
+        self.results.iter().filter(|result| !result.is_success())
+    }
+
+    /// Whether every expired VolumeSnapshot was pruned successfully, and removed from the
+    /// catalog
+    pub fn is_success(&self) -> bool {
+        self.failed().next().is_none() && self.catalog_error.is_none()
+    }
+}
+
+impl Display for PruneSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Prune summary of {}: {} kept, {} expired, {} failed",
+            self.namespace,
+            self.kept,
+            self.results.len(),
+            self.failed().count()
+        )?;
+        if let Some(catalog_error) = &self.catalog_error {
+            writeln!(f, "  Failed to update the catalog: {}", catalog_error)?;
+        }
+        for result in &self.results {
+            writeln!(f, "  - {}: {}", result.volume_snapshot_name, result.status)?;
+        }
+        Ok(())
+    }
+}
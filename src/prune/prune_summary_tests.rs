@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod tests {
+    use crate::prune::prune_summary::{PruneResult, PruneStatus, PruneSummary};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_prune_summary() {
+        let summary = PruneSummary::new(
+            "test-ns",
+            2,
+            vec![
+                PruneResult::new("vs-b", PruneStatus::Failed("boom".to_string())),
+                PruneResult::new(
+                    "vs-a",
+                    PruneStatus::DeletedContentRetained("vsc-a".to_string()),
+                ),
+            ],
+        );
+
+        assert_eq!(summary.results[0].volume_snapshot_name, "vs-a");
+        assert!(!summary.is_success());
+        assert_eq!(
+            summary.to_string(),
+            "Prune summary of test-ns: 2 kept, 2 expired, 1 failed\n  - vs-a: Deleted (VolumeSnapshotContent vsc-a and its EBS snapshot are retained)\n  - vs-b: Failed: boom\n"
+        );
+    }
+}
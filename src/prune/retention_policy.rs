@@ -0,0 +1,231 @@
+use crate::k8s_ops::vs::volume_snapshot_naming::SOURCE_PVC_LABEL;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshots::VolumeSnapshot;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Label that protects a VolumeSnapshot from ever being pruned, when set to `true`
+pub const PROTECTED_LABEL: &str = "snap-kube/protected";
+
+/// A version of a snapshot, as seen by the retention rules
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotVersion {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SnapshotVersion {
+    pub fn new(name: impl Into<String>, created_at: DateTime<Utc>) -> Self {
+        Self {
+            name: name.into(),
+            created_at,
+        }
+    }
+}
+
+/// Represents the retention rules of the snapshots of a PVC
+///
+/// A snapshot is kept if any of the rules keeps it:
+/// * `keep_last` - The N most recent snapshots
+/// * `max_age` - The snapshots younger than the maximum age
+/// * `keep_daily`, `keep_weekly`, `keep_monthly` - The most recent snapshot of each of the
+///   N most recent days, ISO weeks and months that have a snapshot (grandfather-father-son)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub keep_last: Option<usize>,
+    pub max_age: Option<Duration>,
+    pub keep_daily: Option<usize>,
+    pub keep_weekly: Option<usize>,
+    pub keep_monthly: Option<usize>,
+}
+
+impl RetentionPolicy {
+    /// Creates a new RetentionPolicy
+    ///
+    /// # Arguments
+    ///
+    /// * `keep_last` - Number of most recent snapshots to keep
+    /// * `max_age` - Maximum age of the snapshots to keep
+    /// * `keep_daily` - Number of daily snapshots to keep
+    /// * `keep_weekly` - Number of weekly snapshots to keep
+    /// * `keep_monthly` - Number of monthly snapshots to keep
+    ///
+    /// # Returns
+    ///
+    /// A new RetentionPolicy instance, or an error if no rule is set, since it would prune
+    /// every snapshot
+    pub fn new(
+        keep_last: Option<usize>,
+        max_age: Option<Duration>,
+        keep_daily: Option<usize>,
+        keep_weekly: Option<usize>,
+        keep_monthly: Option<usize>,
+    ) -> Result<Self> {
+        let retention_policy = Self {
+            keep_last,
+            max_age,
+            keep_daily,
+            keep_weekly,
+            keep_monthly,
+        };
+        if retention_policy.is_empty() {
+            bail!("At least one retention rule is required");
+        }
+        Ok(retention_policy)
+    }
+
+    /// Whether no retention rule is set
+    pub fn is_empty(&self) -> bool {
+        self.keep_last.is_none()
+            && self.max_age.is_none()
+            && self.keep_daily.is_none()
+            && self.keep_weekly.is_none()
+            && self.keep_monthly.is_none()
+    }
+
+    /// Select the snapshot versions kept by the retention rules
+    ///
+    /// # Arguments
+    ///
+    /// * `versions` - The snapshot versions of a single PVC
+    /// * `now` - The current time, used by the maximum age rule
+    ///
+    /// # Returns
+    ///
+    /// The names of the snapshot versions to keep
+    pub fn retained(&self, versions: &[SnapshotVersion], now: DateTime<Utc>) -> BTreeSet<String> {
+        let mut versions: Vec<&SnapshotVersion> = versions.iter().collect();
+        versions.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| b.name.cmp(&a.name))
+        });
+
+        let mut retained = BTreeSet::new();
+        if let Some(keep_last) = self.keep_last {
+            retained.extend(versions.iter().take(keep_last).map(|v| v.name.clone()));
+        }
+        if let Some(max_age) = self.max_age {
+            retained.extend(
+                versions
+                    .iter()
+                    .filter(|v| now.signed_duration_since(v.created_at) <= max_age)
+                    .map(|v| v.name.clone()),
+            );
+        }
+        for (keep, period_format) in [
+            (self.keep_daily, "%Y-%m-%d"),
+            (self.keep_weekly, "%G-W%V"),
+            (self.keep_monthly, "%Y-%m"),
+        ] {
+            if let Some(keep) = keep {
+                retained.extend(Self::newest_per_period(&versions, keep, period_format));
+            }
+        }
+        retained
+    }
+
+    /// Select the newest version of each of the most recent periods
+    ///
+    /// The versions must be sorted from the newest to the oldest.
+    fn newest_per_period(
+        versions: &[&SnapshotVersion],
+        keep: usize,
+        period_format: &str,
+    ) -> Vec<String> {
+        let mut retained = Vec::new();
+        let mut last_period = None;
+        for version in versions {
+            if retained.len() >= keep {
+                break;
+            }
+            let period = version.created_at.format(period_format).to_string();
+            if last_period.as_ref() != Some(&period) {
+                retained.push(version.name.clone());
+                last_period = Some(period);
+            }
+        }
+        retained
+    }
+}
+
+/// Whether a VolumeSnapshot carries the protected label
+pub fn is_protected(volume_snapshot: &VolumeSnapshot) -> bool {
+    volume_snapshot
+        .metadata
+        .labels
+        .as_ref()
+        .and_then(|labels| labels.get(PROTECTED_LABEL))
+        .is_some_and(|value| value == "true")
+}
+
+/// Select the VolumeSnapshots that are expired by a retention policy
+///
+/// The retention rules are applied to the VolumeSnapshots of each source PVC separately.
+/// Protected VolumeSnapshots, and the ones that are not ready yet, are never expired and
+/// do not count towards the rules.
+///
+/// # Arguments
+///
+/// * `volume_snapshots` - The VolumeSnapshots of a namespace
+/// * `retention_policy` - Retention policy to apply
+/// * `now` - The current time
+///
+/// # Returns
+///
+/// The expired VolumeSnapshots, sorted by name
+pub fn select_expired_volume_snapshots(
+    volume_snapshots: Vec<VolumeSnapshot>,
+    retention_policy: &RetentionPolicy,
+    now: DateTime<Utc>,
+) -> Vec<VolumeSnapshot> {
+    let mut per_pvc: BTreeMap<String, Vec<VolumeSnapshot>> = BTreeMap::new();
+    for volume_snapshot in volume_snapshots {
+        let ready = volume_snapshot
+            .status
+            .as_ref()
+            .and_then(|status| status.ready_to_use)
+            .unwrap_or(false);
+        if is_protected(&volume_snapshot)
+            || !ready
+            || volume_snapshot.metadata.creation_timestamp.is_none()
+        {
+            continue;
+        }
+        let pvc = volume_snapshot
+            .spec
+            .source
+            .persistent_volume_claim_name
+            .clone()
+            .or_else(|| {
+                volume_snapshot
+                    .metadata
+                    .labels
+                    .as_ref()
+                    .and_then(|labels| labels.get(SOURCE_PVC_LABEL).cloned())
+            })
+            .unwrap_or_default();
+        per_pvc.entry(pvc).or_default().push(volume_snapshot);
+    }
+
+    let mut expired: Vec<VolumeSnapshot> = Vec::new();
+    for volume_snapshots in per_pvc.into_values() {
+        let versions: Vec<SnapshotVersion> = volume_snapshots
+            .iter()
+            .map(|vs| {
+                SnapshotVersion::new(
+                    vs.metadata.name.clone().unwrap_or_default(),
+                    vs.metadata.creation_timestamp.clone().unwrap().0,
+                )
+            })
+            .collect();
+        let retained = retention_policy.retained(&versions, now);
+        expired.extend(
+            volume_snapshots
+                .into_iter()
+                .filter(|vs| !retained.contains(vs.metadata.name.as_deref().unwrap_or_default())),
+        );
+    }
+    expired.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+    expired
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use crate::prune::retention_policy::{
+        select_expired_volume_snapshots, RetentionPolicy, SnapshotVersion, PROTECTED_LABEL,
+    };
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+    use kube::api::ObjectMeta;
+    use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::volumesnapshots::{
+        VolumeSnapshot, VolumeSnapshotSource, VolumeSnapshotSpec, VolumeSnapshotStatus,
+    };
+    use pretty_assertions::assert_eq;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 10, 18, 12, 0, 0).unwrap()
+    }
+
+    /// A nightly snapshot version, taken a number of days ago
+    fn nightly(days_ago: i64) -> SnapshotVersion {
+        SnapshotVersion::new(format!("vs-{}", days_ago), now() - Duration::days(days_ago))
+    }
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn volume_snapshot(name: &str, pvc: &str, days_ago: i64, protected: bool) -> VolumeSnapshot {
+        let labels =
+            protected.then(|| BTreeMap::from([(PROTECTED_LABEL.to_string(), "true".to_string())]));
+        VolumeSnapshot {
+            metadata: ObjectMeta {
+                name: Some(name.to_string()),
+                labels,
+                creation_timestamp: Some(Time(now() - Duration::days(days_ago))),
+                ..Default::default()
+            },
+            spec: VolumeSnapshotSpec {
+                source: VolumeSnapshotSource {
+                    persistent_volume_claim_name: Some(pvc.to_string()),
+                    volume_snapshot_content_name: None,
+                },
+                volume_snapshot_class_name: None,
+            },
+            status: Some(VolumeSnapshotStatus {
+                ready_to_use: Some(true),
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_retention_policy_requires_a_rule() {
+        assert!(RetentionPolicy::new(None, None, None, None, None).is_err());
+        assert!(RetentionPolicy::new(Some(0), None, None, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_keep_last_and_max_age() {
+        let versions: Vec<SnapshotVersion> = (0..10).map(nightly).collect();
+
+        let keep_last = RetentionPolicy::new(Some(3), None, None, None, None).unwrap();
+        assert_eq!(
+            keep_last.retained(&versions, now()),
+            names(&["vs-0", "vs-1", "vs-2"])
+        );
+
+        let max_age =
+            RetentionPolicy::new(None, Some(Duration::days(4)), None, None, None).unwrap();
+        assert_eq!(
+            max_age.retained(&versions, now()),
+            names(&["vs-0", "vs-1", "vs-2", "vs-3", "vs-4"])
+        );
+
+        // The rules are combined, a version is kept if any of them keeps it
+        let combined =
+            RetentionPolicy::new(Some(1), Some(Duration::days(2)), None, None, None).unwrap();
+        assert_eq!(
+            combined.retained(&versions, now()),
+            names(&["vs-0", "vs-1", "vs-2"])
+        );
+    }
+
+    #[test]
+    fn test_grandfather_father_son() {
+        // A nightly snapshot for 90 days, plus an extra one earlier today
+        let mut versions: Vec<SnapshotVersion> = (0..90).map(nightly).collect();
+        versions.push(SnapshotVersion::new("vs-early", now() - Duration::hours(6)));
+
+        let daily = RetentionPolicy::new(None, None, Some(2), None, None).unwrap();
+        assert_eq!(daily.retained(&versions, now()), names(&["vs-0", "vs-1"]));
+
+        // 2024-10-18 is a Friday, so the newest snapshot of each week is on a Sunday
+        let weekly = RetentionPolicy::new(None, None, None, Some(3), None).unwrap();
+        assert_eq!(
+            weekly.retained(&versions, now()),
+            names(&["vs-0", "vs-5", "vs-12"])
+        );
+
+        let monthly = RetentionPolicy::new(None, None, None, None, Some(3)).unwrap();
+        assert_eq!(
+            monthly.retained(&versions, now()),
+            names(&["vs-0", "vs-18", "vs-48"])
+        );
+    }
+
+    #[test]
+    fn test_select_expired_volume_snapshots() {
+        let volume_snapshots = vec![
+            volume_snapshot("data-0", "data", 0, false),
+            volume_snapshot("data-1", "data", 1, false),
+            volume_snapshot("data-2", "data", 2, true),
+            volume_snapshot("data-3", "data", 3, false),
+            volume_snapshot("wal-5", "wal", 5, false),
+        ];
+        let retention_policy = RetentionPolicy::new(Some(1), None, None, None, None).unwrap();
+
+        let expired: Vec<String> =
+            select_expired_volume_snapshots(volume_snapshots, &retention_policy, now())
+                .into_iter()
+                .map(|vs| vs.metadata.name.unwrap())
+                .collect();
+        assert_eq!(expired, vec!["data-1", "data-3"]);
+    }
+}
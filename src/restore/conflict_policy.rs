@@ -0,0 +1,36 @@
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// Suffix of the name a PVC is restored under when it already exists in the target
+/// namespace, with the rename policy
+pub const RENAME_SUFFIX: &str = "restored";
+
+/// Represents what a restore does with a PVC that already exists in the target namespace
+#[derive(
+    ValueEnum, Clone, Debug, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema,
+)]
+pub enum ConflictPolicy {
+    /// Fail the restore of the PVC, leaving the existing PVC in place
+    #[default]
+    Fail,
+    /// Skip the restore of the PVC, leaving the existing PVC in place
+    Skip,
+    /// Delete the existing PVC and restore it, provided that no pod that is not terminated
+    /// mounts it
+    Replace,
+    /// Restore the PVC under a suffixed name, next to the existing PVC
+    Rename,
+}
+
+impl Display for ConflictPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictPolicy::Fail => write!(f, "Fail"),
+            ConflictPolicy::Skip => write!(f, "Skip"),
+            ConflictPolicy::Replace => write!(f, "Replace"),
+            ConflictPolicy::Rename => write!(f, "Rename"),
+        }
+    }
+}
@@ -1,4 +1,17 @@
 #[cfg(feature = "restore")]
+pub mod conflict_policy;
+#[cfg(feature = "restore")]
 pub mod restore_operator;
 #[cfg(feature = "restore")]
 pub mod restore_payload;
+#[cfg(feature = "restore")]
+pub mod restore_strategy;
+#[cfg(feature = "restore")]
+pub mod restore_summary;
+
+#[cfg(all(test, feature = "restore"))]
+mod restore_operator_tests;
+#[cfg(all(test, feature = "restore"))]
+mod restore_payload_tests;
+#[cfg(all(test, feature = "restore"))]
+mod restore_summary_tests;
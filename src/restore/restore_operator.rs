@@ -1,93 +1,1154 @@
+use crate::aws_ops::ebs::{
+    copy_ebs_snapshot, create_ebs_client, create_ebs_volume_from_snapshot, delete_ebs_volume,
+    ebs_snapshot_restore_size, get_ebs_snapshot, is_own_ebs_snapshot,
+    wait_untill_ebs_snapshot_is_completed, wait_untill_ebs_volume_is_available, EbsSnapshotSource,
+    EbsVolumeSettings, SnapshotCopyTarget, DESCRIPTION_TAG, NAMESPACE_TAG, PVC_TAG,
+    SOURCE_SNAPSHOT_TAG,
+};
+use crate::catalog::{backup_catalog::open_catalog_store, catalog_entry::BackupCatalogEntry};
 use crate::k8s_ops::{
+    pod::pods::{get_pods_using_pvc, KubePodApi},
+    pv::persistent_volumes::StaticVolume,
     pvc::{
-        persistent_volume_claims::{check_if_pvc_exists, get_pvcs_available, KubePvcApi},
+        persistent_volume_claims::{
+            check_if_pvc_exists, delete_pvc, find_available_pvc_name, get_pvcs_available,
+            wait_untill_pvcs_are_bound, KubePvcApi, PvcApiTrait,
+        },
         persistent_volume_claims_operator::PVCOperator,
         persistent_volume_claims_payload::PVCOperatorPayload,
+        pvc_template::PvcTemplate,
+    },
+    sc::storage_classes::{
+        ensure_storage_class_consumes_snapshots, ensure_storage_class_provisioner,
+        ensure_storage_class_supports_volume_mode, get_storage_class, storage_class_fs_type,
+        storage_class_zones, EBS_CSI_DRIVER,
+    },
+    vgs::volume_group_snapshots::group_snapshot_members,
+    vs::{
+        volume_snapshot_naming::{
+            find_latest_volume_snapshot, restored_snapshot_content_name, volume_snapshot_labels,
+            IMPORT_ORIGIN, ORIGIN_LABEL,
+        },
+        volume_snapshots_operator::VolumeSnapshotOperator,
     },
-    vs::volume_snapshots_operator::VolumeSnapshotOperator,
     vsc::{
-        volume_snapshot_contents::get_snapshot_handle,
+        retain_policy::VSCRetainPolicy,
+        volume_snapshot_contents::{
+            get_snapshot_class_driver, get_snapshot_handle, get_snapshot_source,
+            volume_snapshot_class_api,
+        },
         volume_snapshot_contents_operator::VolumeSnapshotContentOperator,
     },
 };
-use anyhow::{bail, Result};
-use kube::{api::PostParams, Api, Client};
-use kube_custom_resources_rs::snapshot_storage_k8s_io::v1::{
-    volumesnapshotcontents::VolumeSnapshotContent,
-    volumesnapshots::{VolumeSnapshot, VolumeSnapshotStatus},
+use anyhow::{bail, Context, Result};
+use aws_sdk_ec2::Client as EbsClient;
+use futures::{stream, StreamExt};
+use k8s_openapi::api::{core::v1::PersistentVolume, storage::v1::StorageClass};
+use kube::{
+    api::{DeleteParams, DynamicObject, Patch, PatchParams, PostParams},
+    Api, Client,
+};
+use kube_custom_resources_rs::{
+    groupsnapshot_storage_k8s_io::v1alpha1::volumegroupsnapshots::VolumeGroupSnapshot,
+    snapshot_storage_k8s_io::v1::{
+        volumesnapshotcontents::VolumeSnapshotContent,
+        volumesnapshots::{VolumeSnapshot, VolumeSnapshotStatus},
+    },
 };
-use tracing::info;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{error, info, warn};
 
-use super::restore_payload::RestorePayload;
+use super::{
+    conflict_policy::{ConflictPolicy, RENAME_SUFFIX},
+    restore_payload::RestorePayload,
+    restore_strategy::RestoreStrategy,
+    restore_summary::{PvcRestoreResult, PvcRestoreStatus, RestoreSummary},
+};
+
+/// Reclaim policy of the static PersistentVolumes when neither the payload nor their
+/// StorageClass sets one, as for the dynamically provisioned ones
+const DEFAULT_RECLAIM_POLICY: &str = "Delete";
+
+/// Name prefix the VolumeSnapshots of the imported EBS snapshots are labelled with, when the
+/// restore has no VolumeSnapshot name prefix
+const IMPORTED_NAME_PREFIX: &str = "imported";
 
 /// A struct for restoring a PVC from a VolumeSnapshot
 pub struct RestoreOperator;
 
+/// Where the snapshot of a PVC is read from
+pub(crate) enum SnapshotSource<'a> {
+    /// The catalog entry of the PVC, when restoring a backup ID
+    CatalogEntry(&'a BackupCatalogEntry),
+    /// A VolumeSnapshot of the source namespace, e.g. a member of a VolumeGroupSnapshot
+    VolumeSnapshot(&'a str),
+    /// The newest ready VolumeSnapshot of the PVC in the source namespace
+    Latest,
+}
+
+/// How a PVC is restored, given the PVC of the same name in the target namespace, if any
+enum ConflictResolution {
+    /// No PVC of the same name exists
+    Create,
+    /// The existing PVC is left in place
+    Skip,
+    /// The existing PVC is deleted before the restore
+    Replace,
+    /// The PVC is restored under another name
+    Rename(String),
+}
+
+impl ConflictResolution {
+    /// The name the PVC is restored under
+    fn target_pvc<'a>(&'a self, pvc: &'a str) -> &'a str {
+        match self {
+            ConflictResolution::Rename(renamed) => renamed,
+            _ => pvc,
+        }
+    }
+
+    /// The status of the PVC once restored
+    fn restored_status(self) -> PvcRestoreStatus {
+        match self {
+            ConflictResolution::Create => PvcRestoreStatus::Restored,
+            ConflictResolution::Skip => PvcRestoreStatus::Skipped,
+            ConflictResolution::Replace => PvcRestoreStatus::Replaced,
+            ConflictResolution::Rename(name) => PvcRestoreStatus::Renamed(name),
+        }
+    }
+}
+
+/// What a restored PVC is provisioned from
+enum RestoreSource {
+    /// A VolumeSnapshot of the target namespace
+    VolumeSnapshot { name: String, restore_size: String },
+    /// A static PersistentVolume of an EBS volume restored from the snapshot
+    StaticVolume(StaticVolume),
+}
+
 impl RestoreOperator {
     /// Restores one or more PVCs from a VolumeSnapshot to a specific namespace
-    pub async fn restore(restore_payload: RestorePayload) -> Result<()> {
+    ///
+    /// The PVCs are restored concurrently, bounded by the `max_concurrency` of the payload.
+    /// A failure of one PVC does not abort the others, and the outcome of each PVC is
+    /// reported in the returned summary.
+    ///
+    /// If a backup ID is set, the PVCs and their snapshots are read from the catalog entries
+    /// of that backup, instead of being derived from the VolumeSnapshot name prefix.
+    ///
+    /// If a VolumeGroupSnapshot is set, every member PVC of the group is restored with its
+    /// original name, see [`RestoreOperator::restore_group`].
+    ///
+    /// If an EBS snapshot is set, the PVC is restored from it, see
+    /// [`RestoreOperator::restore_from_ebs_snapshot`].
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `on_result` - Called with the outcome of each PVC as soon as it is known, e.g. to
+    ///   report the progress of the restore. The members of a VolumeGroupSnapshot are only
+    ///   known once the whole group is restored
+    pub async fn restore(
+        restore_payload: RestorePayload,
+        on_result: impl Fn(&PvcRestoreResult),
+    ) -> Result<RestoreSummary> {
+        restore_payload.pvc_overrides().validate()?;
+        restore_payload.pvc_rename().validate()?;
+        restore_payload.validate_restore_strategy()?;
+
         // Create a Kubernetes client
         let k8s_client = Client::try_default().await?;
 
         // Define the VolumeSnapshot, VolumeSnapshotContent and PersistentVolumeClaim APIs
-        let restore_k8s_apis_struct = RestoreKubernetesApisStruct {
-            source_vs_api: Api::namespaced(k8s_client.clone(), restore_payload.source_ns()),
-            source_pvcs_api: KubePvcApi {
-                api: Api::namespaced(k8s_client.clone(), restore_payload.source_ns()),
-            },
-            target_vs_api: Api::namespaced(k8s_client.clone(), restore_payload.target_ns()),
-            target_pvcs_api: KubePvcApi {
-                api: Api::namespaced(k8s_client.clone(), restore_payload.target_ns()),
-            },
-            vsc_api: Api::all(k8s_client.clone()),
+        let restore_k8s_apis_struct =
+            RestoreKubernetesApisStruct::new(k8s_client.clone(), &restore_payload);
+
+        if let Some(ebs_snapshot_source) = restore_payload.ebs_snapshot_source() {
+            let pvc = restore_payload
+                .pvc_name()
+                .context("A PVC name is required to restore an EBS snapshot")?;
+            let target_pvc = restore_payload.pvc_rename().rename(pvc)?;
+            let status = match Self::restore_from_ebs_snapshot(
+                &restore_payload,
+                &restore_k8s_apis_struct,
+                &target_pvc,
+                ebs_snapshot_source,
+            )
+            .await
+            {
+                Ok(status) => status,
+                Err(e) => {
+                    error!("Failed to restore PVC {}: {:#}", pvc, e);
+                    PvcRestoreStatus::Failed(format!("{:#}", e))
+                }
+            };
+            let result = PvcRestoreResult::new(pvc, status).with_target_pvc_name(target_pvc);
+            on_result(&result);
+            return Ok(RestoreSummary::new(vec![result]));
+        }
+
+        if let Some(volume_group_snapshot) = restore_payload.volume_group_snapshot() {
+            let restore_summary = Self::restore_group(
+                &restore_payload,
+                &restore_k8s_apis_struct,
+                volume_group_snapshot,
+            )
+            .await?;
+            restore_summary.results.iter().for_each(on_result);
+            return Ok(restore_summary);
+        }
+
+        // Read the catalog entries of the backup to restore, if any
+        let catalog_entries = match restore_payload.backup_id() {
+            Some(backup_id) => {
+                let catalog_location = restore_payload
+                    .catalog()
+                    .context("A catalog is required to restore a backup ID")?;
+                let entries = open_catalog_store(k8s_client.clone(), catalog_location)
+                    .get(backup_id)
+                    .await?;
+                Self::select_catalog_entries(&restore_payload, entries)
+            }
+            None => BTreeMap::new(),
         };
 
-        // Check if we will restore all PVCs in the namespace
-        let pvcs = if restore_payload.include_all_pvcs() {
-            get_pvcs_available(&restore_k8s_apis_struct.source_pvcs_api).await?
+        // Check if we will restore the PVCs of the backup, all PVCs in the namespace, or the ones matching the selector
+        let pvcs = if restore_payload.backup_id().is_some() {
+            catalog_entries.keys().cloned().collect()
+        } else if restore_payload.include_all_pvcs() || !restore_payload.pvc_selector().is_empty() {
+            get_pvcs_available(
+                &restore_k8s_apis_struct.source_pvcs_api,
+                restore_payload.pvc_selector(),
+            )
+            .await?
         } else {
             vec![restore_payload
                 .pvc_name()
-                .unwrap_or_else(|| panic!("PVC name is required when include_all_pvcs is false"))
+                .context("PVC name is required when include_all_pvcs is false and no PVC selector is set")?
                 .to_string()]
         };
+        let target_pvcs = restore_payload.pvc_rename().rename_all(&pvcs)?;
+        Self::validate_storage_classes(
+            &restore_payload,
+            &restore_k8s_apis_struct,
+            &pvcs,
+            &catalog_entries,
+        )
+        .await?;
 
-        // We will iterate over the PVCs vector and restore each PVC
+        // Restore each PVC in isolation and keep the outcome of each one
+        let results: Vec<PvcRestoreResult> = stream::iter(target_pvcs)
+            .map(|(pvc, target_pvc)| async {
+                let snapshot_source = match catalog_entries.get(&pvc) {
+                    Some(entry) => SnapshotSource::CatalogEntry(entry),
+                    None => SnapshotSource::Latest,
+                };
+                let status = match Self::restore_pvc(
+                    &restore_payload,
+                    &restore_k8s_apis_struct,
+                    &pvc,
+                    &target_pvc,
+                    snapshot_source,
+                )
+                .await
+                {
+                    Ok(status) => status,
+                    Err(e) => {
+                        error!("Failed to restore PVC {}: {:#}", pvc, e);
+                        PvcRestoreStatus::Failed(format!("{:#}", e))
+                    }
+                };
+                let result = PvcRestoreResult::new(pvc, status).with_target_pvc_name(target_pvc);
+                on_result(&result);
+                result
+            })
+            .buffer_unordered(restore_payload.max_concurrency())
+            .collect()
+            .await;
 
-        for pvc in pvcs {
+        Ok(RestoreSummary::new(results))
+    }
+
+    /// Restores every member PVC of a VolumeGroupSnapshot, with its original name unless it
+    /// is renamed by the payload
+    ///
+    /// The restore of the group is all or nothing: with the fail conflict policy, it is
+    /// checked up front that none of the members exists in the target namespace, and a member
+    /// is only reported as restored once all the restored members are Bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `volume_group_snapshot` - Name of the VolumeGroupSnapshot in the source namespace
+    ///
+    /// # Returns
+    ///
+    /// The outcome of each member PVC
+    async fn restore_group(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        volume_group_snapshot: &str,
+    ) -> Result<RestoreSummary> {
+        info!("Restoring VolumeGroupSnapshot: {}", volume_group_snapshot);
+        let status = restore_k8s_apis_struct
+            .source_vgs_api
+            .get(volume_group_snapshot)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to get VolumeGroupSnapshot {}",
+                    volume_group_snapshot
+                )
+            })?
+            .status
+            .unwrap_or_default();
+        if !status.ready_to_use.unwrap_or(false) {
+            bail!(
+                "VolumeGroupSnapshot {} is not ready to use",
+                volume_group_snapshot
+            );
+        }
+        let members = group_snapshot_members(&status);
+        if members.is_empty() {
+            bail!(
+                "VolumeGroupSnapshot {} has no members",
+                volume_group_snapshot
+            );
+        }
+        let target_pvcs = restore_payload.pvc_rename().rename_all(members.keys())?;
+        Self::validate_storage_classes(
+            restore_payload,
+            restore_k8s_apis_struct,
+            &members.keys().cloned().collect::<Vec<_>>(),
+            &BTreeMap::new(),
+        )
+        .await?;
+        if restore_payload.on_conflict() == ConflictPolicy::Fail {
+            for target_pvc in target_pvcs.values() {
+                check_if_pvc_exists(&restore_k8s_apis_struct.target_pvcs_api, target_pvc, false)
+                    .await?;
+            }
+        }
+
+        let member_count = members.len();
+        let results: Vec<PvcRestoreResult> = stream::iter(members)
+            .map(|(pvc, volume_snapshot_name)| {
+                let target_pvc = target_pvcs[&pvc].clone();
+                async move {
+                    let status = match Self::restore_pvc(
+                        restore_payload,
+                        restore_k8s_apis_struct,
+                        &pvc,
+                        &target_pvc,
+                        SnapshotSource::VolumeSnapshot(&volume_snapshot_name),
+                    )
+                    .await
+                    {
+                        Ok(status) => status,
+                        Err(e) => {
+                            error!("Failed to restore PVC {}: {:#}", pvc, e);
+                            PvcRestoreStatus::Failed(format!("{:#}", e))
+                        }
+                    };
+                    PvcRestoreResult::new(pvc, status).with_target_pvc_name(target_pvc)
+                }
+            })
+            .buffer_unordered(restore_payload.max_concurrency())
+            .collect()
+            .await;
+
+        let restored: Vec<String> = results
+            .iter()
+            .filter_map(|result| result.restored_pvc_name().map(str::to_string))
+            .collect();
+        let failed_count = results.iter().filter(|result| !result.is_success()).count();
+        let group_error = if failed_count > 0 {
+            Some(format!(
+                "{} of {} members of VolumeGroupSnapshot {} failed to restore",
+                failed_count, member_count, volume_group_snapshot
+            ))
+        } else {
+            wait_untill_pvcs_are_bound(
+                &restore_k8s_apis_struct.target_pvcs_api,
+                &restored,
+                restore_payload.bind_timeout(),
+            )
+            .await
+            .err()
+            .map(|e| format!("{:#}", e))
+        };
+
+        let results = results
+            .into_iter()
+            .map(|result| match &group_error {
+                Some(group_error) if result.restored_pvc_name().is_some() => PvcRestoreResult {
+                    status: PvcRestoreStatus::Failed(format!(
+                        "The group was not fully restored: {}",
+                        group_error
+                    )),
+                    ..result
+                },
+                _ => result,
+            })
+            .collect();
+        Ok(RestoreSummary::new(results))
+    }
+
+    /// Restores a PVC from an EBS snapshot taken outside of the cluster
+    ///
+    /// If the snapshot is in another region, or is shared by another account, it is first
+    /// copied into the region of the cluster, so that the restored PVC does not depend on it.
+    ///
+    /// The snapshot is imported as a VolumeSnapshot of the target namespace, labelled like
+    /// the ones of a backup, so that later restores and prunes find it. A snapshot imported as
+    /// is, e.g. one made by AWS Backup or by hand, always gets a Retain policy, so that
+    /// deleting its VolumeSnapshot never deletes it.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvc` - Name of the PVC to restore
+    /// * `ebs_snapshot_source` - The EBS snapshot to restore the PVC from
+    ///
+    /// # Returns
+    ///
+    /// The status of the PVC
+    async fn restore_from_ebs_snapshot(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvc: &str,
+        ebs_snapshot_source: &EbsSnapshotSource,
+    ) -> Result<PvcRestoreStatus> {
+        info!(
+            "Restoring PVC: {} from EBS snapshot: {}",
+            pvc, ebs_snapshot_source.snapshot_id
+        );
+        let conflict_resolution =
+            Self::resolve_conflict(restore_payload, restore_k8s_apis_struct, pvc).await?;
+        if let ConflictResolution::Skip = conflict_resolution {
+            return Ok(PvcRestoreStatus::Skipped);
+        }
+        let pvc_template = PvcTemplate::default().with_overrides(restore_payload.pvc_overrides());
+        let storage_class_name = Self::resolve_storage_class(
+            restore_payload,
+            restore_k8s_apis_struct,
+            None,
+            &pvc_template,
+        )
+        .await?;
+
+        let ebs_client = create_ebs_client(Some(restore_payload.region().to_string())).await?;
+        let source_region = ebs_snapshot_source
+            .region
+            .as_deref()
+            .unwrap_or(restore_payload.region());
+        let needs_copy = source_region != restore_payload.region()
+            || !is_own_ebs_snapshot(&ebs_client, &ebs_snapshot_source.snapshot_id).await?;
+
+        let snapshot_id = if needs_copy {
+            let tags = BTreeMap::from([(
+                DESCRIPTION_TAG.to_string(),
+                format!(
+                    "snap-kube restore of PVC {}/{}",
+                    restore_payload.target_ns(),
+                    pvc
+                ),
+            )]);
+            let (_, snapshot_id) = copy_ebs_snapshot(
+                source_region,
+                &ebs_snapshot_source.snapshot_id,
+                &SnapshotCopyTarget {
+                    region: restore_payload.region().to_string(),
+                    kms_key_id: ebs_snapshot_source.kms_key_id.clone(),
+                },
+                &tags,
+            )
+            .await?;
+            snapshot_id
+        } else {
+            ebs_snapshot_source.snapshot_id.clone()
+        };
+        wait_untill_ebs_snapshot_is_completed(
+            &ebs_client,
+            &snapshot_id,
+            ebs_snapshot_source.copy_timeout(),
+        )
+        .await?;
+
+        let vsc_retain_policy = if needs_copy
+            || restore_payload.restore_strategy() == RestoreStrategy::StaticVolume
+        {
+            *restore_payload.vsc_retain_policy()
+        } else {
+            if *restore_payload.vsc_retain_policy() == VSCRetainPolicy::Delete {
+                warn!(
+                    "EBS snapshot {} is imported as is, its VolumeSnapshotContent gets a Retain policy",
+                    snapshot_id
+                );
+            }
+            VSCRetainPolicy::Retain
+        };
+        let name_prefix = match restore_payload.vs_name_prefix() {
+            "" => IMPORTED_NAME_PREFIX,
+            vs_name_prefix => vs_name_prefix,
+        };
+
+        let snapshot = get_ebs_snapshot(ebs_client, snapshot_id.clone()).await?;
+        let restore_size = ebs_snapshot_restore_size(&snapshot)?;
+        let target_pvc = conflict_resolution.target_pvc(pvc);
+        // The imported VolumeSnapshot is found like a backed up one, but never pruned
+        let mut labels = volume_snapshot_labels(name_prefix, target_pvc, &snapshot_id);
+        labels.insert(ORIGIN_LABEL.to_string(), IMPORT_ORIGIN.to_string());
+        let restore_source = Self::create_restore_source(
+            restore_payload,
+            restore_k8s_apis_struct,
+            target_pvc,
+            format!("{}-{}", target_pvc, snapshot_id),
+            snapshot_id.clone(),
+            restore_size,
+            labels,
+            vsc_retain_policy,
+            &pvc_template,
+            &storage_class_name,
+        )
+        .await?;
+        Self::restore_target_pvc(
+            restore_payload,
+            restore_k8s_apis_struct,
+            pvc,
+            &conflict_resolution,
+            restore_source,
+            pvc_template,
+            &storage_class_name,
+        )
+        .await?;
+        Ok(conflict_resolution.restored_status())
+    }
+
+    /// Resolve the conflict of a PVC with the PVC of the same name in the target namespace,
+    /// according to the conflict policy of the payload
+    ///
+    /// Nothing is changed yet, an existing PVC to replace is only checked not to be used by a
+    /// pod, see [`RestoreOperator::prepare_target_pvc`].
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvc` - Name of the PVC to restore
+    ///
+    /// # Returns
+    ///
+    /// How the PVC is restored, or an error with the fail policy
+    async fn resolve_conflict(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvc: &str,
+    ) -> Result<ConflictResolution> {
+        if restore_k8s_apis_struct
+            .target_pvcs_api
+            .get_opt(pvc)
+            .await?
+            .is_none()
+        {
+            return Ok(ConflictResolution::Create);
+        }
+
+        match restore_payload.on_conflict() {
+            ConflictPolicy::Fail => bail!(
+                "PVC already exists: {} on target namespace {:?}",
+                pvc,
+                restore_payload.target_ns()
+            ),
+            ConflictPolicy::Skip => {
+                warn!(
+                    "PVC {} already exists on target namespace {}, skipping it",
+                    pvc,
+                    restore_payload.target_ns()
+                );
+                Ok(ConflictResolution::Skip)
+            }
+            ConflictPolicy::Replace => {
+                Self::ensure_pvc_is_not_in_use(restore_k8s_apis_struct, pvc).await?;
+                Ok(ConflictResolution::Replace)
+            }
+            ConflictPolicy::Rename => {
+                let renamed = find_available_pvc_name(
+                    &restore_k8s_apis_struct.target_pvcs_api,
+                    pvc,
+                    RENAME_SUFFIX,
+                )
+                .await?;
+                info!(
+                    "PVC {} already exists on target namespace {}, restoring it as {}",
+                    pvc,
+                    restore_payload.target_ns(),
+                    renamed
+                );
+                Ok(ConflictResolution::Rename(renamed))
+            }
+        }
+    }
+
+    /// Check that no pod uses a PVC to replace, including the pods that are still pending
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvc` - Name of the PVC to replace
+    async fn ensure_pvc_is_not_in_use(
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvc: &str,
+    ) -> Result<()> {
+        let pods = get_pods_using_pvc(&restore_k8s_apis_struct.target_pods_api, pvc).await?;
+        if !pods.is_empty() {
+            bail!(
+                "PVC {} cannot be replaced, it is used by the pods: {}",
+                pvc,
+                pods.iter()
+                    .map(|pod| pod.metadata.name.clone().unwrap_or_default())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Delete the existing PVC if it is replaced, right before restoring it
+    ///
+    /// This runs once the source of the restored PVC is created, see
+    /// [`RestoreOperator::create_restore_source`]. The PVC is checked again not to be used by
+    /// a pod, as one may have been scheduled since the conflict was resolved.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvc` - Name of the PVC to restore
+    /// * `conflict_resolution` - How the PVC is restored
+    async fn prepare_target_pvc(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvc: &str,
+        conflict_resolution: &ConflictResolution,
+    ) -> Result<()> {
+        if let ConflictResolution::Replace = conflict_resolution {
+            Self::ensure_pvc_is_not_in_use(restore_k8s_apis_struct, pvc).await?;
+            delete_pvc(
+                &restore_k8s_apis_struct.target_pvcs_api,
+                pvc,
+                restore_payload.bind_timeout(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Select the catalog entries of the source namespace that match the PVC selection
+    ///
+    /// Only the PVC name and the include/exclude regexes can be evaluated against the catalog,
+    /// the label and field selectors are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `entries` - Catalog entries of the backup
+    ///
+    /// # Returns
+    ///
+    /// The selected catalog entries, keyed by PVC name
+    fn select_catalog_entries(
+        restore_payload: &RestorePayload,
+        entries: Vec<BackupCatalogEntry>,
+    ) -> BTreeMap<String, BackupCatalogEntry> {
+        let pvc_selector = restore_payload.pvc_selector();
+        if pvc_selector.label_selector.is_some() || pvc_selector.field_selector.is_some() {
+            warn!("Label and field selectors are ignored when restoring a backup ID");
+        }
+        entries
+            .into_iter()
+            .filter(|entry| entry.source_ns == restore_payload.source_ns())
+            .filter(|entry| match restore_payload.pvc_name() {
+                Some(pvc_name) => entry.pvc_name == pvc_name,
+                None => pvc_selector.matches_name(&entry.pvc_name),
+            })
+            .map(|entry| (entry.pvc_name.clone(), entry))
+            .collect()
+    }
+
+    /// Get the newest ready VolumeSnapshot of a PVC in the source namespace
+    ///
+    /// The VolumeSnapshots taken by a backup are looked up by their labels. If there are none,
+    /// the VolumeSnapshot named `{vs_name_prefix}-{pvc}` is used instead, as it was named
+    /// before the names were versioned.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvc` - Name of the PVC to restore
+    ///
+    /// # Returns
+    ///
+    /// The VolumeSnapshot to restore the PVC from
+    async fn get_latest_volume_snapshot(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvc: &str,
+    ) -> Result<VolumeSnapshot> {
+        if let Some(volume_snapshot) = find_latest_volume_snapshot(
+            &restore_k8s_apis_struct.source_vs_api,
+            restore_payload.vs_name_prefix(),
+            pvc,
+        )
+        .await?
+        {
+            info!(
+                "Using the latest VolumeSnapshot of PVC {}: {}",
+                pvc,
+                volume_snapshot.metadata.name.clone().unwrap_or_default()
+            );
+            return Ok(volume_snapshot);
+        }
+
+        let volume_snapshot_name = format!("{}-{}", restore_payload.vs_name_prefix(), pvc);
+        match restore_k8s_apis_struct
+            .source_vs_api
+            .get(&volume_snapshot_name)
+            .await
+        {
+            Ok(volume_snapshot) => Ok(volume_snapshot),
+            Err(e) => bail!("Failed to get VolumeSnapshot status: {}", e),
+        }
+    }
+
+    /// Get the name, snapshot handle, restore size and source volume mode of a bound
+    /// VolumeSnapshot
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `source_volume_snapshot` - The VolumeSnapshot in the source namespace
+    ///
+    /// # Returns
+    ///
+    /// The name, snapshot handle and restore size of the VolumeSnapshot, and the volume mode
+    /// of its source volume, if recorded in its VolumeSnapshotContent
+    async fn resolve_volume_snapshot(
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        source_volume_snapshot: VolumeSnapshot,
+    ) -> Result<(String, String, String, Option<String>)> {
+        let volume_snapshot_name = source_volume_snapshot
+            .metadata
+            .name
+            .context("VolumeSnapshot has no name")?;
+        let status: VolumeSnapshotStatus = source_volume_snapshot
+            .status
+            .context("VolumeSnapshot has no status")?;
+
+        let bound_vsc_name = status
+            .bound_volume_snapshot_content_name
+            .context("VolumeSnapshot is not bound to a VolumeSnapshotContent")?;
+        let restore_size = status
+            .restore_size
+            .context("VolumeSnapshot has no restore size")?;
+
+        let (snapshot_handle, source_volume_mode) =
+            get_snapshot_source(restore_k8s_apis_struct.vsc_api.clone(), &bound_vsc_name).await?;
+        Ok((
+            volume_snapshot_name,
+            snapshot_handle,
+            restore_size,
+            source_volume_mode,
+        ))
+    }
+
+    /// Restores a single PVC from its VolumeSnapshot
+    ///
+    /// The access modes, volume mode, labels and annotations of the source PVC are carried
+    /// over to the restored one, unless overridden by the payload. They are read from the
+    /// catalog entry of the PVC, or else from the source PVC itself. The volume mode falls
+    /// back to the one recorded in the VolumeSnapshotContent of the source VolumeSnapshot.
+    /// The StorageClass of the source PVC is mapped to the one of the restored PVC.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvc` - Name of the PVC to restore
+    /// * `target_pvc` - Name the PVC is restored under in the target namespace
+    /// * `snapshot_source` - Where the snapshot of the PVC is read from
+    ///
+    /// # Returns
+    ///
+    /// The status of the PVC
+    pub(crate) async fn restore_pvc(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvc: &str,
+        target_pvc: &str,
+        snapshot_source: SnapshotSource<'_>,
+    ) -> Result<PvcRestoreStatus> {
+        if target_pvc == pvc {
             info!("Restoring PVC: {}", pvc);
-            let volume_snapshot_name = format!("{}-{}", restore_payload.vs_name_prefix(), pvc);
-            let volume_snapshot_content_name =
-                format!("{}-{}", restore_payload.vsc_name_prefix(), pvc);
+        } else {
+            info!("Restoring PVC: {} as {}", pvc, target_pvc);
+        }
+
+        // Check if the PVC exists in the target namespace, and what to do with it
+        let conflict_resolution =
+            Self::resolve_conflict(restore_payload, restore_k8s_apis_struct, target_pvc).await?;
+        if let ConflictResolution::Skip = conflict_resolution {
+            return Ok(PvcRestoreStatus::Skipped);
+        }
 
-            // Check if the PVC exists in the target namespace, it should not exist
-            check_if_pvc_exists(&restore_k8s_apis_struct.target_pvcs_api, &pvc, false).await?;
+        // Get the snapshot from the catalog entry, or from a VolumeSnapshot in the source namespace
+        let (volume_snapshot_name, snapshot_handle, restore_size, source_volume_mode) =
+            match snapshot_source {
+                SnapshotSource::CatalogEntry(entry) => (
+                    entry.volume_snapshot_name.clone(),
+                    entry.snapshot_handle.clone(),
+                    entry.restore_size.clone(),
+                    None,
+                ),
+                SnapshotSource::VolumeSnapshot(volume_snapshot_name) => {
+                    let source_volume_snapshot = restore_k8s_apis_struct
+                        .source_vs_api
+                        .get(volume_snapshot_name)
+                        .await
+                        .with_context(|| {
+                            format!("Failed to get VolumeSnapshot {}", volume_snapshot_name)
+                        })?;
+                    Self::resolve_volume_snapshot(restore_k8s_apis_struct, source_volume_snapshot)
+                        .await?
+                }
+                SnapshotSource::Latest => {
+                    let source_volume_snapshot = Self::get_latest_volume_snapshot(
+                        restore_payload,
+                        restore_k8s_apis_struct,
+                        pvc,
+                    )
+                    .await?;
+                    Self::resolve_volume_snapshot(restore_k8s_apis_struct, source_volume_snapshot)
+                        .await?
+                }
+            };
 
-            let status: VolumeSnapshotStatus = match restore_k8s_apis_struct
-                .source_vs_api
-                .get(&volume_snapshot_name)
+        // Get the spec, labels, annotations and StorageClass of the source PVC
+        let (mut pvc_template, source_storage_class_name) = match snapshot_source {
+            SnapshotSource::CatalogEntry(entry) => (
+                PvcTemplate::from_source(
+                    entry.pvc_spec.as_ref(),
+                    Some(&entry.pvc_labels),
+                    Some(&entry.pvc_annotations),
+                ),
+                entry
+                    .pvc_spec
+                    .as_ref()
+                    .and_then(|spec| spec.storage_class_name.clone()),
+            ),
+            _ => match restore_k8s_apis_struct
+                .source_pvcs_api
+                .api
+                .get_opt(pvc)
                 .await
+                .with_context(|| format!("Failed to get source PVC {}", pvc))?
             {
-                Ok(snapshot) => snapshot.status.unwrap(),
-                Err(e) => {
-                    bail!("Failed to get VolumeSnapshot status: {}", e)
+                Some(source_pvc) => (
+                    PvcTemplate::from_pvc(&source_pvc),
+                    source_pvc.spec.and_then(|spec| spec.storage_class_name),
+                ),
+                None => {
+                    warn!(
+                        "Source PVC {} does not exist, the restored PVC gets the default spec",
+                        pvc
+                    );
+                    (PvcTemplate::default(), None)
                 }
+            },
+        };
+        if pvc_template.volume_mode.is_none() {
+            pvc_template.volume_mode = source_volume_mode;
+        }
+        let pvc_template = pvc_template.with_overrides(restore_payload.pvc_overrides());
+        let storage_class_name = Self::resolve_storage_class(
+            restore_payload,
+            restore_k8s_apis_struct,
+            source_storage_class_name.as_deref(),
+            &pvc_template,
+        )
+        .await?;
+
+        // Create the source of the restored PVC before an existing PVC is deleted, so that a
+        // failure leaves it in place
+        let restored_pvc = conflict_resolution.target_pvc(target_pvc);
+        let restore_source = Self::create_restore_source(
+            restore_payload,
+            restore_k8s_apis_struct,
+            restored_pvc,
+            volume_snapshot_name,
+            snapshot_handle,
+            restore_size,
+            BTreeMap::new(),
+            *restore_payload.vsc_retain_policy(),
+            &pvc_template,
+            &storage_class_name,
+        )
+        .await?;
+        Self::restore_target_pvc(
+            restore_payload,
+            restore_k8s_apis_struct,
+            target_pvc,
+            &conflict_resolution,
+            restore_source,
+            pvc_template,
+            &storage_class_name,
+        )
+        .await?;
+        Ok(conflict_resolution.restored_status())
+    }
+
+    /// Get the StorageClass of a restored PVC, and check that it can restore the PVC
+    ///
+    /// The source StorageClass is mapped to its target StorageClass, falling back to the
+    /// StorageClass of the payload, see [`RestoreOperator::check_storage_class`].
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `source_storage_class_name` - StorageClass of the source PVC, if known
+    /// * `pvc_template` - Access modes, volume mode, labels and annotations of the PVC
+    ///
+    /// # Returns
+    ///
+    /// The name of the target StorageClass
+    async fn resolve_storage_class(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        source_storage_class_name: Option<&str>,
+        pvc_template: &PvcTemplate,
+    ) -> Result<String> {
+        let storage_class_name =
+            restore_payload.target_storage_class_name(source_storage_class_name);
+        if let Some(source_storage_class_name) = source_storage_class_name {
+            if source_storage_class_name != storage_class_name {
+                info!(
+                    "Mapping StorageClass {} to {}",
+                    source_storage_class_name, storage_class_name
+                );
+            }
+        }
+
+        Self::check_storage_class(
+            restore_payload,
+            restore_k8s_apis_struct,
+            storage_class_name,
+            pvc_template.volume_mode.as_deref(),
+        )
+        .await?;
+        Ok(storage_class_name.to_string())
+    }
+
+    /// Check every target StorageClass of the PVCs to restore, before any of them is restored
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvcs` - Names of the source PVCs to restore
+    /// * `catalog_entries` - Catalog entries of the PVCs, keyed by PVC name
+    ///
+    /// # Returns
+    ///
+    /// An error naming the first StorageClass that cannot restore the PVCs
+    pub(crate) async fn validate_storage_classes(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvcs: &[String],
+        catalog_entries: &BTreeMap<String, BackupCatalogEntry>,
+    ) -> Result<()> {
+        let mut storage_class_names = BTreeSet::new();
+        for pvc in pvcs {
+            let source_storage_class_name = match catalog_entries.get(pvc) {
+                Some(entry) => entry
+                    .pvc_spec
+                    .as_ref()
+                    .and_then(|spec| spec.storage_class_name.clone()),
+                None => restore_k8s_apis_struct
+                    .source_pvcs_api
+                    .api
+                    .get_opt(pvc)
+                    .await
+                    .with_context(|| format!("Failed to get source PVC {}", pvc))?
+                    .and_then(|source_pvc| source_pvc.spec)
+                    .and_then(|spec| spec.storage_class_name),
             };
+            storage_class_names.insert(
+                restore_payload
+                    .target_storage_class_name(source_storage_class_name.as_deref())
+                    .to_string(),
+            );
+        }
 
-            let bound_vsc_name = status.bound_volume_snapshot_content_name.unwrap();
-            let restore_size = status.restore_size.unwrap();
+        for storage_class_name in storage_class_names {
+            Self::check_storage_class(
+                restore_payload,
+                restore_k8s_apis_struct,
+                &storage_class_name,
+                None,
+            )
+            .await
+            .context("The restore was not started")?;
+        }
+        Ok(())
+    }
 
-            let snapshot_handle =
-                get_snapshot_handle(restore_k8s_apis_struct.vsc_api.clone(), &bound_vsc_name)
-                    .await?;
+    /// Check that a target StorageClass can restore the PVCs
+    ///
+    /// The StorageClass must exist, and be provisioned by the CSI driver of the
+    /// VolumeSnapshotClass of the payload, or by the EBS CSI driver with the static volume
+    /// strategy. If a volume mode is given, the StorageClass must support it.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `storage_class_name` - Name of the target StorageClass
+    /// * `volume_mode` - Volume mode of the PVC, if known
+    async fn check_storage_class(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        storage_class_name: &str,
+        volume_mode: Option<&str>,
+    ) -> Result<()> {
+        let storage_class = get_storage_class(
+            &restore_k8s_apis_struct.storage_class_api,
+            storage_class_name,
+        )
+        .await?;
+        match restore_payload.restore_strategy() {
+            RestoreStrategy::Snapshot => {
+                let driver = get_snapshot_class_driver(
+                    &restore_k8s_apis_struct.vs_class_api,
+                    restore_payload.volume_snapshot_class(),
+                )
+                .await?;
+                ensure_storage_class_consumes_snapshots(&storage_class, &driver)?
+            }
+            RestoreStrategy::StaticVolume => {
+                ensure_storage_class_provisioner(&storage_class, EBS_CSI_DRIVER)?
+            }
+        }
+        if let Some(volume_mode) = volume_mode {
+            ensure_storage_class_supports_volume_mode(&storage_class, volume_mode)?;
+        }
+        Ok(())
+    }
+
+    /// Creates the VolumeSnapshotContent and VolumeSnapshot restoring a snapshot in the target
+    /// namespace, or reuses them if they already hold the snapshot
+    ///
+    /// With the static volume strategy, the EBS volume and its PersistentVolume are created
+    /// without a VolumeSnapshot instead, see [`RestoreOperator::create_static_volume`].
+    ///
+    /// This runs before an existing PVC is replaced, so that a failure leaves it in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvc` - Name of the PVC to restore
+    /// * `volume_snapshot_name` - Name of the VolumeSnapshot in the target namespace
+    /// * `snapshot_handle` - ID of the EBS snapshot
+    /// * `restore_size` - Size of the restored volume
+    /// * `volume_snapshot_labels` - Labels of the VolumeSnapshot in the target namespace
+    /// * `vsc_retain_policy` - Retain policy of the VolumeSnapshotContent
+    /// * `pvc_template` - Access modes, volume mode, labels and annotations of the PVC
+    /// * `storage_class_name` - StorageClass of the PVC
+    ///
+    /// # Returns
+    ///
+    /// What the PVC is restored from
+    #[allow(clippy::too_many_arguments)]
+    async fn create_restore_source(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvc: &str,
+        volume_snapshot_name: String,
+        snapshot_handle: String,
+        restore_size: String,
+        volume_snapshot_labels: BTreeMap<String, String>,
+        vsc_retain_policy: VSCRetainPolicy,
+        pvc_template: &PvcTemplate,
+        storage_class_name: &str,
+    ) -> Result<RestoreSource> {
+        if restore_payload.restore_strategy() == RestoreStrategy::StaticVolume {
+            let static_volume = Self::create_static_volume(
+                restore_payload,
+                restore_k8s_apis_struct,
+                pvc,
+                &snapshot_handle,
+                pvc_template,
+                storage_class_name,
+            )
+            .await?;
+            return Ok(RestoreSource::StaticVolume(static_volume));
+        }
+
+        // Reuse the VolumeSnapshot of the target namespace if it already holds the snapshot,
+        // e.g. when restoring into the source namespace or replacing a PVC restored before
+        if Self::is_volume_snapshot_reusable(
+            restore_k8s_apis_struct,
+            &volume_snapshot_name,
+            &snapshot_handle,
+        )
+        .await?
+        {
+            info!(
+                "Reusing VolumeSnapshot: {} on namespace: {}",
+                volume_snapshot_name,
+                restore_payload.target_ns()
+            );
+            return Ok(RestoreSource::VolumeSnapshot {
+                name: volume_snapshot_name,
+                restore_size,
+            });
+        }
 
+        let volume_snapshot_content_name = restored_snapshot_content_name(
+            restore_payload.vsc_name_prefix(),
+            restore_payload.target_ns(),
+            &volume_snapshot_name,
+        );
+
+        // Reuse the VolumeSnapshotContent left behind by a VolumeSnapshot deleted before, e.g.
+        // with a retain policy
+        if Self::is_snapshot_content_reusable(
+            restore_payload,
+            restore_k8s_apis_struct,
+            &volume_snapshot_content_name,
+            &volume_snapshot_name,
+            &snapshot_handle,
+        )
+        .await?
+        {
+            info!(
+                "Reusing VolumeSnapshotContent: {}",
+                volume_snapshot_content_name
+            );
+        } else {
             let vsc_operator = VolumeSnapshotContentOperator::new(
                 volume_snapshot_content_name.clone(),
                 restore_payload.target_ns().to_string(),
                 volume_snapshot_name.clone(),
                 Some(restore_payload.volume_snapshot_class().to_string()),
                 Some(snapshot_handle.clone()),
-                *restore_payload.vsc_retain_policy(),
+                vsc_retain_policy,
+                pvc_template.volume_mode.clone(),
             );
 
             let snapshot_content = vsc_operator.construct_volume_snapshot_content_resource();
@@ -103,90 +1164,529 @@ impl RestoreOperator {
                         "{}",
                         format!(
                             "Created VolumeSnapshotContent: {} on namespace: {}",
-                            snapshot_content.metadata.name.clone().unwrap(),
+                            snapshot_content.metadata.name.clone().unwrap_or_default(),
                             restore_payload.target_ns()
                         )
                     )
                 }
-                Err(e) => panic!("Failed to create VolumeSnapshotContent: {}", e),
+                Err(e) => bail!("Failed to create VolumeSnapshotContent: {}", e),
             }
+        }
 
-            let vs_operator = VolumeSnapshotOperator::new(
-                volume_snapshot_name.clone(),
-                restore_payload.target_ns().to_string(),
-                restore_payload.volume_snapshot_class().to_string(),
-                None,
-                Some(volume_snapshot_content_name),
-            );
+        let vs_operator = VolumeSnapshotOperator::new(
+            volume_snapshot_name.clone(),
+            restore_payload.target_ns().to_string(),
+            restore_payload.volume_snapshot_class().to_string(),
+            None,
+            Some(volume_snapshot_content_name),
+            volume_snapshot_labels,
+        );
 
-            let target_volume_snapshot = vs_operator.construct_volume_snapshot_resource(
-                Some(snapshot_handle.to_string()),
-                Some(restore_size.to_string()),
-                *restore_payload.vsc_retain_policy(),
-            );
+        let target_volume_snapshot = vs_operator.construct_volume_snapshot_resource(
+            Some(snapshot_handle.to_string()),
+            Some(restore_size.to_string()),
+            vsc_retain_policy,
+        );
 
-            info!("Creating VolumeSnapshot in the target namespace...");
-            let pp = PostParams::default();
-            match restore_k8s_apis_struct
-                .target_vs_api
-                .create(&pp, &target_volume_snapshot)
-                .await
-            {
-                Ok(target_volume_snapshot) => {
-                    info!(
-                        "{}",
-                        format!(
-                            "Created VolumeSnapshot: {} on namespace: {}",
-                            target_volume_snapshot.metadata.name.clone().unwrap(),
-                            restore_payload.target_ns()
-                        )
+        info!("Creating VolumeSnapshot in the target namespace...");
+        let pp = PostParams::default();
+        match restore_k8s_apis_struct
+            .target_vs_api
+            .create(&pp, &target_volume_snapshot)
+            .await
+        {
+            Ok(target_volume_snapshot) => {
+                info!(
+                    "{}",
+                    format!(
+                        "Created VolumeSnapshot: {} on namespace: {}",
+                        target_volume_snapshot
+                            .metadata
+                            .name
+                            .clone()
+                            .unwrap_or_default(),
+                        restore_payload.target_ns()
                     )
-                }
-                Err(e) => panic!("Failed to create VolumeSnapshot: {}", e),
+                )
             }
+            Err(e) => bail!("Failed to create VolumeSnapshot: {}", e),
+        }
+
+        Ok(RestoreSource::VolumeSnapshot {
+            name: volume_snapshot_name,
+            restore_size,
+        })
+    }
 
-            // Restore the PVC for each pvc available
-            let pvc_payload = PVCOperatorPayload::new(
+    /// Deletes the PVC to replace, if any, then creates the restored PVC from its source
+    ///
+    /// If the PVC is not restored, a static volume is deleted along with its EBS volume,
+    /// while a VolumeSnapshot is left for a later restore.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvc` - Name of the PVC of the target namespace it conflicts with
+    /// * `conflict_resolution` - How the PVC is restored
+    /// * `restore_source` - What the PVC is restored from
+    /// * `pvc_template` - Access modes, volume mode, labels and annotations of the PVC
+    /// * `storage_class_name` - StorageClass of the PVC
+    async fn restore_target_pvc(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvc: &str,
+        conflict_resolution: &ConflictResolution,
+        restore_source: RestoreSource,
+        pvc_template: PvcTemplate,
+        storage_class_name: &str,
+    ) -> Result<()> {
+        let static_volume = match &restore_source {
+            RestoreSource::StaticVolume(static_volume) => Some(static_volume.clone()),
+            RestoreSource::VolumeSnapshot { .. } => None,
+        };
+        let result = async {
+            Self::prepare_target_pvc(
+                restore_payload,
+                restore_k8s_apis_struct,
                 pvc,
-                restore_payload.target_ns(),
-                Some(restore_payload.storage_class_name().to_string()),
-                None,
+                conflict_resolution,
+            )
+            .await?;
+            Self::create_restored_pvc(
+                restore_payload,
+                restore_k8s_apis_struct,
+                conflict_resolution.target_pvc(pvc),
+                restore_source,
+                pvc_template,
+                storage_class_name,
+            )
+            .await
+        }
+        .await;
+
+        if let (Err(_), Some(static_volume)) = (&result, static_volume) {
+            match create_ebs_client(Some(restore_payload.region().to_string())).await {
+                Ok(ebs_client) => {
+                    Self::discard_static_volume(
+                        restore_k8s_apis_struct,
+                        &ebs_client,
+                        &static_volume,
+                        true,
+                    )
+                    .await
+                }
+                Err(e) => error!(
+                    "Failed to delete PersistentVolume {} and EBS volume {}: {:#}",
+                    static_volume.pv_name, static_volume.volume_id, e
+                ),
+            }
+        }
+        result
+    }
+
+    /// Creates the restored PVC in the target namespace
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvc` - Name of the PVC to restore
+    /// * `restore_source` - What the PVC is restored from
+    /// * `pvc_template` - Access modes, volume mode, labels and annotations of the PVC
+    /// * `storage_class_name` - StorageClass of the PVC
+    async fn create_restored_pvc(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvc: &str,
+        restore_source: RestoreSource,
+        pvc_template: PvcTemplate,
+        storage_class_name: &str,
+    ) -> Result<()> {
+        let pvc = match restore_source {
+            RestoreSource::VolumeSnapshot { name, restore_size } => {
+                let pvc_payload = PVCOperatorPayload::new(
+                    pvc,
+                    restore_payload.target_ns(),
+                    Some(storage_class_name.to_string()),
+                    pvc_template.access_modes(),
+                    name,
+                    restore_size,
+                    pvc_template.volume_mode,
+                    pvc_template.labels,
+                    pvc_template.annotations,
+                );
+                PVCOperator::new(pvc_payload).construct_persistent_volume_claim_resource()
+            }
+            RestoreSource::StaticVolume(static_volume) => static_volume
+                .construct_persistent_volume_claim_resource(
+                    restore_payload.target_ns(),
+                    pvc,
+                    &pvc_template,
+                ),
+        };
+
+        info!("Restoring PVC...");
+        let pp = PostParams::default();
+        match restore_k8s_apis_struct
+            .target_pvcs_api
+            .api
+            .create(&pp, &pvc)
+            .await
+        {
+            Ok(pvc) => info!(
+                "{}",
+                format!(
+                    "Restored PVC: {} on namespace: {}",
+                    pvc.metadata.name.clone().unwrap_or_default(),
+                    restore_payload.target_ns()
+                )
+            ),
+            Err(e) => bail!("Failed to restore PVC: {}", e),
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the VolumeSnapshot of the target namespace already holds a snapshot
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `volume_snapshot_name` - Name of the VolumeSnapshot in the target namespace
+    /// * `snapshot_handle` - ID of the EBS snapshot
+    ///
+    /// # Returns
+    ///
+    /// Whether the VolumeSnapshot exists and is bound to the snapshot, or an error if it
+    /// exists for another snapshot
+    async fn is_volume_snapshot_reusable(
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        volume_snapshot_name: &str,
+        snapshot_handle: &str,
+    ) -> Result<bool> {
+        let volume_snapshot = match restore_k8s_apis_struct
+            .target_vs_api
+            .get_opt(volume_snapshot_name)
+            .await?
+        {
+            Some(volume_snapshot) => volume_snapshot,
+            None => return Ok(false),
+        };
+        let bound_vsc_name = volume_snapshot
+            .status
+            .and_then(|status| status.bound_volume_snapshot_content_name)
+            .with_context(|| {
+                format!(
+                    "VolumeSnapshot {} already exists in the target namespace and is not bound",
+                    volume_snapshot_name
+                )
+            })?;
+        let existing_snapshot_handle =
+            get_snapshot_handle(restore_k8s_apis_struct.vsc_api.clone(), &bound_vsc_name).await?;
+        if existing_snapshot_handle != snapshot_handle {
+            bail!(
+                "VolumeSnapshot {} already exists in the target namespace for snapshot {}",
                 volume_snapshot_name,
-                restore_size,
+                existing_snapshot_handle
             );
+        }
+        Ok(true)
+    }
 
-            let pvc_operator = PVCOperator::new(pvc_payload);
-            let pvc = pvc_operator.construct_persistent_volume_claim_resource();
+    /// Check whether the VolumeSnapshotContent of a restored VolumeSnapshot already exists
+    /// for the snapshot, and release it from the deleted VolumeSnapshot it was bound to
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `volume_snapshot_content_name` - Name of the VolumeSnapshotContent
+    /// * `volume_snapshot_name` - Name of the VolumeSnapshot in the target namespace
+    /// * `snapshot_handle` - ID of the EBS snapshot
+    ///
+    /// # Returns
+    ///
+    /// Whether the VolumeSnapshotContent exists for the snapshot, or an error if it exists
+    /// for another snapshot or VolumeSnapshot
+    async fn is_snapshot_content_reusable(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        volume_snapshot_content_name: &str,
+        volume_snapshot_name: &str,
+        snapshot_handle: &str,
+    ) -> Result<bool> {
+        let snapshot_content = match restore_k8s_apis_struct
+            .vsc_api
+            .get_opt(volume_snapshot_content_name)
+            .await?
+        {
+            Some(snapshot_content) => snapshot_content,
+            None => return Ok(false),
+        };
+        let existing_snapshot_handle = snapshot_content
+            .spec
+            .source
+            .snapshot_handle
+            .or(snapshot_content
+                .status
+                .and_then(|status| status.snapshot_handle))
+            .unwrap_or_default();
+        if existing_snapshot_handle != snapshot_handle {
+            bail!(
+                "VolumeSnapshotContent {} already exists for snapshot {}",
+                volume_snapshot_content_name,
+                existing_snapshot_handle
+            );
+        }
+        let volume_snapshot_ref = snapshot_content.spec.volume_snapshot_ref;
+        if volume_snapshot_ref.namespace.as_deref() != Some(restore_payload.target_ns())
+            || volume_snapshot_ref.name.as_deref() != Some(volume_snapshot_name)
+        {
+            bail!(
+                "VolumeSnapshotContent {} already exists for VolumeSnapshot {}/{}",
+                volume_snapshot_content_name,
+                volume_snapshot_ref.namespace.unwrap_or_default(),
+                volume_snapshot_ref.name.unwrap_or_default()
+            );
+        }
 
-            info!("Restoring PVC...");
-            let pp = PostParams::default();
-            match restore_k8s_apis_struct
-                .target_pvcs_api
-                .api
-                .create(&pp, &pvc)
+        // The VolumeSnapshot it was bound to is gone, unbind it so the new one can bind to it
+        if volume_snapshot_ref.uid.is_some() {
+            restore_k8s_apis_struct
+                .vsc_api
+                .patch(
+                    volume_snapshot_content_name,
+                    &PatchParams::default(),
+                    &Patch::Merge(json!({ "spec": { "volumeSnapshotRef": { "uid": null } } })),
+                )
                 .await
-            {
-                Ok(pvc) => info!(
-                    "{}",
+                .with_context(|| {
                     format!(
-                        "Restored PVC: {} on namespace: {}",
-                        pvc.metadata.name.clone().unwrap(),
-                        restore_payload.target_ns()
+                        "Failed to unbind VolumeSnapshotContent {}",
+                        volume_snapshot_content_name
                     )
+                })?;
+        }
+        Ok(true)
+    }
+
+    /// Creates an EBS volume from a snapshot, along with a static PersistentVolume reserved
+    /// for the PVC in the target namespace
+    ///
+    /// The settings of the volume are read from the payload, then from the parameters of the
+    /// StorageClass, which must be provisioned by the EBS CSI driver. The availability zone
+    /// is the one of the payload, or the single zone allowed by the StorageClass.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_payload` - Payload of the restore operation
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `pvc` - Name of the PVC to restore
+    /// * `snapshot_id` - ID of the EBS snapshot
+    /// * `pvc_template` - Access modes, volume mode, labels and annotations of the PVC
+    /// * `storage_class_name` - StorageClass of the PVC
+    ///
+    /// # Returns
+    ///
+    /// The static volume, whose PVC is still to be created
+    async fn create_static_volume(
+        restore_payload: &RestorePayload,
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        pvc: &str,
+        snapshot_id: &str,
+        pvc_template: &PvcTemplate,
+        storage_class_name: &str,
+    ) -> Result<StaticVolume> {
+        let storage_class = get_storage_class(
+            &restore_k8s_apis_struct.storage_class_api,
+            storage_class_name,
+        )
+        .await?;
+        ensure_storage_class_provisioner(&storage_class, EBS_CSI_DRIVER)?;
+
+        let settings = restore_payload.ebs_volume_settings().clone().or(
+            EbsVolumeSettings::from_storage_class_parameters(
+                &storage_class.parameters.clone().unwrap_or_default(),
+            )?,
+        );
+        let availability_zone = match settings.availability_zone.clone() {
+            Some(availability_zone) => availability_zone,
+            None => match storage_class_zones(&storage_class).as_slice() {
+                [availability_zone] => availability_zone.clone(),
+                _ => bail!(
+                    "StorageClass {} does not allow a single zone, an availability zone is required",
+                    storage_class_name
                 ),
-                Err(e) => panic!("Failed to restore PVC: {}", e),
-            }
+            },
+        };
+
+        let ebs_client = create_ebs_client(Some(restore_payload.region().to_string())).await?;
+        let snapshot = get_ebs_snapshot(ebs_client.clone(), snapshot_id.to_string()).await?;
+        let size = ebs_snapshot_restore_size(&snapshot)?;
+
+        let tags = BTreeMap::from([
+            (
+                NAMESPACE_TAG.to_string(),
+                restore_payload.target_ns().to_string(),
+            ),
+            (PVC_TAG.to_string(), pvc.to_string()),
+            (SOURCE_SNAPSHOT_TAG.to_string(), snapshot_id.to_string()),
+        ]);
+        let volume_id = create_ebs_volume_from_snapshot(
+            &ebs_client,
+            snapshot_id,
+            &availability_zone,
+            &settings,
+            &tags,
+        )
+        .await?;
+        let static_volume = StaticVolume {
+            pv_name: format!("{}-{}", pvc, volume_id),
+            volume_id: volume_id.clone(),
+            availability_zone,
+            size,
+            storage_class: storage_class_name.to_string(),
+            fs_type: storage_class_fs_type(&storage_class),
+            reclaim_policy: match restore_payload.pv_reclaim_policy() {
+                Some(pv_reclaim_policy) => pv_reclaim_policy.to_string(),
+                None => storage_class
+                    .reclaim_policy
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_RECLAIM_POLICY.to_string()),
+            },
+        };
+
+        if let Err(e) = wait_untill_ebs_volume_is_available(
+            &ebs_client,
+            &volume_id,
+            restore_payload.bind_timeout(),
+        )
+        .await
+        {
+            Self::discard_static_volume(
+                restore_k8s_apis_struct,
+                &ebs_client,
+                &static_volume,
+                false,
+            )
+            .await;
+            return Err(e);
         }
 
-        Ok(())
+        let pp = PostParams::default();
+        if let Err(e) = restore_k8s_apis_struct
+            .pv_api
+            .create(
+                &pp,
+                &static_volume.construct_persistent_volume_resource(
+                    restore_payload.target_ns(),
+                    pvc,
+                    pvc_template,
+                ),
+            )
+            .await
+        {
+            Self::discard_static_volume(
+                restore_k8s_apis_struct,
+                &ebs_client,
+                &static_volume,
+                false,
+            )
+            .await;
+            bail!(
+                "Failed to create PersistentVolume {}: {}",
+                static_volume.pv_name,
+                e
+            );
+        }
+        info!(
+            "Created PersistentVolume: {} for EBS volume: {}",
+            static_volume.pv_name, volume_id
+        );
+
+        Ok(static_volume)
+    }
+
+    /// Deletes a static volume whose PVC was not restored, so that its EBS volume is not
+    /// leaked
+    ///
+    /// Its PersistentVolume is deleted first, if created, then its EBS volume. A failure is
+    /// only logged, so that the error of the restore is the one reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `restore_k8s_apis_struct` - Kubernetes APIs for the restore operation
+    /// * `ebs_client` - EBS client of the region of the volume
+    /// * `static_volume` - The static volume
+    /// * `has_persistent_volume` - Whether its PersistentVolume was created
+    async fn discard_static_volume(
+        restore_k8s_apis_struct: &RestoreKubernetesApisStruct,
+        ebs_client: &EbsClient,
+        static_volume: &StaticVolume,
+        has_persistent_volume: bool,
+    ) {
+        if has_persistent_volume {
+            match restore_k8s_apis_struct
+                .pv_api
+                .delete(&static_volume.pv_name, &DeleteParams::default())
+                .await
+            {
+                Ok(_) => info!("Deleted PersistentVolume: {}", static_volume.pv_name),
+                Err(e) => {
+                    error!(
+                        "Failed to delete PersistentVolume {}, EBS volume {} is left in place: {}",
+                        static_volume.pv_name, static_volume.volume_id, e
+                    );
+                    return;
+                }
+            }
+        }
+        if let Err(e) = delete_ebs_volume(ebs_client, &static_volume.volume_id).await {
+            error!("{:#}", e);
+        }
     }
 }
 
 /// A struct for holding the Kubernetes APIs for the restore operation
-struct RestoreKubernetesApisStruct {
+pub(crate) struct RestoreKubernetesApisStruct {
     source_vs_api: Api<VolumeSnapshot>,
     source_pvcs_api: KubePvcApi,
     target_vs_api: Api<VolumeSnapshot>,
     target_pvcs_api: KubePvcApi,
     vsc_api: Api<VolumeSnapshotContent>,
+    vs_class_api: Api<DynamicObject>,
+    source_vgs_api: Api<VolumeGroupSnapshot>,
+    storage_class_api: Api<StorageClass>,
+    pv_api: Api<PersistentVolume>,
+    target_pods_api: KubePodApi,
+}
+
+impl RestoreKubernetesApisStruct {
+    /// Creates the Kubernetes APIs of the source and target namespaces of a restore
+    ///
+    /// # Arguments
+    ///
+    /// * `k8s_client` - Kubernetes client
+    /// * `restore_payload` - Payload of the restore operation
+    pub(crate) fn new(k8s_client: Client, restore_payload: &RestorePayload) -> Self {
+        Self {
+            source_vs_api: Api::namespaced(k8s_client.clone(), restore_payload.source_ns()),
+            source_pvcs_api: KubePvcApi {
+                api: Api::namespaced(k8s_client.clone(), restore_payload.source_ns()),
+            },
+            target_vs_api: Api::namespaced(k8s_client.clone(), restore_payload.target_ns()),
+            target_pvcs_api: KubePvcApi {
+                api: Api::namespaced(k8s_client.clone(), restore_payload.target_ns()),
+            },
+            vsc_api: Api::all(k8s_client.clone()),
+            vs_class_api: volume_snapshot_class_api(k8s_client.clone()),
+            source_vgs_api: Api::namespaced(k8s_client.clone(), restore_payload.source_ns()),
+            storage_class_api: Api::all(k8s_client.clone()),
+            pv_api: Api::all(k8s_client.clone()),
+            target_pods_api: KubePodApi {
+                api: Api::namespaced(k8s_client, restore_payload.target_ns()),
+            },
+        }
+    }
 }
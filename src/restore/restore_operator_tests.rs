@@ -0,0 +1,252 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        catalog::catalog_entry::BackupCatalogEntry,
+        k8s_ops::{
+            fake_api_server::FakeApiServer,
+            vs::volume_snapshot_naming::restored_snapshot_content_name,
+            vsc::retain_policy::VSCRetainPolicy,
+        },
+        restore::{
+            conflict_policy::ConflictPolicy,
+            restore_operator::{RestoreKubernetesApisStruct, RestoreOperator, SnapshotSource},
+            restore_payload::RestorePayload,
+            restore_summary::PvcRestoreStatus,
+        },
+    };
+    use chrono::Utc;
+    use k8s_openapi::api::core::v1::PersistentVolumeClaimSpec;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::{collections::BTreeMap, time::Duration};
+
+    const PVC_PATH: &str = "/api/v1/namespaces/target/persistentvolumeclaims/data";
+    const VS_PATH: &str =
+        "/apis/snapshot.storage.k8s.io/v1/namespaces/target/volumesnapshots/data-snap-1";
+
+    fn replace_payload() -> RestorePayload {
+        RestorePayload::new(
+            "source",
+            "target",
+            "ebs-vsc",
+            Some("data"),
+            false,
+            "data",
+            "restored",
+            "gp3",
+            VSCRetainPolicy::Retain,
+            "eu-west-1",
+        )
+        .with_max_concurrency(1)
+        .with_bind_timeout(Duration::from_secs(1))
+        .with_on_conflict(ConflictPolicy::Replace)
+    }
+
+    fn catalog_entry() -> BackupCatalogEntry {
+        BackupCatalogEntry {
+            backup_id: "backup-1".to_string(),
+            created_at: Utc::now(),
+            source_ns: "source".to_string(),
+            pvc_name: "data".to_string(),
+            pvc_spec: Some(PersistentVolumeClaimSpec {
+                storage_class_name: Some("gp3".to_string()),
+                ..Default::default()
+            }),
+            pvc_labels: BTreeMap::new(),
+            pvc_annotations: BTreeMap::new(),
+            volume_snapshot_class: "ebs-vsc".to_string(),
+            volume_snapshot_name: "data-snap-1".to_string(),
+            volume_snapshot_content_name: "snapcontent-1".to_string(),
+            snapshot_handle: "snap-1".to_string(),
+            restore_size: "1Gi".to_string(),
+            volume_group_snapshot_name: None,
+            snapshot_copies: vec![],
+        }
+    }
+
+    /// An API server with an EBS CSI StorageClass and VolumeSnapshotClass
+    fn api_server() -> FakeApiServer {
+        let server = FakeApiServer::new();
+        server.insert(
+            "/apis/storage.k8s.io/v1/storageclasses/gp3",
+            json!({
+                "apiVersion": "storage.k8s.io/v1",
+                "kind": "StorageClass",
+                "metadata": { "name": "gp3" },
+                "provisioner": "ebs.csi.aws.com",
+            }),
+        );
+        server.insert(
+            "/apis/snapshot.storage.k8s.io/v1/volumesnapshotclasses/ebs-vsc",
+            json!({
+                "apiVersion": "snapshot.storage.k8s.io/v1",
+                "kind": "VolumeSnapshotClass",
+                "metadata": { "name": "ebs-vsc" },
+                "driver": "ebs.csi.aws.com",
+                "deletionPolicy": "Delete",
+            }),
+        );
+        server
+    }
+
+    /// An API server with the PVC to replace, and a VolumeSnapshotContent left behind by a
+    /// previous restore of a snapshot
+    fn api_server_with_snapshot_content(snapshot_handle: &str) -> (FakeApiServer, String) {
+        let server = api_server();
+        server.insert(
+            PVC_PATH,
+            json!({
+                "apiVersion": "v1",
+                "kind": "PersistentVolumeClaim",
+                "metadata": { "name": "data", "namespace": "target" },
+                "spec": { "storageClassName": "gp3" },
+            }),
+        );
+        let vsc_name = restored_snapshot_content_name("restored", "target", "data-snap-1");
+        server.insert(
+            &format!(
+                "/apis/snapshot.storage.k8s.io/v1/volumesnapshotcontents/{}",
+                vsc_name
+            ),
+            json!({
+                "apiVersion": "snapshot.storage.k8s.io/v1",
+                "kind": "VolumeSnapshotContent",
+                "metadata": { "name": vsc_name },
+                "spec": {
+                    "deletionPolicy": "Retain",
+                    "driver": "ebs.csi.aws.com",
+                    "source": { "snapshotHandle": snapshot_handle },
+                    "volumeSnapshotRef": {
+                        "name": "data-snap-1",
+                        "namespace": "target",
+                        "uid": "uid-deleted",
+                    },
+                },
+            }),
+        );
+        (server, vsc_name)
+    }
+
+    #[tokio::test]
+    async fn test_restore_pvc_replace_keeps_pvc_on_conflicting_snapshot_content() {
+        let (server, _) = api_server_with_snapshot_content("snap-other");
+        let payload = replace_payload();
+        let apis = RestoreKubernetesApisStruct::new(server.client("default"), &payload);
+        let entry = catalog_entry();
+
+        let err = RestoreOperator::restore_pvc(
+            &payload,
+            &apis,
+            "data",
+            "data",
+            SnapshotSource::CatalogEntry(&entry),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("already exists for snapshot snap-other"));
+        assert!(server.get(PVC_PATH).is_some());
+        assert!(server.get(VS_PATH).is_none());
+        assert!(!server
+            .requests()
+            .iter()
+            .any(|request| request.starts_with("DELETE")));
+    }
+
+    #[tokio::test]
+    async fn test_restore_pvc_replace_reuses_snapshot_content() {
+        let (server, vsc_name) = api_server_with_snapshot_content("snap-1");
+        let payload = replace_payload();
+        let apis = RestoreKubernetesApisStruct::new(server.client("default"), &payload);
+        let entry = catalog_entry();
+
+        let status = RestoreOperator::restore_pvc(
+            &payload,
+            &apis,
+            "data",
+            "data",
+            SnapshotSource::CatalogEntry(&entry),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(status, PvcRestoreStatus::Replaced);
+        let vsc = server
+            .get(&format!(
+                "/apis/snapshot.storage.k8s.io/v1/volumesnapshotcontents/{}",
+                vsc_name
+            ))
+            .unwrap();
+        assert!(vsc["spec"]["volumeSnapshotRef"]["uid"].is_null());
+        let volume_snapshot = server.get(VS_PATH).unwrap();
+        assert_eq!(
+            volume_snapshot["spec"]["source"]["volumeSnapshotContentName"],
+            json!(vsc_name)
+        );
+        let pvc = server.get(PVC_PATH).unwrap();
+        assert_eq!(pvc["spec"]["dataSource"]["name"], json!("data-snap-1"));
+
+        // The VolumeSnapshot is created before the PVC is deleted
+        let requests = server.requests();
+        let created_vs = requests
+            .iter()
+            .position(|request| {
+                request == "POST /apis/snapshot.storage.k8s.io/v1/namespaces/target/volumesnapshots"
+            })
+            .unwrap();
+        let deleted_pvc = requests
+            .iter()
+            .position(|request| *request == format!("DELETE {}", PVC_PATH))
+            .unwrap();
+        assert!(created_vs < deleted_pvc);
+    }
+
+    #[tokio::test]
+    async fn test_validate_storage_classes() {
+        let server = api_server();
+        server.insert(
+            "/apis/storage.k8s.io/v1/storageclasses/pd-ssd",
+            json!({
+                "apiVersion": "storage.k8s.io/v1",
+                "kind": "StorageClass",
+                "metadata": { "name": "pd-ssd" },
+                "provisioner": "pd.csi.storage.gke.io",
+            }),
+        );
+        for (pvc, storage_class) in [("data", "gp3"), ("logs", "gp2")] {
+            server.insert(
+                &format!("/api/v1/namespaces/source/persistentvolumeclaims/{}", pvc),
+                json!({
+                    "apiVersion": "v1",
+                    "kind": "PersistentVolumeClaim",
+                    "metadata": { "name": pvc, "namespace": "source" },
+                    "spec": { "storageClassName": storage_class },
+                }),
+            );
+        }
+        let pvcs = ["data".to_string(), "logs".to_string()];
+        let mut payload = replace_payload();
+        let apis = RestoreKubernetesApisStruct::new(server.client("default"), &payload);
+
+        // The unmapped StorageClasses fall back to the one of the payload
+        RestoreOperator::validate_storage_classes(&payload, &apis, &pvcs, &BTreeMap::new())
+            .await
+            .unwrap();
+
+        payload.storage_class_mappings =
+            BTreeMap::from([("gp2".to_string(), "pd-ssd".to_string())]);
+        let err =
+            RestoreOperator::validate_storage_classes(&payload, &apis, &pvcs, &BTreeMap::new())
+                .await
+                .unwrap_err();
+        assert!(format!("{:#}", err).contains(
+            "StorageClass pd-ssd is provisioned by pd.csi.storage.gke.io, which cannot restore the snapshots of ebs.csi.aws.com"
+        ));
+        assert!(!server
+            .requests()
+            .iter()
+            .any(|request| request.starts_with("POST")));
+    }
+}
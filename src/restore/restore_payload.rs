@@ -1,4 +1,15 @@
-use crate::k8s_ops::vsc::retain_policy::VSCRetainPolicy;
+use super::{conflict_policy::ConflictPolicy, restore_strategy::RestoreStrategy};
+use crate::{
+    aws_ops::ebs::{EbsSnapshotSource, EbsVolumeSettings},
+    catalog::backup_catalog::CatalogLocation,
+    k8s_ops::{
+        pv::reclaim_policy::PVReclaimPolicy,
+        pvc::{pvc_rename::PvcRename, pvc_selector::PvcSelector, pvc_template::PvcTemplate},
+        vsc::retain_policy::VSCRetainPolicy,
+    },
+};
+use anyhow::{bail, Result};
+use std::{collections::BTreeMap, time::Duration};
 
 pub struct RestorePayload {
     pub source_ns: String,
@@ -6,13 +17,36 @@ pub struct RestorePayload {
     pub volume_snapshot_class: String,
     pub pvc_name: Option<String>,
     pub include_all_pvcs: bool,
+    pub pvc_selector: PvcSelector,
     pub vs_name_prefix: String,
     pub vsc_name_prefix: String,
     pub storage_class_name: String,
     pub vsc_retain_policy: VSCRetainPolicy,
+    pub max_concurrency: usize,
+    pub backup_id: Option<String>,
+    pub catalog: Option<CatalogLocation>,
+    pub volume_group_snapshot: Option<String>,
+    pub bind_timeout: Duration,
+    pub region: String,
+    pub ebs_snapshot_source: Option<EbsSnapshotSource>,
+    pub restore_strategy: RestoreStrategy,
+    pub ebs_volume_settings: EbsVolumeSettings,
+    pub pvc_overrides: PvcTemplate,
+    pub on_conflict: ConflictPolicy,
+    pub pvc_rename: PvcRename,
+    pub storage_class_mappings: BTreeMap<String, String>,
+    pub pv_reclaim_policy: Option<PVReclaimPolicy>,
 }
 
+/// Default maximum number of PVCs restored concurrently
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Default maximum time to wait for the PVCs to be Bound or deleted
+const DEFAULT_BIND_TIMEOUT: Duration = Duration::from_secs(600);
+
 impl RestorePayload {
+    /// Creates a payload restoring VolumeSnapshots of the source namespace with the snapshot
+    /// strategy, the other options being set by the `with_*` methods
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         source_ns: impl Into<String>,
@@ -24,6 +58,7 @@ impl RestorePayload {
         vsc_name_prefix: impl Into<String>,
         storage_class_name: impl Into<String>,
         vsc_retain_policy: VSCRetainPolicy,
+        region: impl Into<String>,
     ) -> Self {
         Self {
             source_ns: source_ns.into(),
@@ -31,13 +66,114 @@ impl RestorePayload {
             volume_snapshot_class: volume_snapshot_class.into(),
             pvc_name: pvc_name.map(|pvc_name| pvc_name.into()),
             include_all_pvcs,
+            pvc_selector: PvcSelector::default(),
             vs_name_prefix: vs_name_prefix.into(),
             vsc_name_prefix: vsc_name_prefix.into(),
             storage_class_name: storage_class_name.into(),
             vsc_retain_policy,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            backup_id: None,
+            catalog: None,
+            volume_group_snapshot: None,
+            bind_timeout: DEFAULT_BIND_TIMEOUT,
+            region: region.into(),
+            ebs_snapshot_source: None,
+            restore_strategy: RestoreStrategy::Snapshot,
+            ebs_volume_settings: EbsVolumeSettings::default(),
+            pvc_overrides: PvcTemplate::default(),
+            on_conflict: ConflictPolicy::Fail,
+            pvc_rename: PvcRename::default(),
+            storage_class_mappings: BTreeMap::new(),
+            pv_reclaim_policy: None,
         }
     }
 
+    /// Selects the PVCs of the source namespace by labels, fields or name patterns
+    pub fn with_pvc_selector(mut self, pvc_selector: PvcSelector) -> Self {
+        self.pvc_selector = pvc_selector;
+        self
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Restores the PVCs of a backup ID of the catalog, if set
+    pub fn with_backup_id(mut self, backup_id: Option<impl Into<String>>) -> Self {
+        self.backup_id = backup_id.map(|backup_id| backup_id.into());
+        self
+    }
+
+    pub fn with_catalog(mut self, catalog: Option<CatalogLocation>) -> Self {
+        self.catalog = catalog;
+        self
+    }
+
+    /// Restores the members of a VolumeGroupSnapshot of the source namespace, if set
+    pub fn with_volume_group_snapshot(
+        mut self,
+        volume_group_snapshot: Option<impl Into<String>>,
+    ) -> Self {
+        self.volume_group_snapshot =
+            volume_group_snapshot.map(|volume_group_snapshot| volume_group_snapshot.into());
+        self
+    }
+
+    pub fn with_bind_timeout(mut self, bind_timeout: Duration) -> Self {
+        self.bind_timeout = bind_timeout;
+        self
+    }
+
+    /// Restores the PVC from an EBS snapshot, if set
+    pub fn with_ebs_snapshot_source(
+        mut self,
+        ebs_snapshot_source: Option<EbsSnapshotSource>,
+    ) -> Self {
+        self.ebs_snapshot_source = ebs_snapshot_source;
+        self
+    }
+
+    pub fn with_restore_strategy(mut self, restore_strategy: RestoreStrategy) -> Self {
+        self.restore_strategy = restore_strategy;
+        self
+    }
+
+    /// Sets the EBS volume settings and the PersistentVolume reclaim policy of the static
+    /// volume strategy
+    pub fn with_static_volume(
+        mut self,
+        ebs_volume_settings: EbsVolumeSettings,
+        pv_reclaim_policy: Option<PVReclaimPolicy>,
+    ) -> Self {
+        self.ebs_volume_settings = ebs_volume_settings;
+        self.pv_reclaim_policy = pv_reclaim_policy;
+        self
+    }
+
+    pub fn with_pvc_overrides(mut self, pvc_overrides: PvcTemplate) -> Self {
+        self.pvc_overrides = pvc_overrides;
+        self
+    }
+
+    pub fn with_on_conflict(mut self, on_conflict: ConflictPolicy) -> Self {
+        self.on_conflict = on_conflict;
+        self
+    }
+
+    pub fn with_pvc_rename(mut self, pvc_rename: PvcRename) -> Self {
+        self.pvc_rename = pvc_rename;
+        self
+    }
+
+    pub fn with_storage_class_mappings(
+        mut self,
+        storage_class_mappings: BTreeMap<String, String>,
+    ) -> Self {
+        self.storage_class_mappings = storage_class_mappings;
+        self
+    }
+
     pub fn source_ns(&self) -> &str {
         &self.source_ns
     }
@@ -58,6 +194,10 @@ impl RestorePayload {
         self.include_all_pvcs
     }
 
+    pub fn pvc_selector(&self) -> &PvcSelector {
+        &self.pvc_selector
+    }
+
     pub fn vs_name_prefix(&self) -> &str {
         &self.vs_name_prefix
     }
@@ -70,7 +210,101 @@ impl RestorePayload {
         &self.storage_class_name
     }
 
+    pub fn storage_class_mappings(&self) -> &BTreeMap<String, String> {
+        &self.storage_class_mappings
+    }
+
+    /// StorageClass of a restored PVC, the one its source StorageClass is mapped to, or else
+    /// the fallback StorageClass
+    pub fn target_storage_class_name(&self, source_storage_class_name: Option<&str>) -> &str {
+        source_storage_class_name
+            .and_then(|source_storage_class_name| {
+                self.storage_class_mappings.get(source_storage_class_name)
+            })
+            .map(String::as_str)
+            .unwrap_or(&self.storage_class_name)
+    }
+
     pub fn vsc_retain_policy(&self) -> &VSCRetainPolicy {
         &self.vsc_retain_policy
     }
+
+    /// Maximum number of PVCs restored concurrently, at least 1
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency.max(1)
+    }
+
+    pub fn backup_id(&self) -> Option<&str> {
+        self.backup_id.as_deref()
+    }
+
+    pub fn catalog(&self) -> Option<&CatalogLocation> {
+        self.catalog.as_ref()
+    }
+
+    /// Name of the VolumeGroupSnapshot in the source namespace whose members are restored
+    pub fn volume_group_snapshot(&self) -> Option<&str> {
+        self.volume_group_snapshot.as_deref()
+    }
+
+    /// Maximum time to wait for the members of a VolumeGroupSnapshot to be Bound, or for the
+    /// replaced PVCs to be deleted
+    pub fn bind_timeout(&self) -> Duration {
+        self.bind_timeout
+    }
+
+    /// AWS region of the cluster
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    /// EBS snapshot the PVC is restored from, instead of a VolumeSnapshot of the source namespace
+    pub fn ebs_snapshot_source(&self) -> Option<&EbsSnapshotSource> {
+        self.ebs_snapshot_source.as_ref()
+    }
+
+    pub fn restore_strategy(&self) -> RestoreStrategy {
+        self.restore_strategy
+    }
+
+    /// Settings of the EBS volumes created by the static volume strategy, overriding the
+    /// parameters of the StorageClass
+    pub fn ebs_volume_settings(&self) -> &EbsVolumeSettings {
+        &self.ebs_volume_settings
+    }
+
+    /// Reclaim policy of the PersistentVolumes created by the static volume strategy, the
+    /// one of their StorageClass if not set
+    pub fn pv_reclaim_policy(&self) -> Option<PVReclaimPolicy> {
+        self.pv_reclaim_policy
+    }
+
+    /// Access modes, volume mode, labels and annotations of the restored PVCs, overriding the
+    /// ones of the source PVCs
+    pub fn pvc_overrides(&self) -> &PvcTemplate {
+        &self.pvc_overrides
+    }
+
+    /// What to do with the PVCs that already exist in the target namespace
+    pub fn on_conflict(&self) -> ConflictPolicy {
+        self.on_conflict
+    }
+
+    /// How the PVCs are renamed in the target namespace
+    pub fn pvc_rename(&self) -> &PvcRename {
+        &self.pvc_rename
+    }
+
+    /// Check that the static volume strategy restores a backup ID or an EBS snapshot, as it
+    /// works without the CSI snapshot controller and cannot rely on the VolumeSnapshots of the
+    /// source namespace
+    pub fn validate_restore_strategy(&self) -> Result<()> {
+        if self.restore_strategy == RestoreStrategy::StaticVolume
+            && self.backup_id.is_none()
+            && self.ebs_snapshot_source.is_none()
+        {
+            bail!("The StaticVolume strategy requires a backup ID or an EBS snapshot to restore");
+        }
+        Ok(())
+    }
 }
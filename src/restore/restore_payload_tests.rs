@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        aws_ops::ebs::EbsSnapshotSource,
+        k8s_ops::vsc::retain_policy::VSCRetainPolicy,
+        restore::{restore_payload::RestorePayload, restore_strategy::RestoreStrategy},
+    };
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_target_storage_class_name() {
+        let restore_payload = RestorePayload::new(
+            "source",
+            "target",
+            "ebs-vsc",
+            None::<String>,
+            true,
+            "data",
+            "restored",
+            "gp3",
+            VSCRetainPolicy::Retain,
+            "eu-west-1",
+        )
+        .with_storage_class_mappings(BTreeMap::from([("gp2".to_string(), "io2".to_string())]));
+
+        assert_eq!(
+            restore_payload.target_storage_class_name(Some("gp2")),
+            "io2"
+        );
+        assert_eq!(
+            restore_payload.target_storage_class_name(Some("standard")),
+            "gp3"
+        );
+        assert_eq!(restore_payload.target_storage_class_name(None), "gp3");
+    }
+
+    #[test]
+    fn test_validate_restore_strategy() {
+        let restore_payload = |backup_id: Option<&str>,
+                               ebs_snapshot_source: Option<EbsSnapshotSource>,
+                               restore_strategy: RestoreStrategy| {
+            RestorePayload::new(
+                "source",
+                "target",
+                "ebs-vsc",
+                Some("data"),
+                false,
+                "data",
+                "restored",
+                "gp3",
+                VSCRetainPolicy::Retain,
+                "eu-west-1",
+            )
+            .with_backup_id(backup_id)
+            .with_ebs_snapshot_source(ebs_snapshot_source)
+            .with_restore_strategy(restore_strategy)
+        };
+        let ebs_snapshot_source = EbsSnapshotSource {
+            snapshot_id: "snap-0123456789abcdef0".to_string(),
+            region: None,
+            kms_key_id: None,
+            copy_timeout_secs: 60,
+        };
+
+        assert!(restore_payload(None, None, RestoreStrategy::Snapshot)
+            .validate_restore_strategy()
+            .is_ok());
+        assert!(
+            restore_payload(Some("20241018-120000"), None, RestoreStrategy::StaticVolume)
+                .validate_restore_strategy()
+                .is_ok()
+        );
+        assert!(restore_payload(
+            None,
+            Some(ebs_snapshot_source),
+            RestoreStrategy::StaticVolume
+        )
+        .validate_restore_strategy()
+        .is_ok());
+        // The newest VolumeSnapshot of the PVC cannot be looked up without the CSI snapshot
+        // controller
+        assert!(restore_payload(None, None, RestoreStrategy::StaticVolume)
+            .validate_restore_strategy()
+            .is_err());
+    }
+}
@@ -0,0 +1,27 @@
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// Represents how the PVCs are restored from their snapshots
+#[derive(
+    ValueEnum, Clone, Debug, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema,
+)]
+pub enum RestoreStrategy {
+    /// A pre-provisioned VolumeSnapshotContent and VolumeSnapshot, from which the CSI driver
+    /// provisions the PVC, which requires the CSI snapshot controller
+    #[default]
+    Snapshot,
+    /// An EBS volume created from the snapshot with EC2, and a static PersistentVolume bound
+    /// to the PVC, which works without the CSI snapshot controller
+    StaticVolume,
+}
+
+impl Display for RestoreStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RestoreStrategy::Snapshot => write!(f, "Snapshot"),
+            RestoreStrategy::StaticVolume => write!(f, "StaticVolume"),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Represents the outcome of restoring a single PVC
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PvcRestoreStatus {
+    Restored,
+    /// The PVC already existed in the target namespace and was left in place
+    Skipped,
+    /// The PVC already existed in the target namespace and was deleted before the restore
+    Replaced,
+    /// The PVC already existed in the target namespace and was restored under another name
+    Renamed(String),
+    Failed(String),
+}
+
+impl Display for PvcRestoreStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PvcRestoreStatus::Restored => write!(f, "Restored"),
+            PvcRestoreStatus::Skipped => write!(f, "Skipped, the PVC already exists"),
+            PvcRestoreStatus::Replaced => write!(f, "Replaced the existing PVC"),
+            PvcRestoreStatus::Renamed(name) => write!(f, "Restored as {}", name),
+            PvcRestoreStatus::Failed(e) => write!(f, "Failed: {}", e),
+        }
+    }
+}
+
+/// The result of restoring a single PVC
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PvcRestoreResult {
+    pub pvc_name: String,
+    pub status: PvcRestoreStatus,
+    /// Name the PVC is restored under, if it differs from the name of the source PVC
+    pub target_pvc_name: Option<String>,
+}
+
+impl PvcRestoreResult {
+    pub fn new(pvc_name: impl Into<String>, status: PvcRestoreStatus) -> Self {
+        Self {
+            pvc_name: pvc_name.into(),
+            status,
+            target_pvc_name: None,
+        }
+    }
+
+    /// Set the name the PVC is restored under
+    pub fn with_target_pvc_name(mut self, target_pvc_name: impl Into<String>) -> Self {
+        let target_pvc_name = target_pvc_name.into();
+        self.target_pvc_name = (target_pvc_name != self.pvc_name).then_some(target_pvc_name);
+        self
+    }
+
+    pub fn is_success(&self) -> bool {
+        !matches!(self.status, PvcRestoreStatus::Failed(_))
+    }
+
+    /// The name of the PVC restored in the target namespace, if any
+    pub fn restored_pvc_name(&self) -> Option<&str> {
+        match &self.status {
+            PvcRestoreStatus::Restored | PvcRestoreStatus::Replaced => {
+                Some(self.target_pvc_name.as_ref().unwrap_or(&self.pvc_name))
+            }
+            PvcRestoreStatus::Renamed(name) => Some(name),
+            PvcRestoreStatus::Skipped | PvcRestoreStatus::Failed(_) => None,
+        }
+    }
+}
+
+/// A per-PVC summary of a restore operation
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RestoreSummary {
+    pub results: Vec<PvcRestoreResult>,
+}
+
+impl RestoreSummary {
+    /// Creates a new RestoreSummary, sorted by PVC name
+    pub fn new(mut results: Vec<PvcRestoreResult>) -> Self {
+        results.sort_by(|a, b| a.pvc_name.cmp(&b.pvc_name));
+        Self { results }
+    }
+
+    pub fn succeeded(&self) -> impl Iterator<Item = &PvcRestoreResult> {
+        self.results.iter().filter(|result| result.is_success())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &PvcRestoreResult> {
+        self.results.iter().filter(|result| !result.is_success())
+    }
+
+    /// Whether every PVC was restored successfully
+    pub fn is_success(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+impl Display for RestoreSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Restore summary: {} succeeded, {} failed",
+            self.succeeded().count(),
+            self.failed().count()
+        )?;
+        for result in &self.results {
+            match &result.target_pvc_name {
+                Some(target_pvc_name) => writeln!(
+                    f,
+                    "  - {} -> {}: {}",
+                    result.pvc_name, target_pvc_name, result.status
+                )?,
+                None => writeln!(f, "  - {}: {}", result.pvc_name, result.status)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use crate::restore::restore_summary::{PvcRestoreResult, PvcRestoreStatus, RestoreSummary};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_restore_summary() {
+        let summary = RestoreSummary::new(vec![
+            PvcRestoreResult::new("pvc-b", PvcRestoreStatus::Failed("boom".to_string())),
+            PvcRestoreResult::new("pvc-a", PvcRestoreStatus::Restored),
+        ]);
+
+        assert_eq!(summary.results[0].pvc_name, "pvc-a");
+        assert_eq!(summary.succeeded().count(), 1);
+        assert_eq!(summary.failed().count(), 1);
+        assert!(!summary.is_success());
+        assert_eq!(
+            summary.to_string(),
+            "Restore summary: 1 succeeded, 1 failed\n  - pvc-a: Restored\n  - pvc-b: Failed: boom\n"
+        );
+    }
+
+    #[test]
+    fn test_restore_summary_with_conflicts() {
+        let summary = RestoreSummary::new(vec![
+            PvcRestoreResult::new("pvc-a", PvcRestoreStatus::Skipped),
+            PvcRestoreResult::new("pvc-b", PvcRestoreStatus::Replaced),
+            PvcRestoreResult::new(
+                "pvc-c",
+                PvcRestoreStatus::Renamed("pvc-c-restored".to_string()),
+            ),
+        ]);
+
+        assert!(summary.is_success());
+        assert_eq!(
+            summary
+                .results
+                .iter()
+                .map(PvcRestoreResult::restored_pvc_name)
+                .collect::<Vec<_>>(),
+            vec![None, Some("pvc-b"), Some("pvc-c-restored")]
+        );
+        assert_eq!(
+            summary.to_string(),
+            "Restore summary: 3 succeeded, 0 failed\n  - pvc-a: Skipped, the PVC already exists\n  - pvc-b: Replaced the existing PVC\n  - pvc-c: Restored as pvc-c-restored\n"
+        );
+    }
+
+    #[test]
+    fn test_restore_summary_with_target_pvc_names() {
+        let summary = RestoreSummary::new(vec![
+            PvcRestoreResult::new("data", PvcRestoreStatus::Restored)
+                .with_target_pvc_name("qa-data"),
+            PvcRestoreResult::new("wal", PvcRestoreStatus::Restored).with_target_pvc_name("wal"),
+        ]);
+
+        assert_eq!(summary.results[0].restored_pvc_name(), Some("qa-data"));
+        assert_eq!(summary.results[1].target_pvc_name, None);
+        assert_eq!(
+            summary.to_string(),
+            "Restore summary: 2 succeeded, 0 failed\n  - data -> qa-data: Restored\n  - wal: Restored\n"
+        );
+    }
+}
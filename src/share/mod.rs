@@ -0,0 +1,9 @@
+#[cfg(feature = "backup")]
+pub mod share_operator;
+#[cfg(feature = "backup")]
+pub mod share_payload;
+#[cfg(feature = "backup")]
+pub mod share_summary;
+
+#[cfg(all(test, feature = "backup"))]
+mod share_summary_tests;
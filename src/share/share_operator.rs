@@ -0,0 +1,133 @@
+use super::{
+    share_payload::SharePayload,
+    share_summary::{ShareResult, ShareStatus, ShareSummary},
+};
+use crate::{
+    aws_ops::{
+        ebs::{
+            create_ebs_client, revoke_ebs_snapshot_share, share_ebs_snapshot, validate_account_id,
+        },
+        kms::create_kms_client,
+    },
+    catalog::backup_catalog::open_catalog_store,
+};
+use anyhow::{bail, Context, Result};
+use kube::Client;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::error;
+
+/// A struct for sharing the EBS snapshots of the backups with other AWS accounts
+pub struct ShareOperator;
+
+impl ShareOperator {
+    /// Shares EBS snapshots with other AWS accounts, or revokes their share
+    ///
+    /// The snapshots are either given by ID, or read from the catalog entries of a backup ID,
+    /// in which case their copies in other regions are shared as well. Each snapshot is
+    /// shared on its own, so that a failure is reported in the returned summary without
+    /// stopping the others.
+    pub async fn share(share_payload: SharePayload) -> Result<ShareSummary> {
+        if share_payload.account_ids().is_empty() {
+            bail!("At least one AWS account ID is required");
+        }
+        for account_id in share_payload.account_ids() {
+            validate_account_id(account_id)?;
+        }
+
+        let snapshots = Self::get_snapshots(&share_payload).await?;
+        if snapshots.is_empty() {
+            bail!("No EBS snapshot to share");
+        }
+
+        let mut results = Vec::new();
+        for (region, snapshot_ids) in snapshots {
+            let ebs_client = create_ebs_client(Some(region.clone())).await?;
+            let kms_client = create_kms_client(Some(region.clone())).await?;
+            for snapshot_id in snapshot_ids {
+                let status = if share_payload.revoke() {
+                    revoke_ebs_snapshot_share(
+                        &ebs_client,
+                        &kms_client,
+                        &snapshot_id,
+                        share_payload.account_ids(),
+                    )
+                    .await
+                    .map(|_| ShareStatus::Revoked)
+                } else {
+                    share_ebs_snapshot(
+                        &ebs_client,
+                        &kms_client,
+                        &snapshot_id,
+                        share_payload.account_ids(),
+                    )
+                    .await
+                    .map(ShareStatus::Shared)
+                };
+                let status = status.unwrap_or_else(|e| {
+                    error!(
+                        "Failed to {} EBS snapshot {}: {:#}",
+                        if share_payload.revoke() {
+                            "revoke the share of"
+                        } else {
+                            "share"
+                        },
+                        snapshot_id,
+                        e
+                    );
+                    ShareStatus::Failed(format!("{:#}", e))
+                });
+                results.push(ShareResult::new(region.clone(), snapshot_id, status));
+            }
+        }
+
+        Ok(ShareSummary::new(
+            share_payload.account_ids().to_vec(),
+            results,
+        ))
+    }
+
+    /// Get the EBS snapshots to share
+    ///
+    /// # Arguments
+    ///
+    /// * `share_payload` - Payload of the share operation
+    ///
+    /// # Returns
+    ///
+    /// The IDs of the EBS snapshots, keyed by region
+    async fn get_snapshots(
+        share_payload: &SharePayload,
+    ) -> Result<BTreeMap<String, BTreeSet<String>>> {
+        let mut snapshots: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for snapshot_id in share_payload.snapshot_ids() {
+            snapshots
+                .entry(share_payload.region().to_string())
+                .or_default()
+                .insert(snapshot_id.clone());
+        }
+
+        // Read the snapshots of the backup to share, along with their copies, if any
+        if let Some(backup_id) = share_payload.backup_id() {
+            let catalog_location = share_payload
+                .catalog()
+                .context("A catalog is required to share a backup ID")?;
+            let k8s_client = Client::try_default().await?;
+            let entries = open_catalog_store(k8s_client, catalog_location)
+                .get(backup_id)
+                .await?;
+            for entry in entries {
+                snapshots
+                    .entry(share_payload.region().to_string())
+                    .or_default()
+                    .insert(entry.snapshot_handle);
+                for snapshot_copy in entry.snapshot_copies {
+                    snapshots
+                        .entry(snapshot_copy.region)
+                        .or_default()
+                        .insert(snapshot_copy.snapshot_id);
+                }
+            }
+        }
+        Ok(snapshots)
+    }
+}
@@ -0,0 +1,68 @@
+use crate::catalog::backup_catalog::CatalogLocation;
+
+pub struct SharePayload {
+    pub region: String,
+    pub snapshot_ids: Vec<String>,
+    pub backup_id: Option<String>,
+    pub catalog: Option<CatalogLocation>,
+    pub account_ids: Vec<String>,
+    pub revoke: bool,
+}
+
+impl SharePayload {
+    /// Creates a new SharePayload
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - AWS region of the EBS snapshots
+    /// * `snapshot_ids` - IDs of the EBS snapshots to share
+    /// * `backup_id` - Share the EBS snapshots of a backup ID recorded in the catalog, if set
+    /// * `catalog` - Where the backup catalog is stored, required by a backup ID
+    /// * `account_ids` - IDs of the AWS accounts to share the EBS snapshots with
+    /// * `revoke` - Revoke the share instead of granting it
+    ///
+    /// # Returns
+    ///
+    /// A new SharePayload instance
+    pub fn new(
+        region: impl Into<String>,
+        snapshot_ids: Vec<String>,
+        backup_id: Option<impl Into<String>>,
+        catalog: Option<CatalogLocation>,
+        account_ids: Vec<String>,
+        revoke: bool,
+    ) -> Self {
+        Self {
+            region: region.into(),
+            snapshot_ids,
+            backup_id: backup_id.map(|backup_id| backup_id.into()),
+            catalog,
+            account_ids,
+            revoke,
+        }
+    }
+
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    pub fn snapshot_ids(&self) -> &[String] {
+        &self.snapshot_ids
+    }
+
+    pub fn backup_id(&self) -> Option<&str> {
+        self.backup_id.as_deref()
+    }
+
+    pub fn catalog(&self) -> Option<&CatalogLocation> {
+        self.catalog.as_ref()
+    }
+
+    pub fn account_ids(&self) -> &[String] {
+        &self.account_ids
+    }
+
+    pub fn revoke(&self) -> bool {
+        self.revoke
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Represents the outcome of sharing a single EBS snapshot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShareStatus {
+    /// The snapshot was shared, along with its KMS key if it is encrypted
+    Shared(Option<String>),
+    /// The share of the snapshot and of its KMS key was revoked
+    Revoked,
+    Failed(String),
+}
+
+impl Display for ShareStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareStatus::Shared(None) => write!(f, "Shared"),
+            ShareStatus::Shared(Some(kms_key_id)) => {
+                write!(f, "Shared (KMS key {} granted)", kms_key_id)
+            }
+            ShareStatus::Revoked => write!(f, "Revoked"),
+            ShareStatus::Failed(e) => write!(f, "Failed: {}", e),
+        }
+    }
+}
+
+/// The result of sharing a single EBS snapshot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareResult {
+    pub region: String,
+    pub snapshot_id: String,
+    pub status: ShareStatus,
+}
+
+impl ShareResult {
+    pub fn new(
+        region: impl Into<String>,
+        snapshot_id: impl Into<String>,
+        status: ShareStatus,
+    ) -> Self {
+        Self {
+            region: region.into(),
+            snapshot_id: snapshot_id.into(),
+            status,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        !matches!(self.status, ShareStatus::Failed(_))
+    }
+}
+
+/// A per-snapshot summary of a share operation
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShareSummary {
+    pub account_ids: Vec<String>,
+    pub results: Vec<ShareResult>,
+}
+
+impl ShareSummary {
+    /// Creates a new ShareSummary, sorted by region and snapshot ID
+    pub fn new(account_ids: Vec<String>, mut results: Vec<ShareResult>) -> Self {
+        results.sort_by(|a, b| (&a.region, &a.snapshot_id).cmp(&(&b.region, &b.snapshot_id)));
+        Self {
+            account_ids,
+            results,
+        }
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &ShareResult> {
+        self.results.iter().filter(|result| !result.is_success())
+    }
+
+    /// Whether every EBS snapshot was shared or revoked successfully
+    pub fn is_success(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+impl Display for ShareSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Share summary for account(s) {}: {} EBS snapshot(s), {} failed",
+            self.account_ids.join(", "),
+            self.results.len(),
+            self.failed().count()
+        )?;
+        for result in &self.results {
+            writeln!(
+                f,
+                "  - {} ({}): {}",
+                result.snapshot_id, result.region, result.status
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+#[cfg(test)]
+mod tests {
+    use crate::share::share_summary::{ShareResult, ShareStatus, ShareSummary};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_share_summary() {
+        let summary = ShareSummary::new(
+            vec!["111111111111".to_string(), "222222222222".to_string()],
+            vec![
+                ShareResult::new(
+                    "us-east-1",
+                    "snap-0bbb",
+                    ShareStatus::Failed("boom".to_string()),
+                ),
+                ShareResult::new(
+                    "eu-west-1",
+                    "snap-0ccc",
+                    ShareStatus::Shared(Some(
+                        "arn:aws:kms:eu-west-1:333333333333:key/backup".to_string(),
+                    )),
+                ),
+                ShareResult::new("eu-west-1", "snap-0aaa", ShareStatus::Shared(None)),
+            ],
+        );
+
+        assert_eq!(summary.results[0].snapshot_id, "snap-0aaa");
+        assert!(!summary.is_success());
+        assert_eq!(
+            summary.to_string(),
+            "Share summary for account(s) 111111111111, 222222222222: 3 EBS snapshot(s), 1 failed\n  - snap-0aaa (eu-west-1): Shared\n  - snap-0ccc (eu-west-1): Shared (KMS key arn:aws:kms:eu-west-1:333333333333:key/backup granted)\n  - snap-0bbb (us-east-1): Failed: boom\n"
+        );
+    }
+}